@@ -2,33 +2,139 @@ pub mod operations;
 pub mod eval;
 pub mod simplify;
 pub mod expansion;
+pub mod latex;
+pub mod display;
+pub mod codegen;
+pub mod sexpr;
+pub mod pretty;
+pub mod traversal;
+pub mod substitution;
+pub mod ordering;
+pub mod calculus;
+pub mod polynomial;
+pub mod compile;
+pub mod rewrite;
+#[cfg(feature = "complex")]
+pub mod complex;
 
 use std::fmt::{self, Formatter, Display};
+use std::rc::Rc;
 use crate::symbol::Symbol;
+use crate::expr::display::DisplayOptions;
 
 /// Represents a mathematical expression.
 ///
-/// Expressions can be constants (floating point numbers), symbolic variables, or operations
-/// (addition, subtraction, multiplication, division, exponentiation, negation). Each operation
-/// can contain other expressions, allowing complex, nested expressions to be represented.
-#[derive(Debug, Clone, PartialEq)]
+/// Expressions can be constants (floating point numbers, exact rationals, or the named
+/// constants pi and e), symbolic variables, or operations (addition, subtraction,
+/// multiplication, division, exponentiation, negation) and elementary functions (sine, cosine,
+/// exponential, natural logarithm, absolute value, sign, square root). Each operation can
+/// contain other expressions, allowing complex, nested expressions to be represented.
+///
+/// Expressions are totally ordered and compared structurally; see the `ordering` module
+/// for the exact ordering.
+///
+/// Sub-expressions are reference counted, so cloning an expression only bumps the counts of
+/// its direct children and clones share their subtrees.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     /// A constant (floating point number).
     Const(f64),
+    /// An exact rational constant (numerator, denominator).
+    ///
+    /// Always stored in lowest terms with a positive denominator; see `Expr::new_rational`.
+    Rational(i64, i64),
+    /// The mathematical constant pi.
+    Pi,
+    /// Euler's number, the base of the natural logarithm.
+    E,
     /// A symbolic variable.
     Symbol(Symbol),
     /// Addition of two expressions.
-    Add(Box<Expr>, Box<Expr>),
+    Add(Rc<Expr>, Rc<Expr>),
     /// Subtraction of two expressions.
-    Sub(Box<Expr>, Box<Expr>),
+    Sub(Rc<Expr>, Rc<Expr>),
     /// Multiplication of two expressions.
-    Mul(Box<Expr>, Box<Expr>),
+    Mul(Rc<Expr>, Rc<Expr>),
     /// Division of two expressions.
-    Div(Box<Expr>, Box<Expr>),
+    Div(Rc<Expr>, Rc<Expr>),
     /// Exponentiation of two expressions.
-    Pow(Box<Expr>, Box<Expr>),
+    Pow(Rc<Expr>, Rc<Expr>),
     /// Negation of an expression.
-    Neg(Box<Expr>),
+    Neg(Rc<Expr>),
+    /// Sine of an expression (in radians).
+    Sin(Rc<Expr>),
+    /// Cosine of an expression (in radians).
+    Cos(Rc<Expr>),
+    /// Exponential function (e raised to an expression).
+    Exp(Rc<Expr>),
+    /// Natural logarithm of an expression.
+    Ln(Rc<Expr>),
+    /// Absolute value of an expression.
+    Abs(Rc<Expr>),
+    /// Sign of an expression: `-1`, `0`, or `1`.
+    Sign(Rc<Expr>),
+    /// Square root of an expression.
+    Sqrt(Rc<Expr>),
+    /// A finite sum of `body` over the integer values of `index` from `from` to `to`
+    /// inclusive, which is zero if `from > to`; see `Expr::summation`.
+    ///
+    /// The index is bound by the sum: `subs` leaves it alone inside the body, `variables`
+    /// does not report it and `eval` does not look it up. Structural searches such as
+    /// `contains_symbol` and `iter` see it like any other symbol of the body.
+    Sum { body: Rc<Expr>, index: Symbol, from: i64, to: i64 },
+    /// A finite product of `body` over the integer values of `index` from `from` to `to`
+    /// inclusive, which is one if `from > to`; see `Expr::product`.
+    ///
+    /// The index is bound by the product in the same way as by a `Sum`.
+    Product { body: Rc<Expr>, index: Symbol, from: i64, to: i64 },
+}
+
+impl Drop for Expr {
+    /// Dismantles the tree iteratively, so that dropping a deeply nested expression does not
+    /// overflow the stack.
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        self.detach_children(&mut stack);
+        while let Some(mut node) = stack.pop() {
+            node.detach_children(&mut stack);
+        }
+    }
+}
+
+impl Expr {
+    /// Moves the direct sub-expressions of the current expression onto `stack`, leaving
+    /// constant placeholders in their place.
+    ///
+    /// Only children that are not shared with another expression are moved; a shared child
+    /// is kept alive by its other owners, so releasing this reference to it cannot recurse.
+    fn detach_children(&mut self, stack: &mut Vec<Expr>) {
+        let mut detach = |child: &mut Rc<Expr>| {
+            if let Some(child) = Rc::get_mut(child) {
+                stack.push(std::mem::replace(child, Expr::Const(0.0)));
+            }
+        };
+        match self {
+            Expr::Const(_) | Expr::Rational(_, _) | Expr::Pi | Expr::E | Expr::Symbol(_) => {}
+            Expr::Add(lhs, rhs)
+                | Expr::Sub(lhs, rhs)
+                | Expr::Mul(lhs, rhs)
+                | Expr::Div(lhs, rhs)
+                | Expr::Pow(lhs, rhs) => {
+                detach(lhs);
+                detach(rhs);
+            }
+            Expr::Neg(expr)
+                | Expr::Sin(expr)
+                | Expr::Cos(expr)
+                | Expr::Exp(expr)
+                | Expr::Ln(expr)
+                | Expr::Abs(expr)
+                | Expr::Sign(expr)
+                | Expr::Sqrt(expr) => detach(expr),
+            Expr::Sum { body, .. } | Expr::Product { body, .. } => detach(body),
+        }
+    }
 }
 
 // Constructors
@@ -59,6 +165,71 @@ impl Expr {
         Expr::Const(val)
     }
 
+    /// Constructs the constant zero, the identity of addition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// assert_eq!((Expr::zero() + x.clone()).simplify(), x);
+    /// ```
+    pub fn zero() -> Expr {
+        Expr::Const(0.0)
+    }
+
+    /// Constructs the constant one, the identity of multiplication.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// assert_eq!((Expr::one() * x.clone()).simplify(), x);
+    /// ```
+    pub fn one() -> Expr {
+        Expr::Const(1.0)
+    }
+
+    /// Constructs a new exact rational constant `num / den`.
+    ///
+    /// The fraction is reduced to lowest terms and the sign is moved to the numerator, so
+    /// the stored denominator is always positive.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `den` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let half = Expr::new_rational(2, -4);
+    /// assert_eq!(half, Expr::Rational(-1, 2));
+    /// ```
+    pub fn new_rational(num: i64, den: i64) -> Expr {
+        assert!(den != 0, "Cannot construct a rational with a zero denominator");
+        let divisor = gcd(num, den);
+        let (num, den) = (num / divisor, den / divisor);
+        if den < 0 {
+            Expr::Rational(-num, -den)
+        } else {
+            Expr::Rational(num, den)
+        }
+    }
+}
+
+/// Returns the greatest common divisor of two integers, which is always positive for
+/// non-zero input.
+pub(crate) fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
 }
 
 // Borrows Data
@@ -82,32 +253,43 @@ impl Expr {
     }
 }
 
-impl Display for Expr {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+// Rendering helpers
+impl Expr {
+    /// Returns the binding strength of the outermost operation of the expression.
+    ///
+    /// Higher values bind tighter. Renderers use this to decide where parentheses are
+    /// required: sums and differences bind loosest, then products and quotients, negation,
+    /// exponentiation, and finally atoms and function calls.
+    pub(crate) fn precedence(&self) -> u8 {
         match self {
-            Expr::Const(c) => write!(f, "{}", c),
-            Expr::Symbol(s) => write!(f, "{}", s.name),
-            Expr::Add(lhs, rhs) => write!(f, "({} + {})", lhs, rhs),
-            Expr::Sub(lhs, rhs) => write!(f, "({} - {})", lhs, rhs),
-            Expr::Mul(lhs, rhs) => {
-                if let Expr::Const(c) = **lhs {
-                    if let Expr::Symbol(_) = **rhs {
-                        return write!(f, "{}{}", c, rhs);
-                    }
-                } else if let Expr::Const(c) = **rhs {
-                    if let Expr::Symbol(_) = **lhs {
-                        return write!(f, "{}{}", c, lhs);
-                    }
-                }
-                write!(f, "({} * {})", lhs, rhs)
-            }
-            Expr::Div(lhs, rhs) => write!(f, "({} / {})", lhs, rhs),
-            Expr::Pow(lhs, rhs) => write!(f, "({} ^ {})", lhs, rhs),
-            Expr::Neg(expr) => write!(f, "-{}", expr),
+            Expr::Add(_, _) | Expr::Sub(_, _) => 1,
+            Expr::Mul(_, _) | Expr::Div(_, _) => 2,
+            Expr::Neg(_) => 3,
+            Expr::Const(c) if *c < 0.0 => 3,
+            Expr::Rational(_, d) if *d != 1 => 2,
+            Expr::Rational(n, _) if *n < 0 => 3,
+            Expr::Pow(_, _) => 4,
+            _ => 5,
         }
     }
 }
 
+impl Display for Expr {
+    /// Formats the expression using standard operator precedence.
+    ///
+    /// Parentheses are only emitted where they change the meaning of the expression, so
+    /// `(x + y) + z` is written `x + y + z` while `x - (y - z)` keeps its parentheses.
+    /// Exponentiation binds tighter than multiplication and is right-associative. Constants
+    /// are rounded to `DISPLAY_SIGNIFICANT_DIGITS` significant digits, and whole numbers are
+    /// written without a fractional part. Powers with the exact exponents `1/2` and `1/3` are
+    /// written `sqrt(x)` and `cbrt(x)`. Sums and products over an index are written
+    /// `sum(i=a..b, body)` and `prod(i=a..b, body)`. Use `display_with` for other rendering
+    /// styles.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.write_with(f, &DisplayOptions::default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +298,168 @@ mod tests {
     fn add_const() {
         let lhs = Expr::Const(2.0);
         let rhs = Expr::Const(4.0);
-        assert_eq!(Expr::Add(Box::new(lhs.clone()), Box::new(rhs.clone())), lhs + rhs);
+        assert_eq!(Expr::Add(Rc::new(lhs.clone()), Rc::new(rhs.clone())), lhs + rhs);
+    }
+
+    #[test]
+    fn clone_shares_children() {
+        let sum = Expr::new_var("x") + Expr::new_var("y");
+        let copy = sum.clone();
+
+        match (&sum, &copy) {
+            (Expr::Add(lhs, rhs), Expr::Add(copy_lhs, copy_rhs)) => {
+                assert!(Rc::ptr_eq(lhs, copy_lhs));
+                assert!(Rc::ptr_eq(rhs, copy_rhs));
+                assert_eq!(Rc::strong_count(lhs), 2);
+            }
+            _ => unreachable!(),
+        }
+        drop(copy);
+        match &sum {
+            Expr::Add(lhs, _) => assert_eq!(Rc::strong_count(lhs), 1),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn drop_deep_shared_chain() {
+        let x = Expr::new_var("x");
+        let mut res = x.clone();
+        for _ in 0..100_000 {
+            res += x.clone();
+        }
+        let copy = res.clone();
+        drop(res);
+        assert_eq!(copy.depth(), 100_001);
+        drop(copy);
+    }
+
+    #[test]
+    fn display_minimal_parens() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let z = Expr::new_var("z");
+        assert_eq!((x.clone() + y.clone() + z.clone()).to_string(), "x + y + z");
+        assert_eq!((x.clone() - (y.clone() - z.clone())).to_string(), "x - (y - z)");
+        assert_eq!((x.clone() - y.clone() - z.clone()).to_string(), "x - y - z");
+        assert_eq!(((x.clone() + y.clone()) * z.clone()).to_string(), "(x + y) * z");
+        assert_eq!((x.clone() * y.clone() + z.clone()).to_string(), "x * y + z");
+        assert_eq!((x.clone() / (y.clone() * z.clone())).to_string(), "x / (y * z)");
+        assert_eq!((x.clone() * y.clone() / z.clone()).to_string(), "x * y / z");
+        assert_eq!((-(x.clone() + y.clone())).to_string(), "-(x + y)");
+        assert_eq!((x.clone() + y.clone()).sin().to_string(), "sin(x + y)");
+    }
+
+    #[test]
+    fn display_negation_in_sums() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let z = Expr::new_var("z");
+
+        assert_eq!((x.clone() + -y.clone()).to_string(), "x - y");
+        assert_eq!((-x.clone() + y.clone()).to_string(), "y - x");
+        assert_eq!((x.clone() - -y.clone()).to_string(), "x + y");
+        assert_eq!((-x.clone() + -y.clone()).to_string(), "-x - y");
+        assert_eq!((x.clone() + -(y.clone() + z.clone())).to_string(), "x - (y + z)");
+        assert_eq!((-(x.clone() - y.clone()) + z.clone()).to_string(), "z - (x - y)");
+        assert_eq!((x.clone() - -(y.clone() - z.clone())).to_string(), "x + y - z");
+    }
+
+    #[test]
+    fn display_pow_precedence() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let z = Expr::new_var("z");
+        assert_eq!(x.clone().pow(y.clone().pow(z.clone())).to_string(), "x^y^z");
+        assert_eq!(x.clone().pow(y.clone()).pow(z.clone()).to_string(), "(x^y)^z");
+        assert_eq!((x.clone() * y.clone()).pow(z.clone()).to_string(), "(x * y)^z");
+        assert_eq!((x.clone() * y.clone().pow(z.clone())).to_string(), "x * y^z");
+        assert_eq!((-x.clone()).pow(y.clone()).to_string(), "(-x)^y");
+        assert_eq!(Expr::new_val(-2.0).pow(x.clone()).to_string(), "(-2)^x");
+        assert_eq!((2.0 * x.clone() + y.clone().pow(Expr::new_val(2.0))).pow(z).to_string(), "(2x + y^2)^z");
+    }
+
+    #[test]
+    fn rational_reduced() {
+        assert_eq!(Expr::new_rational(2, 6), Expr::Rational(1, 3));
+        assert_eq!(Expr::new_rational(3, -9), Expr::Rational(-1, 3));
+        assert_eq!(Expr::new_rational(0, -5), Expr::Rational(0, 1));
+    }
+
+    #[test]
+    fn display_rational() {
+        let x = Expr::new_var("x");
+        assert_eq!(Expr::new_rational(1, 3).to_string(), "1/3");
+        assert_eq!(Expr::new_rational(4, 2).to_string(), "2");
+        assert_eq!(x.clone().pow(Expr::new_rational(2, 3)).to_string(), "x^(2/3)");
+        assert_eq!((x / Expr::new_rational(1, 3)).to_string(), "x / (1/3)");
+    }
+
+    #[test]
+    fn display_sum() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let res = i.clone().pow(Expr::new_val(2.0)).summation(&index, 1, 3);
+
+        assert_eq!(res.to_string(), "sum(i=1..3, i^2)");
+        assert_eq!((2.0 * (x + i).summation(&index, -1, 1)).to_string(), "2 * sum(i=-1..1, x + i)");
+        assert_eq!(res.to_latex(), "\\sum_{i=1}^{3} i^{2}");
+        assert_eq!(res.pretty(), "             2\nsum(i=1..3, i )");
+    }
+
+    #[test]
+    fn display_product() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let res = Expr::product(i.clone(), &index, 1, 4);
+
+        assert_eq!(res.to_string(), "prod(i=1..4, i)");
+        assert_eq!((x.clone() / (x - i).product(&index, 0, 2)).to_string(), "x / prod(i=0..2, x - i)");
+        assert_eq!(res.to_latex(), "\\prod_{i=1}^{4} i");
+        assert_eq!(res.to_python(), "Product(i, (i, 1, 4))");
+        assert_eq!(res.to_c(), "(1.0 * 2.0 * 3.0 * 4.0)");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = (x + 2.0).pow(y);
+
+        let json = serde_json::to_string(&res).unwrap();
+        let back: Expr = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, res);
+    }
+
+    #[test]
+    fn display_named_constants() {
+        assert_eq!(Expr::Pi.to_string(), "pi");
+        assert_eq!(Expr::E.to_string(), "e");
+        assert_eq!((2.0 * Expr::Pi).to_string(), "2 * pi");
+        assert_eq!(Expr::E.pow(Expr::new_var("x")).to_string(), "e^x");
+    }
+
+    #[test]
+    fn display_functions() {
+        let x = Expr::new_var("x");
+        assert_eq!(x.clone().sin().to_string(), "sin(x)");
+        assert_eq!(x.clone().cos().to_string(), "cos(x)");
+        assert_eq!(x.clone().exp().to_string(), "exp(x)");
+        assert_eq!(x.clone().ln().to_string(), "ln(x)");
+        assert_eq!((x.clone() - 1.0).abs().to_string(), "|x - 1|");
+        assert_eq!(x.clone().sign().to_string(), "sign(x)");
+        assert_eq!(x.sqrt().to_string(), "sqrt(x)");
+    }
+
+    #[test]
+    fn display_indexed_symbols() {
+        let x1 = Expr::Symbol(Symbol::indexed("x", 1));
+        let x2 = Expr::Symbol(Symbol::indexed("x", 2));
+        assert_ne!(x1, x2);
+        assert_eq!(x1.to_string(), "x_1");
+        assert_eq!((x1 + x2).to_string(), "x_1 + x_2");
     }
 }
@@ -0,0 +1,995 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::expr::Expr;
+use crate::expr::eval::{subtree_hashes, EvalError, MemoKey};
+use crate::symbol::Symbol;
+
+impl Expr {
+    /// Returns the symbolic derivative of the current expression with respect to `var`.
+    ///
+    /// The derivative is built directly from the sum, product, quotient, power, and chain
+    /// rules and is not simplified; call `simplify` on the result for a tidier form.
+    ///
+    /// The derivative of `|u|` is taken to be `u / |u| * u'`, which is undefined where `u` is
+    /// zero, and the derivative of `sign(u)` is taken to be zero everywhere it is defined.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = (x.clone() * x.clone()).diff(&Symbol::new("x"));
+    /// assert_eq!(res.simplify(), Expr::new_val(2.0) * x);
+    /// ```
+    pub fn diff(&self, var: &Symbol) -> Expr {
+        // Nodes are differentiated after their children, with an explicit stack so deep trees
+        // do not overflow the call stack. Each finished node leaves its derivative and
+        // whether it depends on `var` on `done`, in the order of its siblings
+        let mut done: Vec<(Rc<Expr>, bool)> = Vec::new();
+        let mut stack = vec![(self, false)];
+        while let Some((node, ready)) = stack.pop() {
+            let children = node.children();
+            if ready {
+                let args = done.split_off(done.len() - children.len());
+                let depends = matches!(node, Expr::Symbol(s) if s == var) || args.iter().any(|(_, depends)| *depends);
+                let deriv = node.diff_node(var, |i| args[i].0.clone(), |i| args[i].1);
+                done.push((Rc::new(deriv), depends));
+            } else {
+                stack.push((node, true));
+                stack.extend(children.into_iter().rev().map(|child| (child, false)));
+            }
+        }
+        let (deriv, _) = done.pop().expect("the root is differentiated last");
+        Rc::try_unwrap(deriv).unwrap_or_else(|deriv| (*deriv).clone())
+    }
+
+    /// Returns the `n`-th derivative of the current expression with respect to `var`.
+    ///
+    /// The expression is differentiated `n` times with `diff`, and each derivative is
+    /// simplified before the next is taken, so the tree stays small as `n` grows and the
+    /// result is simplified. For `n = 0` the expression is returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `var` - The symbol to differentiate with respect to.
+    /// * `n` - The number of times to differentiate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = x.clone().sin().diff_n(&Symbol::new("x"), 2);
+    /// assert_eq!(res, -x.sin());
+    /// ```
+    pub fn diff_n(&self, var: &Symbol, n: usize) -> Expr {
+        (0..n).fold(self.clone(), |deriv, _| deriv.diff(var).simplify())
+    }
+
+    /// Returns the derivative of the current expression with respect to `var` like `diff`,
+    /// differentiating each distinct subexpression only once.
+    ///
+    /// Derivatives are cached by structure for the duration of the call, so a subtree that
+    /// occurs many times is differentiated on its first occurrence, and every occurrence of
+    /// its derivative in the result shares the same allocation. A subtree shared through the
+    /// same `Rc` is also only visited once. The result is equal to that of `diff`. This pays
+    /// off for generated expressions with heavy repetition, such as repeated squaring or
+    /// layered network-like computations, where `diff` does work proportional to the fully
+    /// unshared tree while `diff_memo` does work proportional to the distinct allocations;
+    /// for expressions without repeated subtrees plain `diff` is cheaper.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let common = (x.clone() + 1.0).sin();
+    /// let res = common.clone() * common.clone() + common;
+    /// assert_eq!(res.diff_memo(&Symbol::new("x")), res.diff(&Symbol::new("x")));
+    /// ```
+    pub fn diff_memo(&self, var: &Symbol) -> Expr {
+        let nodes = self.distinct_nodes();
+        let hashes = subtree_hashes(&nodes);
+        let key = |node| MemoKey { hash: hashes[&(node as *const Expr)], expr: node };
+        // Each distinct subtree maps to its derivative and whether it depends on `var`
+        let mut cache: HashMap<MemoKey, (Rc<Expr>, bool)> = HashMap::new();
+        for node in nodes {
+            if cache.contains_key(&key(node)) {
+                continue;
+            }
+            let args: Vec<&(Rc<Expr>, bool)> = node.children().into_iter().map(|child| &cache[&key(child)]).collect();
+            let depends = matches!(node, Expr::Symbol(s) if s == var) || args.iter().any(|(_, depends)| *depends);
+            let deriv = node.diff_node(var, |i| args[i].0.clone(), |i| args[i].1);
+            cache.insert(key(node), (Rc::new(deriv), depends));
+        }
+        let (deriv, _) = cache.remove(&key(self)).expect("the root is differentiated last");
+        Rc::try_unwrap(deriv).unwrap_or_else(|deriv| (*deriv).clone())
+    }
+
+    /// Applies the differentiation rule for the root of the current expression, given the
+    /// derivative `d(i)` of its `i`-th child and whether that child `depends(i)` on `var`.
+    fn diff_node<D, P>(&self, var: &Symbol, d: D, depends: P) -> Expr
+    where
+        D: Fn(usize) -> Rc<Expr>,
+        P: Fn(usize) -> bool,
+    {
+        match self {
+            Expr::Const(_) | Expr::Rational(_, _) | Expr::Pi | Expr::E => Expr::new_val(0.0),
+            Expr::Symbol(s) => Expr::new_val(if s == var { 1.0 } else { 0.0 }),
+            Expr::Add(_, _) => Expr::Add(d(0), d(1)),
+            Expr::Sub(_, _) => Expr::Sub(d(0), d(1)),
+            // (uv)' = u'v + uv'
+            Expr::Mul(lhs, rhs) => Expr::Add(
+                Rc::new(Expr::Mul(d(0), rhs.clone())),
+                Rc::new(Expr::Mul(lhs.clone(), d(1))),
+            ),
+            // (u/v)' = (u'v - uv') / v^2
+            Expr::Div(lhs, rhs) => Expr::Div(
+                Rc::new(Expr::Sub(
+                    Rc::new(Expr::Mul(d(0), rhs.clone())),
+                    Rc::new(Expr::Mul(lhs.clone(), d(1))),
+                )),
+                Rc::new(Expr::Pow(rhs.clone(), Rc::new(Expr::new_val(2.0)))),
+            ),
+            Expr::Pow(base, exp) => {
+                if !depends(1) {
+                    // (u^n)' = n * u^(n-1) * u'
+                    Expr::Mul(
+                        Rc::new(Expr::Mul(
+                            exp.clone(),
+                            Rc::new(Expr::Pow(base.clone(), Rc::new(Expr::Sub(exp.clone(), Rc::new(Expr::new_val(1.0)))))),
+                        )),
+                        d(0),
+                    )
+                } else if !depends(0) {
+                    // (a^v)' = a^v * ln(a) * v'
+                    Expr::Mul(
+                        Rc::new(Expr::Mul(Rc::new(self.clone()), Rc::new(Expr::Ln(base.clone())))),
+                        d(1),
+                    )
+                } else {
+                    // (u^v)' = u^v * (v' * ln(u) + v * u' / u)
+                    Expr::Mul(
+                        Rc::new(self.clone()),
+                        Rc::new(Expr::Add(
+                            Rc::new(Expr::Mul(d(1), Rc::new(Expr::Ln(base.clone())))),
+                            Rc::new(Expr::Div(Rc::new(Expr::Mul(exp.clone(), d(0))), base.clone())),
+                        )),
+                    )
+                }
+            }
+            Expr::Neg(_) => Expr::Neg(d(0)),
+            Expr::Sin(expr) => Expr::Mul(Rc::new(Expr::Cos(expr.clone())), d(0)),
+            Expr::Cos(expr) => Expr::Mul(Rc::new(Expr::Neg(Rc::new(Expr::Sin(expr.clone())))), d(0)),
+            Expr::Exp(_) => Expr::Mul(Rc::new(self.clone()), d(0)),
+            Expr::Ln(expr) => Expr::Div(d(0), expr.clone()),
+            Expr::Abs(expr) => Expr::Mul(Rc::new(Expr::Div(expr.clone(), Rc::new(self.clone()))), d(0)),
+            Expr::Sign(_) => Expr::new_val(0.0),
+            // sqrt(u)' = u' / (2 * sqrt(u))
+            Expr::Sqrt(_) => Expr::Div(
+                d(0),
+                Rc::new(Expr::Mul(Rc::new(Expr::new_val(2.0)), Rc::new(self.clone()))),
+            ),
+            // The derivative of a sum is the sum of the derivatives, and a sum does not
+            // depend on its own index
+            Expr::Sum { index, .. } if index == var => Expr::new_val(0.0),
+            Expr::Sum { index, from, to, .. } => Expr::Sum { body: d(0), index: index.clone(), from: *from, to: *to },
+            // The product rule has no closed form over an index, so the product is written
+            // out and differentiated factor by factor
+            Expr::Product { index, .. } if index == var => Expr::new_val(0.0),
+            Expr::Product { .. } => self.expand_products().diff(var),
+        }
+    }
+
+    /// Returns the gradient of the current expression: its partial derivative with respect to
+    /// each of `vars`, in order.
+    ///
+    /// Like `diff`, the components are not simplified.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = x.clone() * y.clone();
+    /// let grad = res.gradient(&[Symbol::new("x"), Symbol::new("y")]);
+    /// assert_eq!(grad.iter().map(Expr::simplify).collect::<Vec<_>>(), vec![y, x]);
+    /// ```
+    pub fn gradient(&self, vars: &[Symbol]) -> Vec<Expr> {
+        vars.iter().map(|var| self.diff(var)).collect()
+    }
+
+    /// Returns the Hessian of the current expression: the matrix of second partial
+    /// derivatives, where entry `[i][j]` differentiates with respect to `vars[i]` and then
+    /// `vars[j]`.
+    ///
+    /// It is computed by differentiating each component of the gradient again, and the
+    /// entries are not simplified. Mixed partials are built in different orders, so they may
+    /// only agree structurally after `simplify`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = x * y;
+    /// let hessian = res.hessian(&[Symbol::new("x"), Symbol::new("y")]);
+    /// assert_eq!(hessian[0][1].simplify(), Expr::new_val(1.0));
+    /// assert_eq!(hessian[1][1].simplify(), Expr::new_val(0.0));
+    /// ```
+    pub fn hessian(&self, vars: &[Symbol]) -> Vec<Vec<Expr>> {
+        self.gradient(vars).iter().map(|partial| partial.gradient(vars)).collect()
+    }
+
+    /// Returns a symbolic antiderivative of the current expression with respect to `var`.
+    ///
+    /// Handles expressions that do not depend on `var`, powers of `var` (`x^n` becomes
+    /// `x^(n+1)/(n+1)` for constant `n != -1`), sums, differences, negations, and products or
+    /// quotients with a factor that does not depend on `var`. The constant of integration is
+    /// omitted. Returns `None` for any other integrand rather than guessing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = x.clone().pow(Expr::new_val(2.0)).integrate(&Symbol::new("x"));
+    /// assert_eq!(res, Some(x.pow(Expr::new_val(3.0)) / Expr::new_val(3.0)));
+    /// ```
+    pub fn integrate(&self, var: &Symbol) -> Option<Expr> {
+        // A subtree without `var` is kept as it is until its parent is combined, so a constant
+        // factor stays a factor and only a constant term becomes `c * var`
+        enum Part {
+            Constant,
+            Integral(Expr),
+        }
+        let integral = |expr: &Expr, part: Part| match part {
+            Part::Integral(res) => res,
+            Part::Constant => Expr::Mul(Rc::new(expr.clone()), Rc::new(Expr::Symbol(var.clone()))),
+        };
+        let res = self.try_fold_up(|node, parts| {
+            if !matches!(node, Expr::Symbol(s) if s == var) && parts.iter().all(|part| matches!(part, Part::Constant)) {
+                return Ok(Part::Constant);
+            }
+            let mut parts = parts.into_iter();
+            let mut next = || parts.next().expect("each child has a part");
+            let res = match node {
+                Expr::Symbol(_) => power_rule(var, 1.0),
+                Expr::Pow(base, exp) => match (&**base, &**exp) {
+                    (Expr::Symbol(s), Expr::Const(n)) if s == var && *n != -1.0 => power_rule(var, *n),
+                    _ => return Err(()),
+                },
+                Expr::Add(lhs, rhs) => Expr::Add(Rc::new(integral(lhs, next())), Rc::new(integral(rhs, next()))),
+                Expr::Sub(lhs, rhs) => Expr::Sub(Rc::new(integral(lhs, next())), Rc::new(integral(rhs, next()))),
+                Expr::Neg(expr) => Expr::Neg(Rc::new(integral(expr, next()))),
+                Expr::Mul(lhs, rhs) => match (next(), next()) {
+                    (Part::Constant, Part::Integral(rhs)) => Expr::Mul(lhs.clone(), Rc::new(rhs)),
+                    (Part::Integral(lhs), Part::Constant) => Expr::Mul(Rc::new(lhs), rhs.clone()),
+                    _ => return Err(()),
+                },
+                Expr::Div(_, rhs) => match (next(), next()) {
+                    (Part::Integral(lhs), Part::Constant) => Expr::Div(Rc::new(lhs), rhs.clone()),
+                    _ => return Err(()),
+                },
+                _ => return Err(()),
+            };
+            Ok(Part::Integral(res))
+        });
+        Some(integral(self, res.ok()?))
+    }
+
+    /// Approximates the definite integral of the current expression over `var` from `a` to `b`.
+    ///
+    /// Uses the composite Simpson's rule over `n` subintervals. The expression is evaluated at
+    /// each node with `var` set to the node's position and every other symbol taken from `vars`.
+    ///
+    /// Returns `Err(EvalError::InvalidArgument)` if `n` is zero or odd, and propagates any error
+    /// raised while evaluating the expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `var` - The variable of integration.
+    /// * `a` - The lower bound of integration.
+    /// * `b` - The upper bound of integration.
+    /// * `n` - The number of subintervals, which must be even.
+    /// * `vars` - Values for any other symbols in the expression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let area = x.pow(Expr::new_val(2.0)).integrate_numeric(&Symbol::new("x"), 0.0, 1.0, 10, &HashMap::new());
+    /// assert!((area.unwrap() - 1.0 / 3.0).abs() < 1e-10);
+    /// ```
+    pub fn integrate_numeric(&self, var: &Symbol, a: f64, b: f64, n: usize, vars: &HashMap<Symbol, f64>) -> Result<f64, EvalError> {
+        if n == 0 || !n.is_multiple_of(2) {
+            return Err(EvalError::InvalidArgument("Simpson's rule requires an even, non-zero number of subintervals"));
+        }
+        let h = (b - a) / n as f64;
+        let mut vars = vars.clone();
+        let mut sum = 0.0;
+        for i in 0..=n {
+            vars.insert(var.clone(), a + i as f64 * h);
+            let weight = if i == 0 || i == n { 1.0 } else if i % 2 == 1 { 4.0 } else { 2.0 };
+            sum += weight * self.eval(&vars)?;
+        }
+        Ok(sum * h / 3.0)
+    }
+
+    /// Approximates the derivative of the current expression with respect to `var` at the
+    /// point given by `vars`, using the central difference `(f(x + h) - f(x - h)) / 2h`.
+    ///
+    /// This works for any expression that can be evaluated near the point, including ones
+    /// `diff` cannot differentiate symbolically. The error shrinks with `h` like `h^2` until
+    /// rounding error takes over; see `diff_numeric_default` for a reasonable choice of `h`.
+    ///
+    /// Returns `Err(EvalError::SymbolNotFound)` if `vars` has no value for `var`,
+    /// `Err(EvalError::InvalidArgument)` if `h` is not positive and finite, and propagates any
+    /// error raised while evaluating the expression at `x - h` or `x + h`.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `var` - The variable to differentiate with respect to.
+    /// * `vars` - The point to differentiate at, including a value for `var`.
+    /// * `h` - The step size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let mut vars = HashMap::new();
+    /// vars.insert(Symbol::new("x"), 0.0);
+    /// let slope = x.sin().diff_numeric(&Symbol::new("x"), &vars, 1e-4).unwrap();
+    /// assert!((slope - 1.0).abs() < 1e-8);
+    /// ```
+    pub fn diff_numeric(&self, var: &Symbol, vars: &HashMap<Symbol, f64>, h: f64) -> Result<f64, EvalError> {
+        if !(h.is_finite() && h > 0.0) {
+            return Err(EvalError::InvalidArgument("the step size must be positive and finite"));
+        }
+        let x = *vars.get(var).ok_or_else(|| EvalError::SymbolNotFound(var.clone()))?;
+        let mut vars = vars.clone();
+        vars.insert(var.clone(), x + h);
+        let upper = self.eval(&vars)?;
+        vars.insert(var.clone(), x - h);
+        let lower = self.eval(&vars)?;
+        Ok((upper - lower) / (2.0 * h))
+    }
+
+    /// The step size that `diff_numeric_default` uses near zero, relative to the magnitude of
+    /// the point elsewhere.
+    pub const DEFAULT_DIFF_STEP: f64 = 1e-5;
+
+    /// Approximates the derivative of the current expression like `diff_numeric`, with a step
+    /// size of `Expr::DEFAULT_DIFF_STEP` scaled by the magnitude of the point when it is
+    /// larger than one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let mut vars = HashMap::new();
+    /// vars.insert(Symbol::new("x"), 2.0);
+    /// let slope = x.clone().pow(Expr::new_val(3.0)).diff_numeric_default(&Symbol::new("x"), &vars).unwrap();
+    /// assert!((slope - 12.0).abs() < 1e-6);
+    /// ```
+    pub fn diff_numeric_default(&self, var: &Symbol, vars: &HashMap<Symbol, f64>) -> Result<f64, EvalError> {
+        let x = *vars.get(var).ok_or_else(|| EvalError::SymbolNotFound(var.clone()))?;
+        self.diff_numeric(var, vars, Expr::DEFAULT_DIFF_STEP * x.abs().max(1.0))
+    }
+
+    /// Finds a root of the current expression in `var` with Newton's method, starting from
+    /// `guess`.
+    ///
+    /// Each iteration replaces `x` by `x - f(x) / f'(x)`, where `f'` is the symbolic
+    /// derivative from `diff`. The iteration stops with the new `x` once a step is no larger
+    /// than `tol`, or with the current `x` if `f(x)` is exactly zero. Which root is found, if
+    /// any, depends on the guess.
+    ///
+    /// Returns `None` if the derivative evaluates to zero, the expression or its derivative
+    /// cannot be evaluated, an iterate is not finite, or there is no convergence within
+    /// `iters` iterations.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `var` - The variable to solve for.
+    /// * `guess` - The starting value of `var`.
+    /// * `vars` - Values for any other symbols in the expression.
+    /// * `iters` - The maximum number of iterations.
+    /// * `tol` - The step size below which the iteration has converged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = x.clone().cos() - x;
+    /// let root = res.solve_newton(&Symbol::new("x"), 1.0, &HashMap::new(), 50, 1e-12).unwrap();
+    /// assert!((root.cos() - root).abs() < 1e-12);
+    /// ```
+    pub fn solve_newton(&self, var: &Symbol, guess: f64, vars: &HashMap<Symbol, f64>, iters: usize, tol: f64) -> Option<f64> {
+        let derivative = self.diff(var).simplify();
+        let mut vars = vars.clone();
+        let mut x = guess;
+        for _ in 0..iters {
+            vars.insert(var.clone(), x);
+            let fx = self.eval(&vars).ok()?;
+            if fx == 0.0 {
+                return Some(x);
+            }
+            let dfx = derivative.eval(&vars).ok()?;
+            if dfx == 0.0 {
+                return None;
+            }
+            let next = x - fx / dfx;
+            if !next.is_finite() {
+                return None;
+            }
+            if (next - x).abs() <= tol {
+                return Some(next);
+            }
+            x = next;
+        }
+        None
+    }
+
+    /// Computes the two-sided limit of the current expression as `var` approaches `at`.
+    ///
+    /// The expression is simplified and evaluated at `at` first. Unless it contains a sign,
+    /// an absolute value or a division, it is continuous wherever it is defined, and that
+    /// value is the limit. If the value is undefined and the expression is a quotient of
+    /// polynomials in `var` with numeric coefficients, the factors `var - at` shared by the
+    /// numerator and denominator are divided out, so the removable singularity of
+    /// `(x^2 - 1) / (x - 1)` at `x = 1` gives exactly `2`. A pole of such a quotient gives
+    /// `None`.
+    ///
+    /// Otherwise both one-sided limits are approximated numerically: the expression is
+    /// evaluated without rounding at `at - h` and `at + h` for halving steps `h`, and each
+    /// side is refined by Richardson extrapolation until successive estimates agree to about
+    /// `1e-9`. The mean is returned if the two sides agree to a relative tolerance of `1e-6`,
+    /// so the result is an approximation, typically good to ten digits or more, for limits
+    /// such as `sin(x) / x` at `0`. It is `None` when the one-sided limits disagree, as for
+    /// `sign(x)` at `0`, when the expression grows without bound, or when it is undefined on
+    /// either side. When the expression is defined at `at` but may jump there, the defined
+    /// value is only returned if it agrees with both sides. The expression may not contain
+    /// symbols other than `var`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = (x.clone().pow(Expr::new_val(2.0)) - 1.0) / (x - 1.0);
+    /// assert_eq!(res.limit(&Symbol::new("x"), 1.0), Some(2.0));
+    /// ```
+    pub fn limit(&self, var: &Symbol, at: f64) -> Option<f64> {
+        let expr = self.simplify();
+        let eval_at = |val: f64, decimals: Option<u32>| {
+            let mut vars = HashMap::new();
+            vars.insert(var.clone(), val);
+            expr.eval_rounded(&vars, decimals).ok().filter(|res| res.is_finite())
+        };
+        let scale = at.abs().max(1.0);
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0);
+        let approach = || {
+            // Rounding would swamp the small differences the extrapolation works with
+            let lhs = one_sided_limit(|h| eval_at(at - h, None), 0.1 * scale)?;
+            let rhs = one_sided_limit(|h| eval_at(at + h, None), 0.1 * scale)?;
+            close(lhs, rhs).then_some((lhs + rhs) / 2.0)
+        };
+        if let Some(res) = eval_at(at, Some(Expr::DEFAULT_PRECISION)) {
+            // Only signs, absolute values and divisions can jump at a point where the
+            // expression is defined, so anything else is continuous there
+            if !expr.iter().any(|node| matches!(node, Expr::Sign(_) | Expr::Abs(_) | Expr::Div(_, _))) {
+                return Some(res);
+            }
+            return approach().map(|lim| if close(lim, res) { res } else { lim });
+        }
+
+        if let Expr::Div(num, den) = &expr {
+            if let (Some(mut num), Some(mut den)) = (num.poly_coeffs(var), den.poly_coeffs(var)) {
+                while num.len() > 1 && den.len() > 1 && vanishes_at(&den, at) && vanishes_at(&num, at) {
+                    num = deflate(&num, at);
+                    den = deflate(&den, at);
+                }
+                if vanishes_at(&den, at) {
+                    return None;
+                }
+                return Some(horner(&num, at) / horner(&den, at));
+            }
+        }
+
+        approach()
+    }
+}
+
+/// Approximates the limit of `f(h)` as `h` shrinks to zero from `h0`, by Richardson
+/// extrapolation over steps that halve each time.
+///
+/// Each row of the table extrapolates one more power of `h` away, assuming `f` is smooth in
+/// `h`. A step where `f` is undefined, such as one that reaches past a nearby singularity,
+/// restarts the table from the next step. Returns `None` if the estimates do not settle.
+fn one_sided_limit<F: Fn(f64) -> Option<f64>>(f: F, h0: f64) -> Option<f64> {
+    let mut prev: Vec<f64> = Vec::new();
+    for k in 0..40 {
+        let Some(val) = f(h0 / 2f64.powi(k)) else {
+            prev.clear();
+            continue;
+        };
+        let mut row = vec![val];
+        for j in 1..=prev.len() {
+            let p = 2f64.powi(j as i32);
+            row.push((p * row[j - 1] - prev[j - 1]) / (p - 1.0));
+        }
+        let (est, last) = (row[row.len() - 1], prev.last().copied());
+        if last.is_some_and(|last| (est - last).abs() <= 1e-9 * est.abs().max(1.0)) {
+            return Some(est);
+        }
+        prev = row;
+    }
+    None
+}
+
+/// Evaluates the polynomial with coefficients `coeffs`, indexed by degree, at `x`.
+fn horner(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// Checks whether the polynomial with coefficients `coeffs` is zero at `x`, to within
+/// rounding relative to the size of its terms.
+fn vanishes_at(coeffs: &[f64], x: f64) -> bool {
+    let size: f64 = coeffs.iter().rev().fold(0.0, |acc, c| acc * x.abs() + c.abs());
+    horner(coeffs, x).abs() <= 1e-9 * size
+}
+
+/// Divides the polynomial with coefficients `coeffs` by `var - root`, dropping the remainder.
+fn deflate(coeffs: &[f64], root: f64) -> Vec<f64> {
+    let mut quotient = vec![0.0; coeffs.len() - 1];
+    let mut acc = 0.0;
+    for i in (1..coeffs.len()).rev() {
+        acc = acc * root + coeffs[i];
+        quotient[i - 1] = acc;
+    }
+    quotient
+}
+
+/// Returns the antiderivative of `var^n`, `var^(n+1) / (n+1)`.
+fn power_rule(var: &Symbol, n: f64) -> Expr {
+    Expr::Div(
+        Rc::new(Expr::Pow(Rc::new(Expr::Symbol(var.clone())), Rc::new(Expr::new_val(n + 1.0)))),
+        Rc::new(Expr::new_val(n + 1.0)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluates `expr` with `x` set to `val`.
+    fn eval_at(expr: &Expr, val: f64) -> f64 {
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("x"), val);
+        expr.eval(&vars).unwrap()
+    }
+
+    #[test]
+    fn diff_polynomial() {
+        let x = Expr::new_var("x");
+        let res = (3.0 * x.clone().pow(Expr::new_val(2.0)) + x.clone() + 5.0).diff(&Symbol::new("x"));
+
+        assert_eq!(eval_at(&res, 2.0), 13.0);
+        assert_eq!(x.diff(&Symbol::new("y")).simplify(), Expr::new_val(0.0));
+    }
+
+    #[test]
+    fn diff_quotient_and_functions() {
+        let x = Expr::new_var("x");
+        let res = (x.clone() / (x.clone() + 1.0)).diff(&Symbol::new("x"));
+        assert_eq!(eval_at(&res, 1.0), 0.25);
+
+        let res = (x.clone() * x.clone()).sin().diff(&Symbol::new("x"));
+        assert_eq!(eval_at(&res, 0.0), 0.0);
+
+        let res = x.clone().ln().diff(&Symbol::new("x"));
+        assert_eq!(eval_at(&res, 4.0), 0.25);
+
+        let res = Expr::new_val(2.0).pow(x.clone()).diff(&Symbol::new("x"));
+        assert!((eval_at(&res, 1.0) - 2.0 * 2f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn diff_abs() {
+        let x = Expr::new_var("x");
+        let res = x.clone().abs().diff(&Symbol::new("x"));
+
+        assert_eq!(res, Expr::Mul(Rc::new(x.clone() / x.clone().abs()), Rc::new(Expr::new_val(1.0))));
+        assert_eq!(eval_at(&res, -3.0), -1.0);
+        assert_eq!(eval_at(&res, 2.0), 1.0);
+        // 0 / |0| is not a number
+        assert!(matches!(res.eval(&HashMap::from([(Symbol::new("x"), 0.0)])), Err(EvalError::UndefinedOperation)));
+        assert_eq!(x.sign().diff(&Symbol::new("x")), Expr::new_val(0.0));
+    }
+
+    #[test]
+    fn diff_sqrt() {
+        let x = Expr::new_var("x");
+        let res = x.sqrt().diff(&Symbol::new("x"));
+
+        assert_eq!(eval_at(&res, 4.0), 0.25);
+    }
+
+    /// Counts the distinct allocations in `expr`, visiting each shared subtree once.
+    fn distinct_nodes(expr: &Expr) -> usize {
+        let mut seen = std::collections::HashSet::new();
+        let mut stack = vec![expr];
+        while let Some(node) = stack.pop() {
+            if seen.insert(node as *const Expr) {
+                stack.extend(node.children());
+            }
+        }
+        seen.len()
+    }
+
+    #[test]
+    fn diff_memo_matches_diff() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let var = Symbol::new("x");
+        let exprs = [
+            3.0 * x.clone().pow(Expr::new_val(2.0)) + x.clone() * y.clone(),
+            (x.clone() / (x.clone() + 1.0)).sin().exp(),
+            x.clone().pow(x.clone()) + y.clone().pow(x.clone()) - x.clone().abs().sqrt().ln(),
+            y.clone().cos() * -y.clone().sign(),
+        ];
+        for expr in exprs {
+            assert_eq!(expr.diff_memo(&var), expr.diff(&var));
+        }
+    }
+
+    #[test]
+    fn diff_memo_shared_tree() {
+        // Squaring twelve times gives x^4096 as a tree with 2^13 - 1 nodes but only 13
+        // distinct ones
+        let var = Symbol::new("x");
+        let mut res = Expr::new_var("x");
+        for _ in 0..12 {
+            res = res.clone() * res;
+        }
+        let memo = res.diff_memo(&var);
+        assert_eq!(memo, res.diff(&var));
+        assert_eq!(eval_at(&memo, 1.0), 4096.0);
+
+        // The memoized derivative reuses one allocation per distinct subtree derivative,
+        // while the plain one allocates for every occurrence
+        assert!(distinct_nodes(&memo) < 100);
+        assert!(distinct_nodes(&res.diff(&var)) > 10_000);
+
+        // Squaring sixty times gives a tree with more than 2^60 nodes, which `diff` could
+        // never walk, while `diff_memo` only visits the 61 distinct ones
+        let mut res = Expr::new_var("x");
+        for _ in 0..60 {
+            res = res.clone() * res;
+        }
+        let memo = res.diff_memo(&var);
+        assert!(distinct_nodes(&memo) < 500);
+        let slope = memo.eval_memo(&HashMap::from([(var, 1.0)])).unwrap();
+        assert!((slope / 2f64.powi(60) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn diff_deep_chain() {
+        let x = Expr::new_var("x");
+        let mut res = Expr::new_val(0.0);
+        for _ in 0..100_000 {
+            res += x.clone();
+        }
+        let deriv = res.diff(&Symbol::new("x"));
+        assert_eq!(eval_at(&deriv, 2.0), 100_000.0);
+        assert_eq!(deriv, res.diff_memo(&Symbol::new("x")));
+    }
+
+    #[test]
+    fn diff_n_repeats_diff() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+        let cube = x.clone().pow(Expr::new_val(3.0));
+
+        assert_eq!(cube.diff_n(&var, 0), cube);
+        assert_eq!(cube.diff_n(&var, 1), cube.diff(&var).simplify());
+        assert_eq!(cube.diff_n(&var, 3), Expr::Const(6.0));
+        assert_eq!(cube.diff_n(&var, 4), Expr::Const(0.0));
+        assert_eq!(x.clone().exp().diff_n(&var, 5), x.clone().exp());
+        assert_eq!(cube.diff_n(&Symbol::new("y"), 2), Expr::Const(0.0));
+    }
+
+    #[test]
+    fn diff_sum() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let res = (i.clone() * x.clone().pow(i.clone())).summation(&index, 1, 3);
+
+        assert_eq!(res.diff(&index), Expr::new_val(0.0));
+        assert_eq!(res.diff_memo(&Symbol::new("x")), res.diff(&Symbol::new("x")));
+        // d/dx (x + 2x^2 + 3x^3) = 1 + 4x + 9x^2
+        assert_eq!(eval_at(&res.diff(&Symbol::new("x")), 2.0), 45.0);
+    }
+
+    #[test]
+    fn diff_product() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let res = (x.clone() + i.clone()).product(&index, 1, 3);
+
+        assert_eq!(res.diff(&index), Expr::new_val(0.0));
+        // d/dx (x + 1)(x + 2)(x + 3) = 3x^2 + 12x + 11
+        assert_eq!(eval_at(&res.diff(&Symbol::new("x")), 0.0), 11.0);
+        assert_eq!(eval_at(&res.diff_memo(&Symbol::new("x")), 1.0), 26.0);
+    }
+
+    #[test]
+    fn gradient_two_variables() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = x.clone().pow(Expr::new_val(2.0)) + x.clone() * y.clone();
+
+        let grad: Vec<Expr> = res.gradient(&[Symbol::new("x"), Symbol::new("y")]).iter().map(Expr::simplify).collect();
+        assert_eq!(grad, vec![Expr::new_val(2.0) * x.clone() + y.clone(), x.clone()]);
+        assert_eq!(res.gradient(&[]), vec![]);
+    }
+
+    #[test]
+    fn hessian_two_variables() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = x.clone().pow(Expr::new_val(2.0)) * y.clone();
+
+        let hessian: Vec<Vec<Expr>> = res
+            .hessian(&[Symbol::new("x"), Symbol::new("y")])
+            .iter()
+            .map(|row| row.iter().map(Expr::simplify).collect())
+            .collect();
+        assert_eq!(hessian[0][0], Expr::new_val(2.0) * y.clone());
+        assert_eq!(hessian[0][1], Expr::new_val(2.0) * x.clone());
+        assert_eq!(hessian[1][0], hessian[0][1]);
+        assert_eq!(hessian[1][1], Expr::new_val(0.0));
+    }
+
+    #[test]
+    fn integrate_square() {
+        let x = Expr::new_var("x");
+        let res = x.clone().pow(Expr::new_val(2.0)).integrate(&Symbol::new("x"));
+
+        assert_eq!(res, Some(x.pow(Expr::new_val(3.0)) / Expr::new_val(3.0)));
+    }
+
+    #[test]
+    fn integrate_linear() {
+        let x = Expr::new_var("x");
+        let integrand = 3.0 * x.clone() + 1.0;
+        let res = integrand.integrate(&Symbol::new("x")).unwrap();
+        assert_eq!(res, 3.0 * (x.clone().pow(Expr::new_val(2.0)) / Expr::new_val(2.0)) + Expr::new_val(1.0) * x.clone());
+
+        // F(2) - F(0) matches the numeric integral
+        let at = |val: f64| {
+            let mut vars = HashMap::new();
+            vars.insert(Symbol::new("x"), val);
+            res.eval(&vars).unwrap()
+        };
+        let area = integrand.integrate_numeric(&Symbol::new("x"), 0.0, 2.0, 10, &HashMap::new()).unwrap();
+        assert!((at(2.0) - at(0.0) - area).abs() < 1e-10);
+    }
+
+    #[test]
+    fn integrate_unsupported() {
+        let x = Expr::new_var("x");
+
+        assert_eq!(x.clone().sin().integrate(&Symbol::new("x")), None);
+        assert_eq!((x.clone() * x.clone()).integrate(&Symbol::new("x")), None);
+        assert_eq!(x.clone().pow(Expr::new_val(-1.0)).integrate(&Symbol::new("x")), None);
+        assert_eq!((1.0 / x).integrate(&Symbol::new("x")), None);
+    }
+
+    #[test]
+    fn integrate_numeric_square() {
+        let x = Expr::new_var("x");
+        let res = x.pow(Expr::new_val(2.0)).integrate_numeric(&Symbol::new("x"), 0.0, 1.0, 100, &HashMap::new());
+
+        assert!((res.unwrap() - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn integrate_numeric_with_parameters() {
+        let x = Expr::new_var("x");
+        let k = Expr::new_var("k");
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("k"), 3.0);
+        let res = (k * x.cos()).integrate_numeric(&Symbol::new("x"), 0.0, std::f64::consts::PI / 2.0, 100, &vars);
+
+        assert!((res.unwrap() - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn integrate_numeric_errors() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert!(matches!(x.integrate_numeric(&Symbol::new("x"), 0.0, 1.0, 3, &HashMap::new()), Err(EvalError::InvalidArgument(_))));
+        assert!(matches!(y.integrate_numeric(&Symbol::new("x"), 0.0, 1.0, 4, &HashMap::new()), Err(EvalError::SymbolNotFound(_))));
+    }
+
+    #[test]
+    fn diff_numeric_cube() {
+        let x = Expr::new_var("x");
+        let cube = x.clone().pow(Expr::new_val(3.0));
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("x"), 2.0);
+
+        // The central difference of x^3 is exactly 3x^2 + h^2
+        let slope = cube.diff_numeric(&Symbol::new("x"), &vars, 1e-3).unwrap();
+        assert!((slope - 12.0).abs() < 1e-5);
+        let slope = cube.diff_numeric_default(&Symbol::new("x"), &vars).unwrap();
+        assert!((slope - 12.0).abs() < 1e-6);
+
+        vars.insert(Symbol::new("x"), 1000.0);
+        let slope = cube.diff_numeric_default(&Symbol::new("x"), &vars).unwrap();
+        assert!((slope - 3e6).abs() / 3e6 < 1e-8);
+    }
+
+    #[test]
+    fn diff_numeric_agrees_with_diff() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let expr = (x.clone() * y.clone()).sin() / (x.clone() + 2.0) + y.clone().exp();
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("x"), 0.7);
+        vars.insert(Symbol::new("y"), -1.3);
+
+        let exact = expr.diff(&Symbol::new("x")).eval(&vars).unwrap();
+        let approx = expr.diff_numeric_default(&Symbol::new("x"), &vars).unwrap();
+        assert!((exact - approx).abs() < 1e-7);
+    }
+
+    #[test]
+    fn diff_numeric_errors() {
+        let x = Expr::new_var("x");
+        let mut vars = HashMap::new();
+
+        assert!(matches!(x.diff_numeric(&Symbol::new("x"), &vars, 1e-5), Err(EvalError::SymbolNotFound(_))));
+        vars.insert(Symbol::new("x"), 0.0);
+        assert!(matches!(x.diff_numeric(&Symbol::new("x"), &vars, 0.0), Err(EvalError::InvalidArgument(_))));
+        assert!(matches!(x.diff_numeric(&Symbol::new("x"), &vars, f64::NAN), Err(EvalError::InvalidArgument(_))));
+        assert!(matches!(x.ln().diff_numeric(&Symbol::new("x"), &vars, 1e-5), Err(EvalError::UndefinedOperation)));
+    }
+
+    #[test]
+    fn limit_removable_singularity() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+        let sq = |e: Expr| e.pow(Expr::new_val(2.0));
+
+        assert_eq!(((sq(x.clone()) - 1.0) / (x.clone() - 1.0)).limit(&var, 1.0), Some(2.0));
+        assert_eq!(((sq(x.clone()) - 4.0) / (x.clone() + 2.0)).limit(&var, -2.0), Some(-4.0));
+        // A double root cancels twice
+        let res = (x.clone().pow(Expr::new_val(3.0)) - sq(x.clone())) / (sq(x.clone()) * (x.clone() + 1.0));
+        assert_eq!(res.limit(&var, 0.0), Some(-1.0));
+        assert_eq!((sq(x.clone()) + x.clone()).limit(&var, 3.0), Some(12.0));
+    }
+
+    #[test]
+    fn limit_numeric() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        let res = (x.clone().sin() / x.clone()).limit(&var, 0.0).unwrap();
+        assert!((res - 1.0).abs() < 1e-10);
+        let res = ((x.clone().exp() - 1.0) / x.clone()).limit(&var, 0.0).unwrap();
+        assert!((res - 1.0).abs() < 1e-10);
+        let res = ((1.0 - x.clone().cos()) / x.clone().pow(Expr::new_val(2.0))).limit(&var, 0.0).unwrap();
+        assert!((res - 0.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn limit_at_jump() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!(x.clone().sign().limit(&var, 0.0), None);
+        assert_eq!((x.clone().abs() / x.clone()).limit(&var, 0.0), None);
+        assert_eq!(x.clone().sign().limit(&var, 2.0), Some(1.0));
+        assert_eq!((x.clone() * x.clone().sign()).limit(&var, 0.0), Some(0.0));
+        // A removable jump gives the value both sides approach, not the value at the point
+        let res = x.clone().sign().pow(Expr::new_val(2.0)).limit(&var, 0.0).unwrap();
+        assert!((res - 1.0).abs() < 1e-10);
+        // Continuous quotients keep their exact value
+        assert_eq!(((x.clone() + 1.0) / (x.clone() - 1.0)).limit(&var, 3.0), Some(2.0));
+    }
+
+    #[test]
+    fn limit_none() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!((1.0 / (x.clone() - 1.0)).limit(&var, 1.0), None);
+        assert_eq!((1.0 / x.clone().pow(Expr::new_val(2.0))).limit(&var, 0.0), None);
+        assert_eq!((x.clone().abs() / x.clone()).limit(&var, 0.0), None);
+        assert_eq!((1.0 / x.clone().sin()).limit(&var, 0.0), None);
+        assert_eq!(x.clone().sqrt().ln().limit(&var, 0.0), None);
+        assert_eq!((Expr::new_var("y") / x).limit(&var, 0.0), None);
+    }
+
+    #[test]
+    fn newton_sqrt_two() {
+        let x = Expr::new_var("x");
+        let res = x.clone().pow(Expr::new_val(2.0)) - 2.0;
+        let var = Symbol::new("x");
+
+        let root = res.solve_newton(&var, 1.0, &HashMap::new(), 50, 1e-12).unwrap();
+        assert!((root - 2f64.sqrt()).abs() < 1e-12);
+        let root = res.solve_newton(&var, -3.0, &HashMap::new(), 50, 1e-12).unwrap();
+        assert!((root + 2f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn newton_with_parameters() {
+        let x = Expr::new_var("x");
+        let a = Expr::new_var("a");
+        let res = x.clone().pow(Expr::new_val(3.0)) - a;
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("a"), 27.0);
+
+        let root = res.solve_newton(&Symbol::new("x"), 1.0, &vars, 100, 1e-12).unwrap();
+        assert!((root - 3.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn newton_failures() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+        let square = x.clone().pow(Expr::new_val(2.0)) + 1.0;
+
+        // The derivative vanishes at the guess
+        assert_eq!(square.solve_newton(&var, 0.0, &HashMap::new(), 50, 1e-12), None);
+        // There is no real root to converge to
+        assert_eq!(square.solve_newton(&var, 0.5, &HashMap::new(), 50, 1e-12), None);
+        // A symbol without a value
+        assert_eq!((x.clone() - Expr::new_var("y")).solve_newton(&var, 0.0, &HashMap::new(), 50, 1e-12), None);
+        // The guess is already a root
+        assert_eq!((x.clone() * x.clone()).solve_newton(&var, 0.0, &HashMap::new(), 50, 1e-12), Some(0.0));
+    }
+}
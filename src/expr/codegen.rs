@@ -0,0 +1,251 @@
+use crate::expr::display::Piece;
+use crate::expr::Expr;
+
+impl Expr {
+    /// Renders the current expression as a Python expression in SymPy syntax.
+    ///
+    /// Powers are written with `**` and every product with an explicit `*`. Functions and
+    /// constants use their SymPy names (`log`, `Abs`, `sign`, `sqrt`, `pi`, `E`), exact
+    /// rationals are written as `Rational(n, d)`, sums as `Sum(body, (i, from, to))` and
+    /// products likewise with `Product`, and infinities and `NaN` as `oo`, `-oo` and `nan`.
+    /// Symbols are emitted verbatim, with an index written as `x_1`. The result can be passed
+    /// to `sympy.sympify`, or evaluated after `from sympy import *`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = 2.0 * x.clone().pow(Expr::new_val(3.0)) + y.ln();
+    /// assert_eq!(res.to_python(), "2 * x**3 + log(y)");
+    /// ```
+    pub fn to_python(&self) -> String {
+        render(self, python_precedence, |node, out, stack| match node {
+            Expr::Const(c) if c.is_nan() => out.push_str("nan"),
+            Expr::Const(c) if c.is_infinite() => out.push_str(if *c > 0.0 { "oo" } else { "-oo" }),
+            Expr::Const(c) => out.push_str(&format!("{}", c)),
+            Expr::Rational(n, d) if *d == 1 => out.push_str(&format!("{}", n)),
+            Expr::Rational(n, d) => out.push_str(&format!("Rational({}, {})", n, d)),
+            Expr::Pi => out.push_str("pi"),
+            Expr::E => out.push('E'),
+            Expr::Symbol(_) => out.push_str(&node.to_string()),
+            Expr::Add(lhs, rhs) => push_infix(stack, lhs, 1, " + ", rhs, 1),
+            Expr::Sub(lhs, rhs) => push_infix(stack, lhs, 1, " - ", rhs, 2),
+            Expr::Mul(lhs, rhs) => push_infix(stack, lhs, 2, " * ", rhs, 2),
+            Expr::Div(lhs, rhs) => push_infix(stack, lhs, 2, " / ", rhs, 3),
+            // Python's ** is right-associative and binds tighter than unary minus, as here
+            Expr::Pow(lhs, rhs) => push_infix(stack, lhs, 5, "**", rhs, 4),
+            Expr::Neg(expr) => {
+                out.push('-');
+                stack.push(Piece::Operand(expr, 3));
+            }
+            Expr::Sin(expr) => push_call(out, stack, "sin(", expr, ")"),
+            Expr::Cos(expr) => push_call(out, stack, "cos(", expr, ")"),
+            Expr::Exp(expr) => push_call(out, stack, "exp(", expr, ")"),
+            Expr::Ln(expr) => push_call(out, stack, "log(", expr, ")"),
+            Expr::Abs(expr) => push_call(out, stack, "Abs(", expr, ")"),
+            Expr::Sign(expr) => push_call(out, stack, "sign(", expr, ")"),
+            Expr::Sqrt(expr) => push_call(out, stack, "sqrt(", expr, ")"),
+            // A sum's body is rendered on its own, so only nested sums and products recurse
+            Expr::Sum { body, index, from, to } =>
+                out.push_str(&format!("Sum({}, ({}, {}, {}))", body.to_python(), index, from, to)),
+            Expr::Product { body, index, from, to } =>
+                out.push_str(&format!("Product({}, ({}, {}, {}))", body.to_python(), index, from, to)),
+        })
+    }
+
+    /// Renders the current expression as a C expression of type `double`.
+    ///
+    /// Exponentiation is written as `pow(a, b)`, every product with an explicit `*`, and the
+    /// functions with their `math.h` names (`exp`, `log`, `fabs`, `sqrt`). The sign function
+    /// has no `math.h` counterpart and is written `((a > 0.0) - (a < 0.0))`. Constants are
+    /// written as `double` literals with enough digits to round-trip, `pi` and `e` included,
+    /// and infinities and `NaN` as the `math.h` macros `INFINITY` and `NAN`. Sums and products
+    /// are written out as a parenthesized chain of additions or multiplications, one term per
+    /// index value. Symbols are emitted verbatim, with an index written as `x_1`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = x.pow(Expr::new_val(2.0)) + y;
+    /// assert_eq!(res.to_c(), "pow(x, 2.0) + y");
+    /// ```
+    pub fn to_c(&self) -> String {
+        render(self, c_precedence, |node, out, stack| match node {
+            Expr::Const(c) if c.is_nan() => out.push_str("NAN"),
+            Expr::Const(c) if c.is_infinite() => out.push_str(if *c > 0.0 { "INFINITY" } else { "-INFINITY" }),
+            Expr::Const(c) => out.push_str(&format!("{:?}", c)),
+            Expr::Rational(n, d) if *d == 1 => out.push_str(&format!("{:?}", *n as f64)),
+            Expr::Rational(n, d) => out.push_str(&format!("({:?} / {:?})", *n as f64, *d as f64)),
+            Expr::Pi => out.push_str(&format!("{:?}", std::f64::consts::PI)),
+            Expr::E => out.push_str(&format!("{:?}", std::f64::consts::E)),
+            Expr::Symbol(_) => out.push_str(&node.to_string()),
+            Expr::Add(lhs, rhs) => push_infix(stack, lhs, 1, " + ", rhs, 1),
+            Expr::Sub(lhs, rhs) => push_infix(stack, lhs, 1, " - ", rhs, 2),
+            Expr::Mul(lhs, rhs) => push_infix(stack, lhs, 2, " * ", rhs, 2),
+            Expr::Div(lhs, rhs) => push_infix(stack, lhs, 2, " / ", rhs, 3),
+            Expr::Pow(lhs, rhs) => {
+                out.push_str("pow(");
+                stack.extend([Piece::Node(lhs), Piece::Text(", "), Piece::Node(rhs), Piece::Text(")")].into_iter().rev());
+            }
+            // A negated negation must not be written --x, which C reads as a decrement
+            Expr::Neg(expr) => {
+                out.push('-');
+                stack.push(Piece::Operand(expr, 4));
+            }
+            Expr::Sin(expr) => push_call(out, stack, "sin(", expr, ")"),
+            Expr::Cos(expr) => push_call(out, stack, "cos(", expr, ")"),
+            Expr::Exp(expr) => push_call(out, stack, "exp(", expr, ")"),
+            Expr::Ln(expr) => push_call(out, stack, "log(", expr, ")"),
+            Expr::Abs(expr) => push_call(out, stack, "fabs(", expr, ")"),
+            Expr::Sign(expr) => {
+                out.push_str("((");
+                stack.extend(
+                    [Piece::Node(expr), Piece::Text(" > 0.0) - ("), Piece::Node(expr), Piece::Text(" < 0.0))")]
+                        .into_iter()
+                        .rev(),
+                );
+            }
+            Expr::Sqrt(expr) => push_call(out, stack, "sqrt(", expr, ")"),
+            // C has no summation, so sums and products are written out term by term
+            Expr::Sum { .. } => out.push_str(&format!("({})", node.expand_sums().to_c())),
+            Expr::Product { .. } => out.push_str(&format!("({})", node.expand_products().to_c())),
+        })
+    }
+}
+
+/// Renders `expr` by calling `write_node` on each node, which writes the start of the node to
+/// the output and leaves the rest of it on the stack. An operand is parenthesized if it binds
+/// looser than its minimum precedence as measured by `precedence`.
+///
+/// The pieces still to be written are kept on an explicit stack, the next one last, so deeply
+/// nested expressions are rendered without overflowing the call stack.
+fn render<'a, F>(expr: &'a Expr, precedence: fn(&Expr) -> u8, mut write_node: F) -> String
+where
+    F: FnMut(&'a Expr, &mut String, &mut Vec<Piece<'a>>),
+{
+    let mut out = String::new();
+    let mut stack = vec![Piece::Node(expr)];
+    while let Some(piece) = stack.pop() {
+        match piece {
+            Piece::Text(text) => out.push_str(text),
+            Piece::Operand(expr, min_prec) if precedence(expr) < min_prec => {
+                out.push('(');
+                stack.push(Piece::Text(")"));
+                write_node(expr, &mut out, &mut stack);
+            }
+            Piece::Operand(expr, _) | Piece::Node(expr) => write_node(expr, &mut out, &mut stack),
+        }
+    }
+    out
+}
+
+/// Leaves `lhs op rhs` on `stack`, with each operand parenthesized below its minimum precedence.
+fn push_infix<'a>(stack: &mut Vec<Piece<'a>>, lhs: &'a Expr, lhs_prec: u8, op: &'static str, rhs: &'a Expr, rhs_prec: u8) {
+    stack.extend([Piece::Operand(lhs, lhs_prec), Piece::Text(op), Piece::Operand(rhs, rhs_prec)].into_iter().rev());
+}
+
+/// Writes `open`, leaving `arg` and `close` on `stack` to be written next.
+fn push_call<'a>(out: &mut String, stack: &mut Vec<Piece<'a>>, open: &str, arg: &'a Expr, close: &'static str) {
+    out.push_str(open);
+    stack.extend([Piece::Node(arg), Piece::Text(close)].into_iter().rev());
+}
+
+/// The precedence of `expr` in Python.
+fn python_precedence(expr: &Expr) -> u8 {
+    // Rational(n, d) is a call, so it binds like an atom
+    match expr {
+        Expr::Rational(_, _) => 5,
+        _ => expr.precedence(),
+    }
+}
+
+/// The precedence of `expr` in C.
+fn c_precedence(expr: &Expr) -> u8 {
+    // pow(a, b) is a call and a rational is already parenthesized, so both bind like atoms
+    match expr {
+        Expr::Pow(_, _) | Expr::Rational(_, _) => 5,
+        _ => expr.precedence(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::Symbol;
+
+    #[test]
+    fn python_power() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!(x.clone().pow(Expr::new_val(2.0)).to_python(), "x**2");
+        assert_eq!(x.clone().pow(y.clone().pow(x.clone())).to_python(), "x**y**x");
+        assert_eq!(x.clone().pow(y.clone()).pow(x.clone()).to_python(), "(x**y)**x");
+        assert_eq!((-x.clone()).pow(y.clone()).to_python(), "(-x)**y");
+        assert_eq!((-x.clone().pow(y.clone())).to_python(), "-x**y");
+        assert_eq!(x.clone().pow(Expr::new_rational(1, 3)).to_python(), "x**Rational(1, 3)");
+        assert_eq!(Expr::E.pow(-x).to_python(), "E**(-x)");
+    }
+
+    #[test]
+    fn python_product() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((3.0 * x.clone()).to_python(), "3 * x");
+        assert_eq!(((x.clone() + y.clone()) * (x.clone() - y.clone())).to_python(), "(x + y) * (x - y)");
+        assert_eq!((x.clone() / (2.0 * y.clone())).to_python(), "x / (2 * y)");
+        assert_eq!((Expr::new_val(0.5) * Expr::Pi).to_python(), "0.5 * pi");
+    }
+
+    #[test]
+    fn python_functions_and_symbols() {
+        let x = Expr::new_var("x");
+        let x1 = Expr::Symbol(Symbol::indexed("x", 1));
+
+        assert_eq!((x.clone().sin() + x.clone().cos()).to_python(), "sin(x) + cos(x)");
+        assert_eq!(x.clone().abs().sqrt().to_python(), "sqrt(Abs(x))");
+        assert_eq!(x.clone().exp().sign().to_python(), "sign(exp(x))");
+        assert_eq!((x1 - x).to_python(), "x_1 - x");
+        assert_eq!(Expr::new_val(f64::NEG_INFINITY).to_python(), "-oo");
+    }
+
+    #[test]
+    fn c_power_and_sum() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((x.clone().pow(Expr::new_val(2.0)) + y.clone()).to_c(), "pow(x, 2.0) + y");
+        assert_eq!((2.0 * x.clone().pow(y.clone() + 1.0)).to_c(), "2.0 * pow(x, y + 1.0)");
+        assert_eq!((x.clone() + y.clone()).pow(Expr::new_rational(1, 3)).to_c(), "pow(x + y, (1.0 / 3.0))");
+        assert_eq!((x.clone() / (x.clone() - y.clone())).to_c(), "x / (x - y)");
+    }
+
+    #[test]
+    fn c_functions_and_negation() {
+        let x = Expr::new_var("x");
+
+        assert_eq!((x.clone().ln() - x.clone().abs().sqrt()).to_c(), "log(x) - sqrt(fabs(x))");
+        assert_eq!(x.clone().sign().to_c(), "((x > 0.0) - (x < 0.0))");
+        assert_eq!((-(-x.clone())).to_c(), "-(-x)");
+        assert_eq!((-Expr::new_val(-2.0)).to_c(), "-(-2.0)");
+        assert_eq!((x.clone() - -x.clone()).to_c(), "x - -x");
+        assert_eq!(Expr::new_val(f64::INFINITY).to_c(), "INFINITY");
+    }
+
+    #[test]
+    fn c_constants_round_trip() {
+        for c in [0.1, 1.0 / 3.0, 1e-7, 6.02214076e23, -2.5] {
+            assert_eq!(Expr::new_val(c).to_c().parse::<f64>().unwrap(), c);
+        }
+        assert_eq!(Expr::Pi.to_c().parse::<f64>().unwrap(), std::f64::consts::PI);
+        assert_eq!(Expr::E.to_c().parse::<f64>().unwrap(), std::f64::consts::E);
+    }
+}
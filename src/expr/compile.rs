@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use crate::expr::Expr;
+use crate::expr::eval::{round, BinaryOp, EvalError, UnaryOp};
+use crate::symbol::Symbol;
+
+/// A single operation of an expression in reverse Polish notation, run on a stack of values.
+///
+/// A program is a sequence of operations where every operation comes after the operations
+/// producing its operands, so `x * (y + 2)` is `[Var(0), Var(1), Const(2), Binary(Add),
+/// Binary(Mul)]`. Running a well-formed program leaves exactly one value on the stack, the
+/// value of the expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RpnOp {
+    /// Pushes a constant.
+    Const(f64),
+    /// Pushes the argument at the given position.
+    Var(usize),
+    /// Pops the right and then the left operand, and pushes the result.
+    Binary(BinaryOp),
+    /// Pops the operand and pushes the result.
+    Unary(UnaryOp),
+}
+
+impl Expr {
+    /// Compiles the current expression into a closure that evaluates it for positional
+    /// arguments, where the value of `order[i]` is passed at index `i`.
+    ///
+    /// The tree is flattened once into reverse Polish notation, as with `to_rpn`, so repeated
+    /// evaluations skip the tree walk and the symbol lookups of `eval`. The closure gives the
+    /// same results and errors as `eval`. It returns `Err(EvalError::SymbolNotFound)` if the
+    /// expression contains a symbol missing from `order`, and
+    /// `Err(EvalError::InvalidArgument)` if it is not given exactly one argument per symbol
+    /// in `order`.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `order` - The symbols in the order their values are passed to the closure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let f = (x.clone() * x + y).compile(&[Symbol::new("x"), Symbol::new("y")]);
+    /// assert_eq!(f(&[3.0, 1.0]).unwrap(), 10.0);
+    /// assert_eq!(f(&[-2.0, 0.5]).unwrap(), 4.5);
+    /// ```
+    pub fn compile(&self, order: &[Symbol]) -> impl Fn(&[f64]) -> Result<f64, EvalError> {
+        let compiled = self.to_rpn_with_order(order);
+        let arity = order.len();
+        move |args: &[f64]| {
+            let program = compiled.as_ref().map_err(|s| EvalError::SymbolNotFound(s.clone()))?;
+            if args.len() != arity {
+                return Err(EvalError::InvalidArgument("expected one argument per symbol in the order"));
+            }
+            Expr::eval_rpn(program, args)
+        }
+    }
+
+    /// Linearizes the current expression into operations in reverse Polish notation.
+    ///
+    /// Symbols become `RpnOp::Var` indexes into the sorted list of the expression's symbols,
+    /// as returned by `variables`. Rationals, `pi` and `e` become constants with the value
+    /// `eval` gives them, so running the program with `eval_rpn` agrees with `eval`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::expr::compile::RpnOp;
+    /// use symbolic_math::expr::eval::BinaryOp;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let rpn = (x * (y + 2.0)).to_rpn();
+    /// assert_eq!(rpn, vec![
+    ///     RpnOp::Var(0), RpnOp::Var(1), RpnOp::Const(2.0),
+    ///     RpnOp::Binary(BinaryOp::Add), RpnOp::Binary(BinaryOp::Mul),
+    /// ]);
+    /// assert_eq!(Expr::eval_rpn(&rpn, &[3.0, 1.0]).unwrap(), 9.0);
+    /// ```
+    pub fn to_rpn(&self) -> Vec<RpnOp> {
+        let order: Vec<Symbol> = self.variables().into_iter().collect();
+        self.to_rpn_with_order(&order).expect("every symbol of the expression is in its variables")
+    }
+
+    /// Runs a program of operations in reverse Polish notation against positional arguments,
+    /// where `RpnOp::Var(i)` pushes `args[i]`.
+    ///
+    /// Operations give the same results and errors as in `eval`. It returns
+    /// `Err(EvalError::InvalidArgument)` if the program is malformed: an operation is missing
+    /// operands, an index is out of range for `args`, or it does not leave exactly one value.
+    ///
+    /// # Arguments
+    ///
+    /// * `ops` - The program to run.
+    /// * `args` - The values of the variables referenced by the program.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::expr::compile::RpnOp;
+    /// use symbolic_math::expr::eval::UnaryOp;
+    ///
+    /// let ops = [RpnOp::Var(0), RpnOp::Unary(UnaryOp::Sqrt)];
+    /// assert_eq!(Expr::eval_rpn(&ops, &[16.0]).unwrap(), 4.0);
+    /// assert!(Expr::eval_rpn(&ops, &[]).is_err());
+    /// ```
+    pub fn eval_rpn(ops: &[RpnOp], args: &[f64]) -> Result<f64, EvalError> {
+        let malformed = || EvalError::InvalidArgument("malformed RPN program");
+        let mut stack: Vec<f64> = Vec::with_capacity(ops.len());
+        for op in ops {
+            let val = match *op {
+                RpnOp::Const(c) => c,
+                RpnOp::Var(i) => *args.get(i).ok_or_else(malformed)?,
+                RpnOp::Binary(op) => {
+                    let rhs = stack.pop().ok_or_else(malformed)?;
+                    let lhs = stack.pop().ok_or_else(malformed)?;
+                    op.apply(lhs, rhs, Some(Expr::DEFAULT_PRECISION))?
+                }
+                RpnOp::Unary(op) => {
+                    let expr = stack.pop().ok_or_else(malformed)?;
+                    op.apply(expr, Some(Expr::DEFAULT_PRECISION))?
+                }
+            };
+            stack.push(val);
+        }
+        match stack[..] {
+            [val] => Ok(val),
+            _ => Err(malformed()),
+        }
+    }
+
+    /// Linearizes the current expression into operations in reverse Polish notation with
+    /// symbols indexed by their position in `order`, returning the first symbol missing from
+    /// `order` if there is one.
+    fn to_rpn_with_order(&self, order: &[Symbol]) -> Result<Vec<RpnOp>, Symbol> {
+        // A program has no loops, so sums and products are written out term by term
+        if self.has_indexed() {
+            return self.expand_indexed().to_rpn_with_order(order);
+        }
+        let mut positions: HashMap<&Symbol, usize> = HashMap::new();
+        for (i, s) in order.iter().enumerate() {
+            positions.entry(s).or_insert(i);
+        }
+
+        let mut program = Vec::new();
+        let mut stack = vec![(self, false)];
+        while let Some((node, ready)) = stack.pop() {
+            let children = node.children();
+            if !ready && !children.is_empty() {
+                stack.push((node, true));
+                stack.extend(children.into_iter().rev().map(|child| (child, false)));
+                continue;
+            }
+            let op = match node {
+                Expr::Const(c) => RpnOp::Const(*c),
+                Expr::Rational(n, d) => RpnOp::Const(round(*n as f64 / *d as f64, Expr::DEFAULT_PRECISION)),
+                Expr::Pi => RpnOp::Const(std::f64::consts::PI),
+                Expr::E => RpnOp::Const(std::f64::consts::E),
+                Expr::Symbol(s) => RpnOp::Var(*positions.get(s).ok_or_else(|| s.clone())?),
+                Expr::Add(_, _) => RpnOp::Binary(BinaryOp::Add),
+                Expr::Sub(_, _) => RpnOp::Binary(BinaryOp::Sub),
+                Expr::Mul(_, _) => RpnOp::Binary(BinaryOp::Mul),
+                Expr::Div(_, _) => RpnOp::Binary(BinaryOp::Div),
+                Expr::Pow(_, exp) => {
+                    // Like eval, use a rational exponent unrounded; it was pushed just before
+                    if let Expr::Rational(n, d) = **exp {
+                        *program.last_mut().expect("the exponent precedes its power") = RpnOp::Const(n as f64 / d as f64);
+                    }
+                    RpnOp::Binary(BinaryOp::Pow)
+                }
+                Expr::Neg(_) => RpnOp::Unary(UnaryOp::Neg),
+                Expr::Sin(_) => RpnOp::Unary(UnaryOp::Sin),
+                Expr::Cos(_) => RpnOp::Unary(UnaryOp::Cos),
+                Expr::Exp(_) => RpnOp::Unary(UnaryOp::Exp),
+                Expr::Ln(_) => RpnOp::Unary(UnaryOp::Ln),
+                Expr::Abs(_) => RpnOp::Unary(UnaryOp::Abs),
+                Expr::Sign(_) => RpnOp::Unary(UnaryOp::Sign),
+                Expr::Sqrt(_) => RpnOp::Unary(UnaryOp::Sqrt),
+                Expr::Sum { .. } | Expr::Product { .. } => unreachable!("sums and products are expanded before linearizing"),
+            };
+            program.push(op);
+        }
+        Ok(program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn sample_expr() -> Expr {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        (x.clone().sin() * y.clone() + x.clone().pow(Expr::new_val(2.0)) / (y.clone() + 3.0)).exp().ln()
+            - Expr::new_rational(1, 3) * x.abs().sqrt()
+            + Expr::Pi * y.sign()
+    }
+
+    #[test]
+    fn compile_agrees_with_eval() {
+        let expr = sample_expr();
+        let order = [Symbol::new("x"), Symbol::new("y")];
+        let f = expr.compile(&order);
+
+        for (x, y) in [(0.0, 0.0), (1.5, -2.0), (-3.25, 4.0), (10.0, 0.125), (-0.5, -7.5)] {
+            let mut vars = HashMap::new();
+            vars.insert(Symbol::new("x"), x);
+            vars.insert(Symbol::new("y"), y);
+            assert_eq!(f(&[x, y]).ok(), expr.eval(&vars).ok(), "at x = {}, y = {}", x, y);
+        }
+    }
+
+    #[test]
+    fn compile_errors() {
+        let x = Expr::new_var("x");
+        let f = x.clone().ln().compile(&[Symbol::new("x")]);
+        assert!(matches!(f(&[-1.0]), Err(EvalError::UndefinedOperation)));
+        assert!(matches!(f(&[]), Err(EvalError::InvalidArgument(_))));
+
+        let f = (x + Expr::new_var("z")).compile(&[Symbol::new("x")]);
+        assert!(matches!(f(&[1.0]), Err(EvalError::SymbolNotFound(s)) if s == Symbol::new("z")));
+    }
+
+    #[test]
+    fn compile_rational_roots() {
+        let x = Expr::new_var("x");
+        let res = x.clone().pow(Expr::new_rational(1, 3)) + x.clone().pow(Expr::new_rational(1, 2));
+        let f = res.compile(&[Symbol::new("x")]);
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("x"), 64.0);
+
+        assert_eq!(f(&[64.0]).unwrap(), 12.0);
+        assert_eq!(f(&[64.0]).unwrap(), res.eval(&vars).unwrap());
+        assert!(matches!(f(&[-1.0]), Err(EvalError::UndefinedOperation)));
+    }
+
+    #[test]
+    fn compile_constant_and_unused_symbols() {
+        let f = (Expr::new_val(2.0) * Expr::E).compile(&[Symbol::new("unused")]);
+        assert_eq!(f(&[5.0]).unwrap(), (Expr::new_val(2.0) * Expr::E).eval(&HashMap::new()).unwrap());
+    }
+
+    #[test]
+    fn compile_deep_chain() {
+        let x = Expr::new_var("x");
+        let mut res = x.clone();
+        for _ in 0..100_000 {
+            res += x.clone();
+        }
+        assert_eq!(res.compile(&[Symbol::new("x")])(&[1.0]).unwrap(), 100_001.0);
+    }
+
+    #[test]
+    fn rpn_round_trip() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let exprs = [
+            sample_expr(),
+            (y.clone() - x.clone()) / (x.clone() * y.clone()).cos(),
+            -(x.clone().pow(y.clone())) + Expr::E,
+            Expr::new_val(4.0).sqrt(),
+        ];
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("x"), 1.25);
+        vars.insert(Symbol::new("y"), -0.75);
+
+        for expr in exprs {
+            let args: Vec<f64> = expr.variables().iter().map(|s| vars[s]).collect();
+            assert_eq!(Expr::eval_rpn(&expr.to_rpn(), &args).ok(), expr.eval(&vars).ok(), "for {}", expr);
+        }
+    }
+
+    #[test]
+    fn rpn_layout() {
+        let x = Expr::new_var("x");
+        let rpn = (-x.clone().ln() + Expr::new_rational(1, 2)).to_rpn();
+
+        assert_eq!(rpn, vec![
+            RpnOp::Var(0),
+            RpnOp::Unary(UnaryOp::Ln),
+            RpnOp::Unary(UnaryOp::Neg),
+            RpnOp::Const(0.5),
+            RpnOp::Binary(BinaryOp::Add),
+        ]);
+        assert_eq!(Expr::Pi.to_rpn(), vec![RpnOp::Const(std::f64::consts::PI)]);
+    }
+
+    #[test]
+    fn rpn_malformed() {
+        let add = RpnOp::Binary(BinaryOp::Add);
+        assert!(matches!(Expr::eval_rpn(&[], &[]), Err(EvalError::InvalidArgument(_))));
+        assert!(matches!(Expr::eval_rpn(&[RpnOp::Const(1.0), add], &[]), Err(EvalError::InvalidArgument(_))));
+        assert!(matches!(Expr::eval_rpn(&[RpnOp::Const(1.0), RpnOp::Const(2.0)], &[]), Err(EvalError::InvalidArgument(_))));
+        assert!(matches!(Expr::eval_rpn(&[RpnOp::Var(1)], &[3.0]), Err(EvalError::InvalidArgument(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rpn_serde_round_trip() {
+        let x = Expr::new_var("x");
+        let rpn = (x.clone().sin() * 2.0).to_rpn();
+        let json = serde_json::to_string(&rpn).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<RpnOp>>(&json).unwrap(), rpn);
+    }
+
+    /// A rough comparison of `eval` against a compiled closure, run with
+    /// `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn compile_benchmark() {
+        let expr = sample_expr();
+        let order = [Symbol::new("x"), Symbol::new("y")];
+        let points: Vec<(f64, f64)> = (0..100_000).map(|i| (i as f64 * 0.001, 1.0 - i as f64 * 0.0005)).collect();
+
+        let start = Instant::now();
+        let mut vars = HashMap::new();
+        let mut eval_sum = 0.0;
+        for &(x, y) in &points {
+            vars.insert(Symbol::new("x"), x);
+            vars.insert(Symbol::new("y"), y);
+            eval_sum += expr.eval(&vars).unwrap_or(0.0);
+        }
+        let eval_time = start.elapsed();
+
+        let start = Instant::now();
+        let f = expr.compile(&order);
+        let mut compiled_sum = 0.0;
+        for &(x, y) in &points {
+            compiled_sum += f(&[x, y]).unwrap_or(0.0);
+        }
+        let compiled_time = start.elapsed();
+
+        assert_eq!(eval_sum, compiled_sum);
+        println!("eval: {:?}, compiled: {:?}", eval_time, compiled_time);
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use crate::expr::Expr;
+use crate::expr::eval::{round, EvalError};
+use crate::symbol::Symbol;
+
+pub use num_complex::Complex64;
+
+impl Expr {
+    /// Evaluates the current expression over the complex numbers using the given map of
+    /// symbols to values.
+    ///
+    /// Functions take their principal values, so `sqrt(-1)` is `i` and `ln(-1)` is `i*pi`,
+    /// and a negative base raised to a fractional power no longer fails. Results are rounded
+    /// like those of `eval`. It returns `Err(EvalError::UndefinedOperation)` when an operation
+    /// has no finite value, such as division by zero or the logarithm of zero, and an error
+    /// if a symbol is missing from the map.
+    ///
+    /// Requires the `complex` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `vars` - A map from symbols to their corresponding complex values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::expr::complex::Complex64;
+    /// use std::collections::HashMap;
+    ///
+    /// let res = Expr::new_val(-1.0).pow(Expr::new_val(0.5));
+    /// assert_eq!(res.eval_complex(&HashMap::new()).unwrap(), Complex64::new(0.0, 1.0));
+    /// ```
+    pub fn eval_complex(&self, vars: &HashMap<Symbol, Complex64>) -> Result<Complex64, EvalError> {
+        if self.has_indexed() {
+            return self.expand_indexed().eval_complex(vars);
+        }
+        self.try_fold_up(|node, args| node.eval_complex_node(&args, vars))
+    }
+
+    /// Evaluates the outermost operation of the current expression over the complex numbers,
+    /// given the already evaluated values of its children in `args`.
+    fn eval_complex_node(&self, args: &[Complex64], vars: &HashMap<Symbol, Complex64>) -> Result<Complex64, EvalError> {
+        let res = match self {
+            Expr::Const(c) => return Ok(Complex64::new(*c, 0.0)),
+            Expr::Rational(n, d) => Complex64::new(*n as f64 / *d as f64, 0.0),
+            Expr::Pi => return Ok(Complex64::new(std::f64::consts::PI, 0.0)),
+            Expr::E => return Ok(Complex64::new(std::f64::consts::E, 0.0)),
+            Expr::Symbol(s) => return vars.get(s).cloned().ok_or(EvalError::SymbolNotFound(s.clone())),
+            Expr::Add(_, _) => args[0] + args[1],
+            Expr::Sub(_, _) => args[0] - args[1],
+            Expr::Mul(_, _) => args[0] * args[1],
+            Expr::Div(_, _) => {
+                if args[1] == Complex64::new(0.0, 0.0) {
+                    return Err(EvalError::UndefinedOperation);
+                }
+                args[0] / args[1]
+            }
+            Expr::Pow(_, _) => {
+                let (base, exp) = (args[0], args[1]);
+                if base == Complex64::new(0.0, 0.0) {
+                    // The principal power is exp(ln(0) * w), which only has a limit for
+                    // w = 0 (giving 1) or Re(w) > 0 (giving 0)
+                    if exp == Complex64::new(0.0, 0.0) {
+                        Complex64::new(1.0, 0.0)
+                    } else if exp.re > 0.0 {
+                        Complex64::new(0.0, 0.0)
+                    } else {
+                        return Err(EvalError::UndefinedOperation);
+                    }
+                } else {
+                    base.powc(exp)
+                }
+            }
+            Expr::Neg(_) => return Ok(-args[0]),
+            Expr::Sin(_) => args[0].sin(),
+            Expr::Cos(_) => args[0].cos(),
+            Expr::Exp(_) => args[0].exp(),
+            Expr::Ln(_) => args[0].ln(),
+            Expr::Abs(_) => return Ok(Complex64::new(args[0].norm(), 0.0)),
+            Expr::Sign(_) => {
+                if args[0] == Complex64::new(0.0, 0.0) {
+                    Complex64::new(0.0, 0.0)
+                } else {
+                    args[0] / args[0].norm()
+                }
+            }
+            Expr::Sqrt(_) => args[0].sqrt(),
+            Expr::Sum { .. } | Expr::Product { .. } => unreachable!("sums and products are expanded before evaluation"),
+        };
+        if res.is_finite() {
+            Ok(Complex64::new(round(res.re, Expr::DEFAULT_PRECISION), round(res.im, Expr::DEFAULT_PRECISION)))
+        } else {
+            Err(EvalError::UndefinedOperation)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complex_roots() {
+        let vars: HashMap<Symbol, Complex64> = HashMap::new();
+        let i = Complex64::new(0.0, 1.0);
+
+        assert_eq!(Expr::new_val(-1.0).sqrt().eval_complex(&vars).unwrap(), i);
+        assert_eq!(Expr::new_val(-4.0).pow(Expr::new_val(0.5)).eval_complex(&vars).unwrap(), 2.0 * i);
+        assert_eq!(Expr::new_val(-1.0).ln().eval_complex(&vars).unwrap(), Complex64::new(0.0, round(std::f64::consts::PI, Expr::DEFAULT_PRECISION)));
+        assert_eq!(Expr::Pi.eval_complex(&vars).unwrap(), Complex64::new(std::f64::consts::PI, 0.0));
+    }
+
+    #[test]
+    fn complex_symbols() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, Complex64> = HashMap::new();
+        vars.insert(Symbol::new("x"), Complex64::new(3.0, 4.0));
+
+        assert_eq!((x.clone() * x.clone()).eval_complex(&vars).unwrap(), Complex64::new(-7.0, 24.0));
+        assert_eq!(x.clone().abs().eval_complex(&vars).unwrap(), Complex64::new(5.0, 0.0));
+        assert_eq!(x.clone().sign().eval_complex(&vars).unwrap(), Complex64::new(0.6, 0.8));
+        assert_eq!((Expr::Pi * x.clone() / x.clone()).eval_complex(&vars).unwrap(), Complex64::new(round(std::f64::consts::PI, Expr::DEFAULT_PRECISION), 0.0));
+    }
+
+    #[test]
+    fn complex_euler_identity() {
+        let vars: HashMap<Symbol, Complex64> = HashMap::new();
+        let i = Expr::new_val(-1.0).sqrt();
+
+        assert_eq!((i * Expr::Pi).exp().eval_complex(&vars).unwrap(), Complex64::new(-1.0, 0.0));
+    }
+
+    #[test]
+    fn complex_undefined() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, Complex64> = HashMap::new();
+
+        assert!(matches!(x.clone().eval_complex(&vars), Err(EvalError::SymbolNotFound(_))));
+        vars.insert(Symbol::new("x"), Complex64::new(0.0, 0.0));
+        assert!(matches!((1.0 / x.clone()).eval_complex(&vars), Err(EvalError::UndefinedOperation)));
+        assert!(matches!(x.clone().ln().eval_complex(&vars), Err(EvalError::UndefinedOperation)));
+        assert!(matches!(x.clone().pow(Expr::new_val(-1.0)).eval_complex(&vars), Err(EvalError::UndefinedOperation)));
+        assert_eq!(x.clone().pow(Expr::new_val(0.0)).eval_complex(&vars).unwrap(), Complex64::new(1.0, 0.0));
+    }
+}
@@ -0,0 +1,308 @@
+use std::fmt::{self, Write};
+use crate::expr::Expr;
+
+/// The number of significant digits constants are rounded to by `Display` and `display_with`.
+pub const DISPLAY_SIGNIFICANT_DIGITS: usize = 12;
+
+/// How `display_with` writes a multiplication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MulStyle {
+    /// A constant times a symbol is written without an operator (e.g. `2x`), and every
+    /// other product with ` * `. This is what `Display` uses.
+    Implicit,
+    /// Every product is written with ` * `.
+    Explicit,
+    /// Every product is written with the unicode middle dot ` · `.
+    Dot,
+}
+
+/// How `display_with` writes an exponentiation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PowStyle {
+    /// `x^y`, as in `Display`.
+    Caret,
+    /// `x**y`, as in Python or Fortran.
+    DoubleStar,
+}
+
+/// Options controlling how `display_with` renders an expression.
+///
+/// The default options render exactly like `Display`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayOptions {
+    /// How to write multiplications.
+    pub mul: MulStyle,
+    /// How to write exponentiations.
+    pub pow: PowStyle,
+    /// Whether to only emit parentheses where operator precedence requires them. When
+    /// `false`, every operand that is not a symbol, a constant or a function call is
+    /// parenthesized, and negated terms in sums are written as they are stored, so
+    /// `x + -y` stays `x + (-y)` instead of becoming `x - y`.
+    pub minimal_parens: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> DisplayOptions {
+        DisplayOptions { mul: MulStyle::Implicit, pow: PowStyle::Caret, minimal_parens: true }
+    }
+}
+
+impl Expr {
+    /// Renders the current expression as a string using the given options.
+    ///
+    /// `Display` is the same as `display_with(&DisplayOptions::default())`.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `opts` - The rendering options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::expr::display::{DisplayOptions, MulStyle, PowStyle};
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = 2.0 * x.clone().pow(Expr::new_val(2.0)) + x.clone();
+    /// assert_eq!(res.display_with(&DisplayOptions::default()), res.to_string());
+    ///
+    /// let opts = DisplayOptions { mul: MulStyle::Explicit, pow: PowStyle::DoubleStar, minimal_parens: false };
+    /// assert_eq!(res.display_with(&opts), "(2 * (x**2)) + x");
+    /// ```
+    pub fn display_with(&self, opts: &DisplayOptions) -> String {
+        let mut out = String::new();
+        self.write_with(&mut out, opts).expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Writes the current expression to `f` using the given options.
+    ///
+    /// The pieces still to be written are kept on an explicit stack, the next one last, so
+    /// deeply nested expressions are written without overflowing the call stack.
+    pub(crate) fn write_with<W: Write>(&self, f: &mut W, opts: &DisplayOptions) -> fmt::Result {
+        let mut stack = vec![Piece::Node(self)];
+        while let Some(piece) = stack.pop() {
+            let node = match piece {
+                Piece::Text(text) => {
+                    f.write_str(text)?;
+                    continue;
+                }
+                // An operand is parenthesized if it binds looser than `min_prec`, or if it is
+                // not an atom and minimal parentheses are turned off
+                Piece::Operand(expr, min_prec) => {
+                    let min_prec = if opts.minimal_parens { min_prec } else { 5 };
+                    if expr.precedence() < min_prec {
+                        f.write_str("(")?;
+                        stack.push(Piece::Text(")"));
+                    }
+                    expr
+                }
+                Piece::Node(expr) => expr,
+            };
+            match node {
+                Expr::Const(c) => write!(f, "{}", format_const(*c))?,
+                Expr::Rational(n, d) if *d == 1 => write!(f, "{}", n)?,
+                Expr::Rational(n, d) => write!(f, "{}/{}", n, d)?,
+                Expr::Pi => write!(f, "pi")?,
+                Expr::E => write!(f, "e")?,
+                Expr::Symbol(s) => write!(f, "{}", s)?,
+                // a + -b is written a - b, and -a + b is written b - a
+                Expr::Add(lhs, rhs) => match (&**lhs, &**rhs) {
+                    (_, Expr::Neg(rhs)) if opts.minimal_parens =>
+                        stack.extend([Piece::Operand(lhs, 1), Piece::Text(" - "), Piece::Operand(rhs, 2)].into_iter().rev()),
+                    (Expr::Neg(lhs), _) if opts.minimal_parens =>
+                        stack.extend([Piece::Operand(rhs, 1), Piece::Text(" - "), Piece::Operand(lhs, 2)].into_iter().rev()),
+                    _ => stack.extend([Piece::Operand(lhs, 1), Piece::Text(" + "), Piece::Operand(rhs, 1)].into_iter().rev()),
+                },
+                // a - -b is written a + b
+                Expr::Sub(lhs, rhs) => match &**rhs {
+                    Expr::Neg(rhs) if opts.minimal_parens =>
+                        stack.extend([Piece::Operand(lhs, 1), Piece::Text(" + "), Piece::Operand(rhs, 1)].into_iter().rev()),
+                    _ => stack.extend([Piece::Operand(lhs, 1), Piece::Text(" - "), Piece::Operand(rhs, 2)].into_iter().rev()),
+                },
+                Expr::Mul(lhs, rhs) => match (&**lhs, &**rhs) {
+                    (Expr::Const(c), Expr::Symbol(s)) | (Expr::Symbol(s), Expr::Const(c)) if opts.mul == MulStyle::Implicit =>
+                        write!(f, "{}{}", format_const(*c), s)?,
+                    _ => {
+                        let op = match opts.mul {
+                            MulStyle::Dot => " · ",
+                            MulStyle::Implicit | MulStyle::Explicit => " * ",
+                        };
+                        stack.extend([Piece::Operand(lhs, 2), Piece::Text(op), Piece::Operand(rhs, 2)].into_iter().rev());
+                    }
+                },
+                Expr::Div(lhs, rhs) =>
+                    stack.extend([Piece::Operand(lhs, 2), Piece::Text(" / "), Piece::Operand(rhs, 3)].into_iter().rev()),
+                // Exact square and cube roots are written as calls
+                Expr::Pow(lhs, rhs) if **rhs == Expr::Rational(1, 2) => write_call(f, &mut stack, "sqrt", lhs)?,
+                Expr::Pow(lhs, rhs) if **rhs == Expr::Rational(1, 3) => write_call(f, &mut stack, "cbrt", lhs)?,
+                Expr::Pow(lhs, rhs) => {
+                    let op = match opts.pow {
+                        PowStyle::Caret => "^",
+                        PowStyle::DoubleStar => "**",
+                    };
+                    stack.extend([Piece::Operand(lhs, 5), Piece::Text(op), Piece::Operand(rhs, 4)].into_iter().rev());
+                }
+                Expr::Neg(expr) => {
+                    f.write_str("-")?;
+                    stack.push(Piece::Operand(expr, 3));
+                }
+                Expr::Sin(expr) => write_call(f, &mut stack, "sin", expr)?,
+                Expr::Cos(expr) => write_call(f, &mut stack, "cos", expr)?,
+                Expr::Exp(expr) => write_call(f, &mut stack, "exp", expr)?,
+                Expr::Ln(expr) => write_call(f, &mut stack, "ln", expr)?,
+                Expr::Abs(expr) => {
+                    f.write_str("|")?;
+                    stack.extend([Piece::Node(expr), Piece::Text("|")].into_iter().rev());
+                }
+                Expr::Sign(expr) => write_call(f, &mut stack, "sign", expr)?,
+                Expr::Sqrt(expr) => write_call(f, &mut stack, "sqrt", expr)?,
+                Expr::Sum { body, index, from, to } => {
+                    write!(f, "sum({}={}..{}, ", index, from, to)?;
+                    stack.extend([Piece::Node(body), Piece::Text(")")].into_iter().rev());
+                }
+                Expr::Product { body, index, from, to } => {
+                    write!(f, "prod({}={}..{}, ", index, from, to)?;
+                    stack.extend([Piece::Node(body), Piece::Text(")")].into_iter().rev());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A part of the output of `write_with` or `to_latex` that is still to be written.
+pub(crate) enum Piece<'a> {
+    /// An expression, written as it is.
+    Node(&'a Expr),
+    /// An operand, parenthesized if it binds looser than the given precedence.
+    Operand(&'a Expr, u8),
+    /// Literal text between the parts of an expression.
+    Text(&'static str),
+}
+
+/// Formats a constant for display, rounded to `DISPLAY_SIGNIFICANT_DIGITS` significant digits.
+///
+/// The rounding absorbs floating point noise, so `0.1 + 0.2` is written `0.3` and
+/// `2.9999999999999996` is written `3`. Whole numbers are written without a fractional part
+/// and zero without a sign, and infinities and `NaN` are written as `inf`, `-inf` and `NaN`.
+pub(crate) fn format_const(c: f64) -> String {
+    if !c.is_finite() {
+        return format!("{}", c);
+    }
+    let rounded: f64 = format!("{:.*e}", DISPLAY_SIGNIFICANT_DIGITS - 1, c)
+        .parse()
+        .expect("a formatted float parses back");
+    if rounded == 0.0 {
+        "0".to_string()
+    } else if rounded.fract() == 0.0 {
+        format!("{:.0}", rounded)
+    } else {
+        format!("{}", rounded)
+    }
+}
+
+/// Writes the name and opening parenthesis of a call of the function `name`, leaving `arg`
+/// and the closing parenthesis on `stack` to be written next.
+fn write_call<'a, W: Write>(f: &mut W, stack: &mut Vec<Piece<'a>>, name: &str, arg: &'a Expr) -> fmt::Result {
+    write!(f, "{}(", name)?;
+    stack.extend([Piece::Node(arg), Piece::Text(")")].into_iter().rev());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn explicit_full() -> DisplayOptions {
+        DisplayOptions { mul: MulStyle::Explicit, pow: PowStyle::DoubleStar, minimal_parens: false }
+    }
+
+    #[test]
+    fn default_matches_display() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let exprs = [
+            (2.0 * x.clone() + y.clone().pow(Expr::new_val(2.0))).pow(y.clone()),
+            x.clone() + -y.clone(),
+            x.clone() - (y.clone() - 1.0).sin() / x.clone().abs(),
+            x.clone().pow(Expr::new_rational(1, 3)),
+        ];
+
+        for expr in exprs {
+            assert_eq!(expr.display_with(&DisplayOptions::default()), expr.to_string());
+        }
+    }
+
+    #[test]
+    fn same_expression_two_styles() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = 3.0 * x.clone() + x.clone() * y.clone().pow(Expr::new_val(2.0)) - (x.clone() - y.clone());
+
+        assert_eq!(res.display_with(&DisplayOptions::default()), "3x + x * y^2 - (x - y)");
+        assert_eq!(res.display_with(&explicit_full()), "((3 * x) + (x * (y**2))) - (x - y)");
+    }
+
+    #[test]
+    fn mul_styles() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = 2.0 * x.clone() * y.clone();
+        let with_mul = |mul| DisplayOptions { mul, ..DisplayOptions::default() };
+
+        assert_eq!(res.display_with(&with_mul(MulStyle::Implicit)), "2x * y");
+        assert_eq!(res.display_with(&with_mul(MulStyle::Explicit)), "2 * x * y");
+        assert_eq!(res.display_with(&with_mul(MulStyle::Dot)), "2 · x · y");
+    }
+
+    #[test]
+    fn integral_constants() {
+        assert_eq!(Expr::new_val(2.0).to_string(), "2");
+        assert_eq!(Expr::new_val(-3.0).to_string(), "-3");
+        assert_eq!(Expr::new_val(-0.0).to_string(), "0");
+        assert_eq!(Expr::new_val(1e15).to_string(), "1000000000000000");
+        assert_eq!(Expr::new_val(2.9999999999999996).to_string(), "3");
+        assert_eq!(Expr::new_val(0.1 * 3.0 * 10.0).to_string(), "3");
+        assert_eq!((Expr::new_val(2.0000000000001) * Expr::new_var("x")).to_string(), "2x");
+        assert_eq!(Expr::new_var("x").pow(Expr::new_val(2.0)).to_string(), "x^2");
+    }
+
+    #[test]
+    fn fractional_constants() {
+        assert_eq!(Expr::new_val(2.5).to_string(), "2.5");
+        assert_eq!(Expr::new_val(0.1 + 0.2).to_string(), "0.3");
+        assert_eq!(Expr::new_val(1.0 / 3.0).to_string(), "0.333333333333");
+        assert_eq!(Expr::new_val(-2.0 / 3.0).to_string(), "-0.666666666667");
+        assert_eq!(Expr::new_val(1e-13).to_string(), "0.0000000000001");
+        assert_eq!(Expr::new_val(f64::NEG_INFINITY).to_string(), "-inf");
+        assert_eq!(Expr::new_val(f64::NAN).to_string(), "NaN");
+    }
+
+    #[test]
+    fn rational_roots() {
+        let x = Expr::new_var("x");
+
+        assert_eq!(x.clone().pow(Expr::new_rational(1, 2)).to_string(), "sqrt(x)");
+        assert_eq!((x.clone() + 1.0).pow(Expr::new_rational(1, 3)).to_string(), "cbrt(x + 1)");
+        assert_eq!(x.clone().pow(Expr::new_rational(2, 3)).to_string(), "x^(2/3)");
+        assert_eq!(x.pow(Expr::new_rational(1, 2)).display_with(&explicit_full()), "sqrt(x)");
+    }
+
+    #[test]
+    fn full_parens_keep_structure() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let opts = explicit_full();
+
+        assert_eq!((x.clone() + -y.clone()).display_with(&opts), "x + (-y)");
+        assert_eq!((x.clone() - -y.clone()).display_with(&opts), "x - (-y)");
+        assert_eq!(x.clone().pow(y.clone().pow(x.clone())).display_with(&opts), "x**(y**x)");
+        assert_eq!((x.clone() * y.clone()).sin().display_with(&opts), "sin(x * y)");
+        assert_eq!((-(-x.clone())).display_with(&opts), "-(-x)");
+    }
+}
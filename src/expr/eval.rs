@@ -1,4 +1,9 @@
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
 use crate::expr::Expr;
 use crate::symbol::Symbol;
 
@@ -7,6 +12,29 @@ use crate::symbol::Symbol;
 pub enum EvalError {
     SymbolNotFound(Symbol),
     UndefinedOperation,
+    InvalidArgument(&'static str),
+    /// Evaluating at the value with the given index of a batch failed with the inner error.
+    AtValue(usize, Box<EvalError>),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::SymbolNotFound(s) => write!(f, "symbol '{}' not found", s),
+            EvalError::UndefinedOperation => write!(f, "undefined operation"),
+            EvalError::InvalidArgument(msg) => write!(f, "invalid argument: {}", msg),
+            EvalError::AtValue(i, err) => write!(f, "evaluation at value {} failed: {}", i, err),
+        }
+    }
+}
+
+impl Error for EvalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EvalError::AtValue(_, err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 impl Expr {
@@ -15,6 +43,22 @@ impl Expr {
     /// If an error occurs during the evaluation, such as not finding a symbol in the map
     /// or attempting an undefined operation, it returns an `Err(EvalError)`.
     ///
+    /// Every operation must give a finite result: one that overflows to infinity or is not a
+    /// number, such as `10^1000`, `1e200 * 1e200` or `1/0`, returns
+    /// `Err(EvalError::UndefinedOperation)` on the spot instead of passing the infinity on to
+    /// the rest of the expression.
+    ///
+    /// Powers follow IEEE `pow`, so `0^0` evaluates to `1`, in agreement with `simplify`,
+    /// which rewrites `x^0` to `1`. An exponent of `1/2` or `1/3` is evaluated as a square or
+    /// cube root, and like any other fractional power, raising a negative base to it is an
+    /// undefined operation, even for the odd root `1/3`.
+    ///
+    /// Sums and products are evaluated by writing them out term by term with `expand_sums`
+    /// and `expand_products`, so their indices need not be in the map.
+    ///
+    /// The tree is walked with an explicit stack rather than recursion, so deeply nested
+    /// expressions can be evaluated without overflowing the call stack.
+    ///
     /// # Arguments
     ///
     /// * `&self` - A reference to the current instance of `Expr`.
@@ -36,64 +80,424 @@ impl Expr {
     /// assert_eq!(expr.eval(&vars).unwrap(), 27.0);
     /// ```
     pub fn eval(&self, vars: &HashMap<Symbol, f64>) -> Result<f64, EvalError> {
+        self.eval_with_precision(vars, Expr::DEFAULT_PRECISION)
+    }
+
+    /// The number of decimal places that `eval` rounds intermediate results to.
+    pub const DEFAULT_PRECISION: u32 = 14;
+
+    /// Evaluates the current expression like `eval`, but rounds the result of each operation
+    /// to `decimals` decimal places instead of `Expr::DEFAULT_PRECISION`.
+    ///
+    /// Fewer decimals hide more floating point noise at the cost of accuracy. Values too
+    /// large to be scaled by `10^decimals` without overflowing are left unrounded.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `vars` - A map from symbols to their corresponding values.
+    /// * `decimals` - The number of decimal places to round to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use std::collections::HashMap;
+    ///
+    /// let res = Expr::new_val(2.0) / Expr::new_val(3.0);
+    /// assert_eq!(res.eval_with_precision(&HashMap::new(), 3).unwrap(), 0.667);
+    /// ```
+    pub fn eval_with_precision(&self, vars: &HashMap<Symbol, f64>, decimals: u32) -> Result<f64, EvalError> {
+        self.eval_rounded(vars, Some(decimals))
+    }
+
+    /// Evaluates the current expression like `eval_with_precision`, rounding to `decimals`
+    /// places or not at all for `None`.
+    pub(crate) fn eval_rounded(&self, vars: &HashMap<Symbol, f64>, decimals: Option<u32>) -> Result<f64, EvalError> {
+        if self.has_indexed() {
+            return self.expand_indexed().eval_rounded(vars, decimals);
+        }
+        self.try_fold_up(|node, args| node.eval_node(&args, vars, decimals))
+    }
+
+    /// Evaluates the current expression once for each of `values` taken by the symbol `var`,
+    /// with every other symbol taken from `fixed`.
+    ///
+    /// This is convenient for sampling a function of one variable, for example for plotting.
+    /// A value given for `var` in `fixed` is ignored. If the evaluation fails for one of the
+    /// values, it returns `Err(EvalError::AtValue(i, err))` with the index `i` of that value
+    /// in `values` and the error `err` that `eval` gave.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `var` - The symbol to sweep over `values`.
+    /// * `values` - The values of `var` to evaluate at.
+    /// * `fixed` - A map from the remaining symbols to their corresponding values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let a = Expr::new_var("a");
+    /// let mut fixed = HashMap::new();
+    /// fixed.insert(Symbol::new("a"), 10.0);
+    /// let res = (a * x).eval_batch(&Symbol::new("x"), &[1.0, 2.0, 3.0], &fixed).unwrap();
+    /// assert_eq!(res, vec![10.0, 20.0, 30.0]);
+    /// ```
+    pub fn eval_batch(&self, var: &Symbol, values: &[f64], fixed: &HashMap<Symbol, f64>) -> Result<Vec<f64>, EvalError> {
+        let mut vars = fixed.clone();
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, val)| {
+                vars.insert(var.clone(), *val);
+                self.eval(&vars).map_err(|err| EvalError::AtValue(i, Box::new(err)))
+            })
+            .collect()
+    }
+
+    /// Evaluates the current expression like `eval`, computing each distinct subexpression
+    /// only once.
+    ///
+    /// Values are cached by structure for the duration of the call, so a subtree that occurs
+    /// many times, such as a large common factor, is evaluated on its first occurrence and
+    /// looked up afterwards. A subtree shared through the same `Rc` is also only visited once,
+    /// so the work is proportional to the number of distinct allocations rather than the size
+    /// of the fully unshared tree that `eval` walks. This pays off for expressions with heavy
+    /// repetition; for expressions without repeated subtrees plain `eval` is cheaper.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `vars` - A map from symbols to their corresponding values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let common = (x.clone() + 1.0).sin();
+    /// let expr = common.clone() * common.clone() + common;
+    /// let mut vars = HashMap::new();
+    /// vars.insert(Symbol::new("x"), 2.0);
+    /// assert_eq!(expr.eval_memo(&vars).unwrap(), expr.eval(&vars).unwrap());
+    /// ```
+    pub fn eval_memo(&self, vars: &HashMap<Symbol, f64>) -> Result<f64, EvalError> {
+        let nodes = self.distinct_nodes();
+        if nodes.iter().any(|node| matches!(node, Expr::Sum { .. } | Expr::Product { .. })) {
+            return self.expand_indexed().eval_memo(vars);
+        }
+        let hashes = subtree_hashes(&nodes);
+        let key = |node| MemoKey { hash: hashes[&(node as *const Expr)], expr: node };
+        let mut cache: HashMap<MemoKey, f64> = HashMap::new();
+        for node in nodes {
+            if cache.contains_key(&key(node)) {
+                continue;
+            }
+            let args: Vec<f64> = node.children().into_iter().map(|child| cache[&key(child)]).collect();
+            let val = node.eval_node(&args, vars, Some(Expr::DEFAULT_PRECISION))?;
+            cache.insert(key(node), val);
+        }
+        Ok(cache[&key(self)])
+    }
+
+    /// Evaluates the outermost operation of the current expression, given the already
+    /// evaluated values of its children in `args`, rounding to `decimals` places or not at
+    /// all for `None`.
+    pub(crate) fn eval_node(&self, args: &[f64], vars: &HashMap<Symbol, f64>, decimals: Option<u32>) -> Result<f64, EvalError> {
         match self {
             Expr::Const(c) => Ok(*c),
-            Expr::Symbol(s) => vars.get(&s).cloned().ok_or(EvalError::SymbolNotFound(s.clone())),
-            Expr::Add(lhs, rhs) => {
-                let lhs_val = lhs.eval(vars)?;
-                let rhs_val = rhs.eval(vars)?;
-                Ok(round(lhs_val + rhs_val))
-            }
-            Expr::Sub(lhs, rhs) => {
-                let lhs_val = lhs.eval(vars)?;
-                let rhs_val = rhs.eval(vars)?;
-                Ok(round(lhs_val - rhs_val))
-            }
-            Expr::Mul(lhs, rhs) => {
-                let lhs_val = lhs.eval(vars)?;
-                let rhs_val = rhs.eval(vars)?;
-                Ok(round(lhs_val * rhs_val))
-            }
-            Expr::Div(lhs, rhs) => {
-                let lhs_val = lhs.eval(vars)?;
-                let rhs_val = rhs.eval(vars)?;
-                Ok(round(lhs_val / rhs_val))
+            Expr::Rational(n, d) => Ok(round_to(*n as f64 / *d as f64, decimals)),
+            Expr::Pi => Ok(std::f64::consts::PI),
+            Expr::E => Ok(std::f64::consts::E),
+            Expr::Symbol(s) => vars.get(s).cloned().ok_or(EvalError::SymbolNotFound(s.clone())),
+            Expr::Add(_, _) => BinaryOp::Add.apply(args[0], args[1], decimals),
+            Expr::Sub(_, _) => BinaryOp::Sub.apply(args[0], args[1], decimals),
+            Expr::Mul(_, _) => BinaryOp::Mul.apply(args[0], args[1], decimals),
+            Expr::Div(_, _) => BinaryOp::Div.apply(args[0], args[1], decimals),
+            // A rational exponent is used unrounded, so 1/3 is recognized as a cube root
+            Expr::Pow(_, exp) => match **exp {
+                Expr::Rational(n, d) => BinaryOp::Pow.apply(args[0], n as f64 / d as f64, decimals),
+                _ => BinaryOp::Pow.apply(args[0], args[1], decimals),
+            },
+            Expr::Neg(_) => UnaryOp::Neg.apply(args[0], decimals),
+            Expr::Sin(_) => UnaryOp::Sin.apply(args[0], decimals),
+            Expr::Cos(_) => UnaryOp::Cos.apply(args[0], decimals),
+            Expr::Exp(_) => UnaryOp::Exp.apply(args[0], decimals),
+            Expr::Ln(_) => UnaryOp::Ln.apply(args[0], decimals),
+            Expr::Abs(_) => UnaryOp::Abs.apply(args[0], decimals),
+            Expr::Sign(_) => UnaryOp::Sign.apply(args[0], decimals),
+            Expr::Sqrt(_) => UnaryOp::Sqrt.apply(args[0], decimals),
+            Expr::Sum { .. } | Expr::Product { .. } => unreachable!("sums and products are expanded before evaluation"),
+        }
+    }
+}
+
+/// The operations of `Expr` that take two operands, as used by `RpnOp::Binary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl BinaryOp {
+    /// Applies the operation to evaluated operands, rounding the result to `decimals` places
+    /// like `eval`, or not at all for `None`.
+    ///
+    /// An infinite or `NaN` result, whether from overflow or from a non-finite operand, is an
+    /// undefined operation.
+    pub(crate) fn apply(self, lhs_val: f64, rhs_val: f64, decimals: Option<u32>) -> Result<f64, EvalError> {
+        let res = match self {
+            BinaryOp::Add => lhs_val + rhs_val,
+            BinaryOp::Sub => lhs_val - rhs_val,
+            BinaryOp::Mul => lhs_val * rhs_val,
+            BinaryOp::Div => lhs_val / rhs_val,
+            // powf gives 0^0 = 1, which simplify relies on. Square and cube roots use the
+            // dedicated functions, which are exact on perfect powers
+            BinaryOp::Pow if rhs_val == 0.5 => lhs_val.sqrt(),
+            BinaryOp::Pow if rhs_val == 1.0 / 3.0 && lhs_val >= 0.0 => lhs_val.cbrt(),
+            BinaryOp::Pow => lhs_val.powf(rhs_val),
+        };
+        if res.is_finite() {
+            Ok(round_to(res, decimals))
+        } else {
+            Err(EvalError::UndefinedOperation)
+        }
+    }
+}
+
+/// The operations of `Expr` that take one operand, as used by `RpnOp::Unary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnaryOp {
+    Neg,
+    Sin,
+    Cos,
+    Exp,
+    Ln,
+    Abs,
+    Sign,
+    Sqrt,
+}
+
+impl UnaryOp {
+    /// Applies the operation to an evaluated operand, rounding the result like
+    /// `BinaryOp::apply`.
+    ///
+    /// As for `BinaryOp::apply`, an infinite or `NaN` result is an undefined operation.
+    pub(crate) fn apply(self, expr_val: f64, decimals: Option<u32>) -> Result<f64, EvalError> {
+        let res = match self {
+            UnaryOp::Neg => -expr_val,
+            UnaryOp::Sin => round_to(expr_val.sin(), decimals),
+            UnaryOp::Cos => round_to(expr_val.cos(), decimals),
+            UnaryOp::Exp => round_to(expr_val.exp(), decimals),
+            UnaryOp::Ln if expr_val <= 0.0 => return Err(EvalError::UndefinedOperation),
+            UnaryOp::Ln => round_to(expr_val.ln(), decimals),
+            UnaryOp::Abs => expr_val.abs(),
+            UnaryOp::Sqrt if expr_val < 0.0 => return Err(EvalError::UndefinedOperation),
+            UnaryOp::Sqrt => round_to(expr_val.sqrt(), decimals),
+            UnaryOp::Sign if expr_val == 0.0 => 0.0,
+            UnaryOp::Sign => expr_val.signum(),
+        };
+        if res.is_finite() {
+            Ok(res)
+        } else {
+            Err(EvalError::UndefinedOperation)
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluates as much of the current expression as possible using the given map of symbols
+    /// to values, leaving unknown symbols in place.
+    ///
+    /// Known symbols are replaced by their values and any subtree that then consists only of
+    /// constants is folded into a single `Const`. Folds that would produce a non-finite result,
+    /// such as division by zero, are left unevaluated. The named constants `pi` and `e` are
+    /// kept symbolic.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `vars` - A map from symbols to their corresponding values; it need not be complete.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let mut vars = HashMap::new();
+    /// vars.insert(Symbol::new("x"), 2.0);
+    /// assert_eq!((x + y.clone()).eval_partial(&vars), Expr::new_val(2.0) + y);
+    /// ```
+    pub fn eval_partial(&self, vars: &HashMap<Symbol, f64>) -> Expr {
+        let pre = |node: &Expr| match node {
+            Expr::Symbol(s) => match vars.get(s) {
+                Some(val) => Some(Expr::new_val(*val)),
+                None => Some(node.clone()),
+            },
+            Expr::Const(_) | Expr::Rational(_, _) | Expr::Pi | Expr::E => Some(node.clone()),
+            // The index of a sum or product is bound, so a value given for it does not apply
+            // to the body
+            Expr::Sum { index, .. } | Expr::Product { index, .. } if vars.contains_key(index) => {
+                let mut inner = vars.clone();
+                inner.remove(index);
+                Some(node.eval_partial(&inner))
             }
-            Expr::Pow(lhs, rhs) => {
-                let base_val = lhs.eval(vars)?;
-                let exp_val = rhs.eval(vars)?;
-                let res = base_val.powf(exp_val);
-                if res.is_nan() || res.is_infinite() {
-                    Err(EvalError::UndefinedOperation)
-                } else {
-                    Ok(round(res))
+            _ => None,
+        };
+        self.transform_pruned(pre, |partial| {
+            let foldable = partial
+                .children()
+                .iter()
+                .all(|child| matches!(child, Expr::Const(_) | Expr::Rational(_, _)));
+            if foldable {
+                if let Ok(val) = partial.eval(&HashMap::new()) {
+                    return Expr::new_val(val);
                 }
             }
-            Expr::Neg(expr) => {
-                let expr_val = expr.eval(vars)?;
-                Ok(-expr_val)
+            partial
+        })
+    }
+}
+
+/// A cache key for `eval_memo` and `diff_memo` that pairs a subtree with its precomputed
+/// structural hash, so looking it up does not rehash the whole subtree.
+pub(crate) struct MemoKey<'a> {
+    pub(crate) hash: u64,
+    pub(crate) expr: &'a Expr,
+}
+
+impl Hash for MemoKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl PartialEq for MemoKey<'_> {
+    fn eq(&self, other: &MemoKey) -> bool {
+        self.hash == other.hash && (std::ptr::eq(self.expr, other.expr) || self.expr == other.expr)
+    }
+}
+
+impl Eq for MemoKey<'_> {}
+
+/// Returns a structural hash for each of `nodes`, keyed by the node's address.
+///
+/// The nodes must come from `distinct_nodes`, so every child is hashed before its parent.
+/// Each hash is built from the node's variant and its children's hashes, so every distinct
+/// allocation is hashed once. Equal subtrees get equal hashes.
+pub(crate) fn subtree_hashes(nodes: &[&Expr]) -> HashMap<*const Expr, u64> {
+    let mut hashes: HashMap<*const Expr, u64> = HashMap::new();
+    for node in nodes {
+        let children = node.children();
+        let mut state = DefaultHasher::new();
+        if children.is_empty() {
+            node.hash(&mut state);
+        } else {
+            mem::discriminant(*node).hash(&mut state);
+            for child in children {
+                hashes[&(child as *const Expr)].hash(&mut state);
             }
         }
+        hashes.insert(*node as *const Expr, state.finish());
     }
+    hashes
 }
 
-/// Rounds a given `f64` value to the 14th decimal place.
+/// Rounds a given `f64` value to `decimals` decimal places.
 ///
 /// This function is used in the `eval` method above to round the results of floating
-/// point operations, mitigating the effects of floating point precision errors.
+/// point operations, mitigating the effects of floating point precision errors. Values that
+/// would overflow when scaled are returned unchanged.
 ///
 /// # Arguments
 ///
 /// * `val` - The `f64` value to be rounded.
-fn round(val: f64) -> f64 {
-    (val * 10e14).round() / 10e14
+/// * `decimals` - The number of decimal places to keep.
+pub(crate) fn round(val: f64, decimals: u32) -> f64 {
+    let scale = 10f64.powi(decimals.min(i32::MAX as u32) as i32);
+    let scaled = val * scale;
+    if scaled.is_finite() {
+        scaled.round() / scale
+    } else {
+        val
+    }
+}
+
+/// Rounds `val` to `decimals` decimal places like `round`, or leaves it as is for `None`.
+fn round_to(val: f64, decimals: Option<u32>) -> f64 {
+    decimals.map_or(val, |decimals| round(val, decimals))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::rc::Rc;
     use std::collections::HashMap;
 
+    #[test]
+    fn eval_batch_sweep() {
+        let x = Expr::new_var("x");
+        let res = x.clone().pow(Expr::new_val(2.0)).eval_batch(&Symbol::new("x"), &[0.0, 1.0, 2.0, 3.0], &HashMap::new());
+        assert_eq!(res.unwrap(), vec![0.0, 1.0, 4.0, 9.0]);
+
+        let mut fixed = HashMap::new();
+        fixed.insert(Symbol::new("x"), 100.0);
+        fixed.insert(Symbol::new("y"), 1.0);
+        let res = (x.clone() + Expr::new_var("y")).eval_batch(&Symbol::new("x"), &[1.0, 2.0], &fixed);
+        assert_eq!(res.unwrap(), vec![2.0, 3.0]);
+        assert_eq!(x.eval_batch(&Symbol::new("x"), &[], &HashMap::new()).unwrap(), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn eval_batch_reports_failing_value() {
+        let x = Expr::new_var("x");
+        let res = x.clone().ln().eval_batch(&Symbol::new("x"), &[1.0, 2.0, -1.0, 3.0], &HashMap::new());
+        assert!(matches!(res, Err(EvalError::AtValue(2, err)) if matches!(*err, EvalError::UndefinedOperation)));
+
+        let res = (x + Expr::new_var("y")).eval_batch(&Symbol::new("x"), &[1.0], &HashMap::new());
+        assert!(matches!(res, Err(EvalError::AtValue(0, err)) if matches!(*err, EvalError::SymbolNotFound(_))));
+    }
+
+    #[test]
+    fn eval_error_messages() {
+        let inner = EvalError::SymbolNotFound(Symbol::new("x"));
+        assert_eq!(inner.to_string(), "symbol 'x' not found");
+        assert_eq!(EvalError::SymbolNotFound(Symbol::indexed("x", 2)).to_string(), "symbol 'x_2' not found");
+        assert_eq!(EvalError::UndefinedOperation.to_string(), "undefined operation");
+        assert_eq!(EvalError::InvalidArgument("malformed RPN program").to_string(), "invalid argument: malformed RPN program");
+
+        let err = EvalError::AtValue(3, Box::new(inner));
+        assert_eq!(err.to_string(), "evaluation at value 3 failed: symbol 'x' not found");
+        assert_eq!(err.source().unwrap().to_string(), "symbol 'x' not found");
+        assert!(EvalError::UndefinedOperation.source().is_none());
+    }
+
+    #[test]
+    fn eval_error_propagates_as_boxed_error() {
+        fn run() -> Result<f64, Box<dyn Error>> {
+            Ok(Expr::new_var("y").eval(&HashMap::new())?)
+        }
+        assert_eq!(run().unwrap_err().to_string(), "symbol 'y' not found");
+    }
+
     #[test]
     fn eval_basic_operations() {
         let x = Expr::new_var("x");
@@ -114,4 +518,277 @@ mod tests {
         let res_complicated = (res_add.pow(res_sub) * res_div) * res_mul;
         assert_eq!(res_complicated.eval(&vars).unwrap(), 1.8);
     }
+
+    #[test]
+    fn eval_partial_leaves_unknowns() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 2.0);
+
+        let res = x.clone() + y.clone();
+        assert_eq!(res.eval_partial(&vars), Expr::Add(Rc::new(Expr::Const(2.0)), Rc::new(y.clone())));
+
+        let res = (x.clone() * 3.0 + 1.0) * y.clone();
+        assert_eq!(res.eval_partial(&vars), Expr::new_val(7.0) * y);
+    }
+
+    #[test]
+    fn eval_partial_complete_map() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 3.0);
+
+        assert_eq!(x.pow(Expr::new_val(2.0)).eval_partial(&vars), Expr::new_val(9.0));
+    }
+
+    #[test]
+    fn eval_partial_keeps_named_constants() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 3.0);
+
+        assert_eq!(Expr::Pi.eval_partial(&HashMap::new()), Expr::Pi);
+        assert_eq!(Expr::E.eval_partial(&vars), Expr::E);
+        assert_eq!((x * Expr::Pi).eval_partial(&vars), Expr::new_val(3.0) * Expr::Pi);
+    }
+
+    #[test]
+    fn eval_partial_skips_non_finite() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 0.0);
+
+        let res = (1.0 / x.clone()) + y.clone();
+        assert_eq!(res.eval_partial(&vars), (1.0 / Expr::new_val(0.0)) + y);
+        let res = x.clone() / x.clone();
+        assert_eq!(res.eval_partial(&vars), Expr::new_val(0.0) / Expr::new_val(0.0));
+    }
+
+    #[test]
+    fn eval_abs_and_sign() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+
+        vars.insert(x.get_symbol().unwrap(), -2.5);
+        assert_eq!(x.clone().abs().eval(&vars).unwrap(), 2.5);
+        assert_eq!(x.clone().sign().eval(&vars).unwrap(), -1.0);
+
+        vars.insert(x.get_symbol().unwrap(), 0.0);
+        assert_eq!(x.clone().abs().eval(&vars).unwrap(), 0.0);
+        assert_eq!(x.clone().sign().eval(&vars).unwrap(), 0.0);
+
+        vars.insert(x.get_symbol().unwrap(), 4.0);
+        assert_eq!(x.clone().abs().eval(&vars).unwrap(), 4.0);
+        assert_eq!(x.sign().eval(&vars).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn eval_sqrt() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+
+        vars.insert(x.get_symbol().unwrap(), 9.0);
+        assert_eq!(x.clone().sqrt().eval(&vars).unwrap(), 3.0);
+
+        vars.insert(x.get_symbol().unwrap(), -4.0);
+        assert!(matches!(x.sqrt().eval(&vars), Err(EvalError::UndefinedOperation)));
+    }
+
+    #[test]
+    fn eval_named_constants() {
+        let vars: HashMap<Symbol, f64> = HashMap::new();
+
+        assert_eq!(Expr::Pi.eval(&vars).unwrap(), std::f64::consts::PI);
+        assert_eq!(Expr::E.eval(&vars).unwrap(), std::f64::consts::E);
+        assert_eq!(Expr::E.ln().eval(&vars).unwrap(), 1.0);
+        assert_eq!(Expr::Pi.cos().eval(&vars).unwrap(), -1.0);
+    }
+
+    #[test]
+    fn eval_deep_chain() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 1.0);
+
+        let mut res = x.clone();
+        for _ in 0..100_000 {
+            res += x.clone();
+        }
+        assert_eq!(res.eval(&vars).unwrap(), 100_001.0);
+
+        // Every pass over the tree must cope with the same depth
+        let var = x.get_symbol().unwrap();
+        let y = Expr::new_var("y");
+        assert_eq!(res.to_string().len(), 100_001 + 3 * 100_000);
+        assert_eq!(res.to_latex().len(), 100_001 + 3 * 100_000);
+        assert_eq!(res.to_python().len(), 100_001 + 3 * 100_000);
+        assert_eq!(res.to_c().len(), 100_001 + 3 * 100_000);
+        assert_eq!(res.variables().len(), 1);
+        assert_eq!(res.eval_partial(&vars), Expr::new_val(100_001.0));
+        assert_eq!(res.poly_coeffs(&var), Some(vec![0.0, 100_001.0]));
+        assert!(res.integrate(&var).is_some());
+        assert_eq!(res.canonicalize(), res);
+        let renamed = res.subs(&var, &y);
+        assert_eq!(renamed.variables().len(), 1);
+        assert_eq!(renamed.replace(&y, &x), res);
+    }
+
+    #[test]
+    fn eval_memo_shared_subtrees() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 0.5);
+        vars.insert(y.get_symbol().unwrap(), 2.0);
+
+        // Each level uses the previous one twice, so the tree doubles in size at every step
+        // while containing only a handful of distinct subtrees.
+        let mut res = (x.clone() * y.clone()).sin();
+        for _ in 0..12 {
+            res = (res.clone() + x.clone()).cos() * res.clone() + y.clone();
+        }
+        assert!(res.node_count() > 10_000);
+        assert_eq!(res.eval_memo(&vars).unwrap(), res.eval(&vars).unwrap());
+
+        // With sixty levels the unshared tree has more than 2^60 nodes, far too many for
+        // `eval`, but only a few hundred distinct allocations
+        let mut res = (x.clone() * y.clone()).sin();
+        let mut expected = 1f64.sin();
+        for _ in 0..60 {
+            res = (res.clone() + x.clone()).cos() * res.clone() + y.clone();
+            expected = (expected + 0.5).cos() * expected + 2.0;
+        }
+        assert!((res.eval_memo(&vars).unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn eval_memo_matches_eval() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 3.0);
+
+        let res = (x.clone() + 1.0).pow(x.clone()) / (x.clone() + 1.0) - Expr::new_rational(1, 3);
+        assert_eq!(res.eval_memo(&vars).unwrap(), res.eval(&vars).unwrap());
+
+        let res = x.clone().ln() + y.clone();
+        assert!(matches!(res.eval_memo(&vars), Err(EvalError::SymbolNotFound(_))));
+        let res = (x.clone() - 3.0).ln();
+        assert!(matches!(res.eval_memo(&vars), Err(EvalError::UndefinedOperation)));
+    }
+
+    #[test]
+    fn eval_memo_deep_chain() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 1.0);
+
+        let mut res = x.clone();
+        for _ in 0..100_000 {
+            res = x.clone() + res;
+        }
+        assert_eq!(res.eval_memo(&vars).unwrap(), 100_001.0);
+    }
+
+    #[test]
+    fn eval_with_precision_levels() {
+        let vars: HashMap<Symbol, f64> = HashMap::new();
+        let res = Expr::new_val(2.0) / Expr::new_val(3.0);
+
+        assert_eq!(res.eval_with_precision(&vars, 0).unwrap(), 1.0);
+        assert_eq!(res.eval_with_precision(&vars, 2).unwrap(), 0.67);
+        assert_eq!(res.eval_with_precision(&vars, 6).unwrap(), 0.666667);
+        assert_eq!(res.eval_with_precision(&vars, 14).unwrap(), res.eval(&vars).unwrap());
+        assert_eq!(res.eval_with_precision(&vars, 400).unwrap(), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn eval_rational_roots() {
+        let x = Expr::new_var("x");
+        let half = x.clone().pow(Expr::new_rational(1, 2));
+        let third = x.clone().pow(Expr::new_rational(1, 3));
+        for val in [0.0, 0.25, 2.0, 9.0, 1e6] {
+            let mut vars = HashMap::new();
+            vars.insert(Symbol::new("x"), val);
+            assert_eq!(half.eval(&vars).unwrap(), x.clone().sqrt().eval(&vars).unwrap());
+        }
+
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("x"), 27.0);
+        assert_eq!(third.eval(&vars).unwrap(), 3.0);
+        assert_eq!(third.eval_memo(&vars).unwrap(), 3.0);
+        assert_eq!(x.clone().pow(Expr::new_rational(2, 3)).eval(&vars).unwrap(), 9.0);
+
+        vars.insert(Symbol::new("x"), -4.0);
+        assert!(matches!(half.eval(&vars), Err(EvalError::UndefinedOperation)));
+        assert!(matches!(third.eval(&vars), Err(EvalError::UndefinedOperation)));
+    }
+
+    #[test]
+    fn round_at_fourteenth_place() {
+        assert_eq!(round(0.123456789012349, 14), 0.12345678901235);
+        assert_eq!(round(0.123456789012341, 14), 0.12345678901234);
+        assert_eq!(round(-0.123456789012349, 14), -0.12345678901235);
+
+        let vars: HashMap<Symbol, f64> = HashMap::new();
+        let res = Expr::new_val(0.123456789012349) + Expr::new_val(0.0);
+        assert_eq!(res.eval(&vars).unwrap(), 0.12345678901235);
+    }
+
+    #[test]
+    fn eval_rounds_away_float_noise() {
+        // Unrounded, 0.1 + 0.2 is 0.30000000000000004 and 0.1 * 3.0 is 0.30000000000000004
+        let x = Expr::new_var("x");
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("x"), 0.1);
+        assert_eq!((x.clone() + 0.2).eval(&vars).unwrap(), 0.3);
+        assert_eq!((3.0 * x.clone()).eval(&vars).unwrap(), 0.3);
+        assert_eq!((x + 0.2).eval_memo(&vars).unwrap(), 0.3);
+    }
+
+    #[test]
+    fn eval_large_values_not_rounded_to_nan() {
+        let vars: HashMap<Symbol, f64> = HashMap::new();
+        let res = Expr::new_val(1e300) * Expr::new_val(2.0);
+
+        assert_eq!(res.eval(&vars).unwrap(), 2e300);
+    }
+
+    #[test]
+    fn eval_overflow_is_undefined() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(Symbol::new("x"), 1e200);
+
+        // x * x overflows to infinity, and 1 / inf would otherwise hide it as 0
+        let res = 1.0 / (x.clone() * x.clone());
+        assert!(matches!(res.eval(&vars), Err(EvalError::UndefinedOperation)));
+        assert!(matches!(res.eval_memo(&vars), Err(EvalError::UndefinedOperation)));
+        assert!(matches!(res.compile(&[Symbol::new("x")])(&[1e200]), Err(EvalError::UndefinedOperation)));
+        assert!(matches!((x.clone() + x.clone() * 1e200).eval(&vars), Err(EvalError::UndefinedOperation)));
+        assert!(matches!(Expr::new_val(10.0).pow(Expr::new_val(1000.0)).eval(&vars), Err(EvalError::UndefinedOperation)));
+        assert!(matches!((x.clone() / 0.0).eval(&vars), Err(EvalError::UndefinedOperation)));
+        assert!(matches!((Expr::new_val(f64::INFINITY) - 1.0).eval(&vars), Err(EvalError::UndefinedOperation)));
+        assert_eq!((x.clone() * 1e100).eval(&vars).unwrap(), 1e300);
+    }
+
+    #[test]
+    fn eval_functions() {
+        let x = Expr::new_var("x");
+        let mut vars: HashMap<Symbol, f64> = HashMap::new();
+        vars.insert(x.get_symbol().unwrap(), 0.0);
+
+        assert_eq!(x.clone().sin().eval(&vars).unwrap(), 0.0);
+        assert_eq!(x.clone().cos().eval(&vars).unwrap(), 1.0);
+        assert_eq!(x.clone().exp().eval(&vars).unwrap(), 1.0);
+        assert!(matches!(x.clone().ln().eval(&vars), Err(EvalError::UndefinedOperation)));
+
+        vars.insert(x.get_symbol().unwrap(), -1.0);
+        assert!(matches!(x.clone().ln().eval(&vars), Err(EvalError::UndefinedOperation)));
+
+        vars.insert(x.get_symbol().unwrap(), 1.0);
+        assert_eq!(x.ln().eval(&vars).unwrap(), 0.0);
+    }
 }
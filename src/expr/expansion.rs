@@ -1,10 +1,17 @@
+use std::rc::Rc;
 use crate::expr::Expr;
 
 impl Expr {
     /// Expands the current expression to a possibly expanded form.
     ///
     /// The method expands mathematical expressions based on several
-    /// algebraic rules.
+    /// algebraic rules. The tree is rebuilt bottom-up with an explicit stack rather than
+    /// recursion, so deeply nested expressions do not overflow the call stack.
+    ///
+    /// Sums and differences raised to a small non-negative integer power are expanded by
+    /// repeated multiplication, up to `Expr::MAX_EXPAND_POWER`; use `expand_with_limit` to
+    /// choose a different limit. Negation is pushed inward, so `-(a + b)` becomes `-a + -b`
+    /// and `-(a * b)` becomes `(-a) * b`.
     ///
     /// # Example
     ///
@@ -17,35 +24,252 @@ impl Expr {
     /// assert_eq!(res.expand(), x * Expr::new_val(2.0) + y * Expr::new_val(2.0));
     /// ```
     pub fn expand(&self) -> Expr {
-        match self {
-            Expr::Mul(lhs, rhs) => {
-                let lhs = lhs.expand();
-                let rhs = rhs.expand();
-                match (&lhs, &rhs) {
-                    // (a + b) * c -> a*c + b*c
-                    (Expr::Add(a, b), c) | (c, Expr::Add(a, b)) => 
-                        Expr::Add(Box::new(Expr::Mul(a.clone(), Box::new(c.clone()))),
-                                  Box::new(Expr::Mul(b.clone(), Box::new(c.clone())))).expand(),
-                    // c * (a - b) -> c*a - c*b
-                    (Expr::Sub(a, b), c) | (c, Expr::Sub(a, b)) => 
-                        Expr::Sub(Box::new(Expr::Mul(Box::new(c.clone()), a.clone())),
-                                  Box::new(Expr::Mul(Box::new(c.clone()), b.clone()))).expand(),
-                    _ => Expr::Mul(Box::new(lhs), Box::new(rhs)),
+        self.expand_with_limit(Expr::MAX_EXPAND_POWER)
+    }
+
+    /// The largest exponent that `expand` will multiply out for a power of a sum.
+    pub const MAX_EXPAND_POWER: u32 = 8;
+
+    /// Expands the current expression like `expand`, but only multiplies out powers of sums
+    /// whose exponent is at most `max_power`.
+    ///
+    /// Expanding `(a + b)^n` produces `2^n` products, so a low limit keeps the result from
+    /// blowing up. Powers above the limit are left as they are.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = (x.clone() + 1.0).pow(Expr::new_val(3.0));
+    /// assert_eq!(res.expand_with_limit(2), res);
+    /// assert_ne!(res.expand_with_limit(3), res);
+    /// ```
+    pub fn expand_with_limit(&self, max_power: u32) -> Expr {
+        self.transform_up(|node| node.expand_node(max_power))
+    }
+
+    /// Applies the expansion rules to the outermost operation of an expression whose
+    /// children have already been expanded.
+    fn expand_node(self, max_power: u32) -> Expr {
+        match &self {
+            // (a ± b) * (c ± d) -> a*c ± a*d ± b*c ± b*d, keeping each operand on its side
+            Expr::Mul(lhs, rhs) if lhs.is_sum() || rhs.is_sum() => {
+                let (lhs_terms, rhs_terms) = (lhs.sum_terms(), rhs.sum_terms());
+                let mut acc: Option<Expr> = None;
+                for &(lhs_sign, l) in &lhs_terms {
+                    for &(rhs_sign, r) in &rhs_terms {
+                        let product = Rc::new(Expr::Mul(Rc::new(l.clone()), Rc::new(r.clone())));
+                        let negative = lhs_sign * rhs_sign < 0.0;
+                        acc = Some(match acc {
+                            None if negative => Expr::Neg(product),
+                            None => (*product).clone(),
+                            Some(acc) if negative => Expr::Sub(Rc::new(acc), product),
+                            Some(acc) => Expr::Add(Rc::new(acc), product),
+                        });
+                    }
+                }
+                acc.expect("a product has at least one term on each side")
+            }
+            Expr::Pow(base, exp) => match (&**base, &**exp) {
+                // (a + b)^n -> (a + b) * ... * (a + b), then distribute
+                (Expr::Add(_, _) | Expr::Sub(_, _), Expr::Const(n))
+                    if n.fract() == 0.0 && *n >= 2.0 && *n <= max_power as f64 =>
+                {
+                    let mut product = (**base).clone();
+                    for _ in 1..(*n as u32) {
+                        product = Expr::Mul(Rc::new(product), base.clone());
+                    }
+                    product.expand_with_limit(max_power)
                 }
+                _ => self,
             },
-            Expr::Add(lhs, rhs) => Expr::Add(Box::new(lhs.expand()), Box::new(rhs.expand())),
-            Expr::Sub(lhs, rhs) => Expr::Sub(Box::new(lhs.expand()), Box::new(rhs.expand())),
-            Expr::Div(lhs, rhs) => Expr::Div(Box::new(lhs.expand()), Box::new(rhs.expand())),
-            Expr::Pow(lhs, rhs) => Expr::Pow(Box::new(lhs.expand()), Box::new(rhs.expand())),
-            _ => self.clone(),
+            // -(a ± b) -> -a ∓ b, with each term negated like a product below
+            Expr::Neg(expr) if expr.is_sum() => expr
+                .sum_terms()
+                .into_iter()
+                .map(|(sign, term)| if sign > 0.0 { negate_term(term) } else { term.clone() })
+                .reduce(|acc, term| Expr::Add(Rc::new(acc), Rc::new(term)))
+                .expect("a sum has at least one term"),
+            // -(a * b) -> (-a) * b
+            Expr::Neg(expr) if matches!(**expr, Expr::Mul(_, _)) => negate_term(expr),
+            _ => self,
+        }
+    }
+
+    /// Returns `true` if the outermost operation is an addition or a subtraction.
+    fn is_sum(&self) -> bool {
+        matches!(self, Expr::Add(_, _) | Expr::Sub(_, _))
+    }
+
+    /// Returns the signed terms of the current expression if it is a sum, or the expression
+    /// itself with a positive sign otherwise.
+    fn sum_terms(&self) -> Vec<(f64, &Expr)> {
+        let mut terms = Vec::new();
+        if self.is_sum() {
+            self.signed_terms(1.0, &mut terms);
+        } else {
+            terms.push((1.0, self));
         }
+        terms
     }
 }
 
+/// Negates a term by negating the leftmost factor of its chain of products, so `a * b`
+/// becomes `(-a) * b` and a negated factor loses its negation.
+fn negate_term(term: &Expr) -> Expr {
+    let mut rights = Vec::new();
+    let mut left = term;
+    while let Expr::Mul(lhs, rhs) = left {
+        rights.push(rhs.clone());
+        left = lhs;
+    }
+    let mut res = match left {
+        Expr::Neg(inner) => (**inner).clone(),
+        _ => Expr::Neg(Rc::new(left.clone())),
+    };
+    for rhs in rights.into_iter().rev() {
+        res = Expr::Mul(Rc::new(res), rhs);
+    }
+    res
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn expand_square() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = (x.clone() + y.clone()).pow(Expr::new_val(2.0)).expand().simplify();
+
+        assert_eq!(res, x.clone().pow(Expr::new_val(2.0)) + Expr::new_val(2.0) * (x.clone() * y.clone()) + y.clone().pow(Expr::new_val(2.0)));
+    }
+
+    #[test]
+    fn expand_cube() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let cube = (x.clone() - y.clone()).pow(Expr::new_val(3.0));
+        let res = cube.expand();
+
+        assert!(!res.iter().any(|node| matches!(node, Expr::Pow(_, _))));
+        let mut vars = std::collections::HashMap::new();
+        vars.insert(crate::symbol::Symbol::new("x"), 5.0);
+        vars.insert(crate::symbol::Symbol::new("y"), 2.0);
+        assert_eq!(res.eval(&vars).unwrap(), 27.0);
+    }
+
+    #[test]
+    fn expand_power_limit() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let high = (x.clone() + y.clone()).pow(Expr::new_val(20.0));
+        assert_eq!(high.expand(), high);
+
+        let fractional = (x.clone() + y.clone()).pow(Expr::new_val(2.5));
+        assert_eq!(fractional.expand(), fractional);
+
+        let square = (x.clone() + y.clone()).pow(Expr::new_val(2.0));
+        assert_eq!(square.expand_with_limit(1), square);
+        assert_eq!(x.clone().pow(Expr::new_val(2.0)).expand(), x.pow(Expr::new_val(2.0)));
+    }
+
+    #[test]
+    fn expand_deep_chain() {
+        let x = Expr::new_var("x");
+        let mut res = x.clone();
+        for _ in 0..100_000 {
+            res += x.clone();
+        }
+        assert_eq!(res.expand().node_count(), res.node_count());
+    }
+
+    #[test]
+    fn expand_product_of_sums() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+        let d = Expr::new_var("d");
+        let res = (a.clone() + b.clone()) * (c.clone() + d.clone());
+
+        assert_eq!(res.expand(), a.clone() * c.clone() + a.clone() * d.clone() + b.clone() * c.clone() + b.clone() * d.clone());
+    }
+
+    #[test]
+    fn expand_product_of_nested_sums() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+        let res = (a.clone() + b.clone() + c.clone()) * (a.clone() - b.clone());
+        let expanded = res.expand();
+
+        assert!(!expanded.iter().any(|node| matches!(node, Expr::Mul(lhs, rhs)
+            if matches!(**lhs, Expr::Add(_, _) | Expr::Sub(_, _)) || matches!(**rhs, Expr::Add(_, _) | Expr::Sub(_, _)))));
+        assert!(expanded.probably_equal(&res, 16));
+    }
+
+    #[test]
+    fn expand_product_of_difference_and_sum() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+        let d = Expr::new_var("d");
+
+        let res = (a.clone() - b.clone()) * (c.clone() + d.clone());
+        assert_eq!(res.expand(), a.clone() * c.clone() + a.clone() * d.clone() - b.clone() * c.clone() - b.clone() * d.clone());
+
+        let res = (a.clone() + b.clone()) * (c.clone() - d.clone());
+        assert_eq!(res.expand(), a.clone() * c.clone() - a.clone() * d.clone() + b.clone() * c.clone() - b.clone() * d.clone());
+
+        let res = (a.clone() - b.clone()) * (c.clone() - d.clone());
+        assert_eq!(res.expand(), a.clone() * c.clone() - a.clone() * d.clone() - b.clone() * c.clone() + b.clone() * d.clone());
+    }
+
+    #[test]
+    fn expand_negated_sum() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+
+        let neg = |e: &Expr| Expr::Neg(Rc::new(e.clone()));
+        assert_eq!((-(a.clone() + b.clone())).expand(), Expr::Add(Rc::new(neg(&a)), Rc::new(neg(&b))));
+        assert_eq!((-(a.clone() - b.clone())).expand(), Expr::Add(Rc::new(neg(&a)), Rc::new(b.clone())));
+        assert_eq!((-(a.clone() + b.clone() - c.clone())).expand(), neg(&a) + neg(&b) + c.clone());
+        assert_eq!((-(-a.clone() + b.clone())).expand(), a.clone() + neg(&b));
+        assert_eq!((-(a.clone() * b.clone() + c.clone())).expand(), neg(&a) * b.clone() + neg(&c));
+    }
+
+    #[test]
+    fn expand_negated_product() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+
+        let neg = |e: &Expr| Expr::Neg(Rc::new(e.clone()));
+        assert_eq!((-(a.clone() * b.clone())).expand(), Expr::Mul(Rc::new(neg(&a)), Rc::new(b.clone())));
+        assert_eq!((-(a.clone() * b.clone() * c.clone())).expand(), neg(&a) * b.clone() * c.clone());
+        assert_eq!((-(-a.clone() * b.clone())).expand(), a.clone() * b.clone());
+        // The product of sums is distributed first
+        let res = -((a.clone() + b.clone()) * c.clone());
+        assert_eq!(res.expand(), neg(&a) * c.clone() + neg(&b) * c.clone());
+        assert_eq!((-a.clone().sin()).expand(), -a.sin());
+    }
+
+    #[test]
+    fn expand_keeps_operand_side() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+
+        assert_eq!(((a.clone() - b.clone()) * c.clone()).expand(), a.clone() * c.clone() - b.clone() * c.clone());
+        assert_eq!((c.clone() * (a.clone() - b.clone())).expand(), c.clone() * a.clone() - c.clone() * b.clone());
+        assert_eq!((c.clone() * (a.clone() + b.clone())).expand(), c.clone() * a.clone() + c.clone() * b.clone());
+        assert_eq!(((-b.clone() - a.clone()) * c.clone()).expand(), -(b.clone() * c.clone()) - a.clone() * c.clone());
+    }
+
     #[test]
     fn test_expansion() {
         // Testing (a + b) * c -> a*c + b*c
@@ -0,0 +1,169 @@
+use crate::expr::Expr;
+use crate::expr::display::{format_const, Piece};
+use crate::symbol::Symbol;
+
+impl Expr {
+    /// Renders the current expression as a LaTeX string.
+    ///
+    /// Divisions are rendered with `\frac`, exponents are wrapped in braces, and a constant
+    /// multiplied by a symbol is written without an operator (e.g. `2x`). Unlike `Display`,
+    /// parentheses are only emitted where standard operator precedence requires them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let z = Expr::new_var("z");
+    /// assert_eq!(((x + y) / z).to_latex(), "\\frac{x + y}{z}");
+    /// ```
+    pub fn to_latex(&self) -> String {
+        // The pieces still to be written are kept on an explicit stack, the next one last, so
+        // deeply nested expressions are rendered without overflowing the call stack
+        let mut out = String::new();
+        let mut stack = vec![Piece::Node(self)];
+        while let Some(piece) = stack.pop() {
+            let node = match piece {
+                Piece::Text(text) => {
+                    out.push_str(text);
+                    continue;
+                }
+                Piece::Operand(expr, min_prec) => {
+                    if expr.precedence() < min_prec {
+                        out.push_str("\\left(");
+                        stack.push(Piece::Text("\\right)"));
+                    }
+                    expr
+                }
+                Piece::Node(expr) => expr,
+            };
+            match node {
+                Expr::Const(c) => out.push_str(&format_const(*c)),
+                Expr::Rational(n, d) if *d == 1 => out.push_str(&n.to_string()),
+                Expr::Rational(n, d) if *n < 0 => out.push_str(&format!("-\\frac{{{}}}{{{}}}", -n, d)),
+                Expr::Rational(n, d) => out.push_str(&format!("\\frac{{{}}}{{{}}}", n, d)),
+                Expr::Pi => out.push_str("\\pi"),
+                Expr::E => out.push('e'),
+                Expr::Symbol(s) => out.push_str(&symbol_latex(s)),
+                Expr::Add(lhs, rhs) => stack.extend([Piece::Node(lhs), Piece::Text(" + "), Piece::Node(rhs)].into_iter().rev()),
+                Expr::Sub(lhs, rhs) =>
+                    stack.extend([Piece::Node(lhs), Piece::Text(" - "), Piece::Operand(rhs, 2)].into_iter().rev()),
+                Expr::Mul(lhs, rhs) => match (&**lhs, &**rhs) {
+                    (Expr::Const(c), Expr::Symbol(s)) | (Expr::Symbol(s), Expr::Const(c)) if *c >= 0.0 =>
+                        out.push_str(&format!("{}{}", format_const(*c), symbol_latex(s))),
+                    _ => stack.extend([Piece::Operand(lhs, 2), Piece::Text(" \\cdot "), Piece::Operand(rhs, 2)].into_iter().rev()),
+                },
+                Expr::Div(lhs, rhs) => {
+                    out.push_str("\\frac{");
+                    stack.extend([Piece::Node(lhs), Piece::Text("}{"), Piece::Node(rhs), Piece::Text("}")].into_iter().rev());
+                }
+                Expr::Pow(lhs, rhs) if **rhs == Expr::Rational(1, 2) => write_call(&mut out, &mut stack, "\\sqrt{", lhs, "}"),
+                Expr::Pow(lhs, rhs) if **rhs == Expr::Rational(1, 3) => write_call(&mut out, &mut stack, "\\sqrt[3]{", lhs, "}"),
+                Expr::Pow(lhs, rhs) =>
+                    stack.extend([Piece::Operand(lhs, 5), Piece::Text("^{"), Piece::Node(rhs), Piece::Text("}")].into_iter().rev()),
+                Expr::Neg(expr) => {
+                    out.push('-');
+                    stack.push(Piece::Operand(expr, 3));
+                }
+                Expr::Sin(expr) => write_call(&mut out, &mut stack, "\\sin\\left(", expr, "\\right)"),
+                Expr::Cos(expr) => write_call(&mut out, &mut stack, "\\cos\\left(", expr, "\\right)"),
+                Expr::Exp(expr) => write_call(&mut out, &mut stack, "e^{", expr, "}"),
+                Expr::Ln(expr) => write_call(&mut out, &mut stack, "\\ln\\left(", expr, "\\right)"),
+                Expr::Abs(expr) => write_call(&mut out, &mut stack, "\\left|", expr, "\\right|"),
+                Expr::Sqrt(expr) => write_call(&mut out, &mut stack, "\\sqrt{", expr, "}"),
+                Expr::Sign(expr) => write_call(&mut out, &mut stack, "\\operatorname{sgn}\\left(", expr, "\\right)"),
+                Expr::Sum { body, index, from, to } => {
+                    out.push_str(&format!("\\sum_{{{}={}}}^{{{}}} ", symbol_latex(index), from, to));
+                    stack.push(Piece::Operand(body, 2));
+                }
+                Expr::Product { body, index, from, to } => {
+                    out.push_str(&format!("\\prod_{{{}={}}}^{{{}}} ", symbol_latex(index), from, to));
+                    stack.push(Piece::Operand(body, 2));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Renders a symbol as LaTeX, writing its index as a subscript.
+fn symbol_latex(s: &Symbol) -> String {
+    match s.index {
+        Some(index) => format!("{}_{{{}}}", s.name, index),
+        None => s.name.clone(),
+    }
+}
+
+/// Writes `open`, leaving `arg` and `close` on `stack` to be written next.
+fn write_call<'a>(out: &mut String, stack: &mut Vec<Piece<'a>>, open: &str, arg: &'a Expr, close: &'static str) {
+    out.push_str(open);
+    stack.extend([Piece::Node(arg), Piece::Text(close)].into_iter().rev());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latex_fraction() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let z = Expr::new_var("z");
+        assert_eq!(((x + y) / z).to_latex(), "\\frac{x + y}{z}");
+    }
+
+    #[test]
+    fn latex_power() {
+        let x = Expr::new_var("x");
+        let n = Expr::new_var("n");
+        assert_eq!(x.clone().pow(n + 1.0).to_latex(), "x^{n + 1}");
+        assert_eq!((x.clone() + 1.0).pow(Expr::new_val(2.0)).to_latex(), "\\left(x + 1\\right)^{2}");
+    }
+
+    #[test]
+    fn latex_rational_roots() {
+        let x = Expr::new_var("x");
+        assert_eq!((x.clone() + 1.0).pow(Expr::new_rational(1, 2)).to_latex(), "\\sqrt{x + 1}");
+        assert_eq!(x.clone().pow(Expr::new_rational(1, 3)).to_latex(), "\\sqrt[3]{x}");
+        assert_eq!(x.pow(Expr::new_rational(2, 3)).to_latex(), "x^{\\frac{2}{3}}");
+    }
+
+    #[test]
+    fn latex_precedence() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        assert_eq!((2.0 * x.clone()).to_latex(), "2x");
+        assert_eq!(((x.clone() + y.clone()) * x.clone()).to_latex(), "\\left(x + y\\right) \\cdot x");
+        assert_eq!((x.clone() - (x.clone() - y.clone())).to_latex(), "x - \\left(x - y\\right)");
+        assert_eq!((-(x.clone() + y.clone())).to_latex(), "-\\left(x + y\\right)");
+        assert_eq!(x.clone().sin().to_latex(), "\\sin\\left(x\\right)");
+        assert_eq!(x.clone().abs().to_latex(), "\\left|x\\right|");
+        assert_eq!((x + 1.0).sqrt().to_latex(), "\\sqrt{x + 1}");
+    }
+
+    #[test]
+    fn latex_rational() {
+        assert_eq!(Expr::new_rational(1, 3).to_latex(), "\\frac{1}{3}");
+        assert_eq!(Expr::new_rational(-1, 3).to_latex(), "-\\frac{1}{3}");
+    }
+
+    #[test]
+    fn latex_indexed_symbols() {
+        let x1 = Expr::Symbol(crate::symbol::Symbol::indexed("x", 1));
+        let x12 = Expr::Symbol(crate::symbol::Symbol::indexed("x", 12));
+        assert_eq!(x1.to_latex(), "x_{1}");
+        assert_eq!((x1 * x12).to_latex(), "x_{1} \\cdot x_{12}");
+    }
+
+    #[test]
+    fn latex_sum() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = crate::symbol::Symbol::indexed("i", 1);
+        let res = (x + Expr::Symbol(index.clone())).summation(&index, 0, 9);
+        assert_eq!(res.to_latex(), "\\sum_{i_{1}=0}^{9} \\left(x + i_{1}\\right)");
+        assert_eq!(i.summation(&crate::symbol::Symbol::new("i"), 1, 2).to_latex(), "\\sum_{i=1}^{2} i");
+    }
+}
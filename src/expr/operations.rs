@@ -1,5 +1,9 @@
+use std::iter;
+use std::mem;
 use std::ops;
+use std::rc::Rc;
 use crate::expr::Expr;
+use crate::symbol::Symbol;
 
 // Takes ownership
 impl Expr {
@@ -25,7 +29,207 @@ impl Expr {
     ///
     /// Note: This function consumes the `Expr` instances that it operates on.
     pub fn pow(self, expr: Expr) -> Expr {
-        Expr::Pow(Box::new(self), Box::new(expr))
+        Expr::Pow(Rc::new(self), Rc::new(expr))
+    }
+
+    /// Takes the sine of an `Expr` instance, creating a new `Expr::Sin` variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let result = x.sin();
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn sin(self) -> Expr {
+        Expr::Sin(Rc::new(self))
+    }
+
+    /// Takes the cosine of an `Expr` instance, creating a new `Expr::Cos` variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let result = x.cos();
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn cos(self) -> Expr {
+        Expr::Cos(Rc::new(self))
+    }
+
+    /// Raises e to the power of an `Expr` instance, creating a new `Expr::Exp` variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let result = x.exp();
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn exp(self) -> Expr {
+        Expr::Exp(Rc::new(self))
+    }
+
+    /// Takes the natural logarithm of an `Expr` instance, creating a new `Expr::Ln` variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let result = x.ln();
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn ln(self) -> Expr {
+        Expr::Ln(Rc::new(self))
+    }
+
+    /// Takes the absolute value of an `Expr` instance, creating a new `Expr::Abs` variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let result = x.abs();
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn abs(self) -> Expr {
+        Expr::Abs(Rc::new(self))
+    }
+
+    /// Takes the sign of an `Expr` instance, creating a new `Expr::Sign` variant.
+    ///
+    /// The sign evaluates to `-1` for negative values, `0` for zero, and `1` for positive values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let result = x.sign();
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn sign(self) -> Expr {
+        Expr::Sign(Rc::new(self))
+    }
+
+    /// Takes the square root of an `Expr` instance, creating a new `Expr::Sqrt` variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let result = x.sqrt();
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn sqrt(self) -> Expr {
+        Expr::Sqrt(Rc::new(self))
+    }
+
+    /// Sums an `Expr` instance over the integers `from` to `to` taken by `index`, creating a
+    /// new `Expr::Sum` variant.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The body of the sum, in terms of `index`.
+    /// * `index` - The symbol running over the range.
+    /// * `from` - The first value of `index`.
+    /// * `to` - The last value of `index`, inclusive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let i = Expr::new_var("i");
+    /// let result = i.pow(Expr::new_val(2.0)).summation(&Symbol::new("i"), 1, 3);
+    /// assert_eq!(result.eval(&HashMap::new()).unwrap(), 14.0);
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn summation(self, index: &Symbol, from: i64, to: i64) -> Expr {
+        Expr::Sum { body: Rc::new(self), index: index.clone(), from, to }
+    }
+
+    /// Multiplies an `Expr` instance over the integers `from` to `to` taken by `index`,
+    /// creating a new `Expr::Product` variant.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The body of the product, in terms of `index`.
+    /// * `index` - The symbol running over the range.
+    /// * `from` - The first value of `index`.
+    /// * `to` - The last value of `index`, inclusive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    /// use std::collections::HashMap;
+    ///
+    /// let i = Expr::new_var("i");
+    /// let result = Expr::product(i, &Symbol::new("i"), 1, 4);
+    /// assert_eq!(result.eval(&HashMap::new()).unwrap(), 24.0);
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instance that it operates on.
+    pub fn product(self, index: &Symbol, from: i64, to: i64) -> Expr {
+        Expr::Product { body: Rc::new(self), index: index.clone(), from, to }
+    }
+
+    /// Divides an `Expr` instance by another, creating a new `Expr::Div` variant, unless the
+    /// denominator is a literal zero.
+    ///
+    /// Unlike the `/` operator, which builds any division, this returns `Err` when `rhs` is
+    /// a zero constant as recognized by `is_zero`, such as `Const(0.0)` or `Rational(0, 1)`.
+    /// A denominator that is only zero for some values of its symbols, or that simplifies to
+    /// zero, is accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The numerator of the division.
+    /// * `rhs` - The denominator of the division.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// assert_eq!(x.clone().checked_div(Expr::new_val(2.0)), Ok(x.clone() / 2.0));
+    /// assert!(x.checked_div(Expr::new_val(0.0)).is_err());
+    /// ```
+    ///
+    /// Note: This function consumes the `Expr` instances that it operates on.
+    pub fn checked_div(self, rhs: Expr) -> Result<Expr, &'static str> {
+        if rhs.is_zero() {
+            Err("division by zero")
+        } else {
+            Ok(Expr::Div(Rc::new(self), Rc::new(rhs)))
+        }
     }
 }
 
@@ -34,7 +238,7 @@ impl ops::Add for Expr {
     type Output = Expr;
 
     fn add(self, rhs: Expr) -> Expr {
-        Expr::Add(Box::new(self), Box::new(rhs))
+        Expr::Add(Rc::new(self), Rc::new(rhs))
     }
 }
 
@@ -42,7 +246,7 @@ impl ops::Add<f64> for Expr {
     type Output = Expr;
 
     fn add(self, rhs: f64) -> Expr {
-        Expr::Add(Box::new(self), Box::new(Expr::new_val(rhs)))
+        Expr::Add(Rc::new(self), Rc::new(Expr::new_val(rhs)))
     }
 }
 
@@ -50,7 +254,7 @@ impl ops::Add<Expr> for f64 {
     type Output = Expr;
 
     fn add(self, rhs: Expr) -> Expr {
-        Expr::Add(Box::new(Expr::new_val(self)), Box::new(rhs))
+        Expr::Add(Rc::new(Expr::new_val(self)), Rc::new(rhs))
     }
 }
 
@@ -59,7 +263,7 @@ impl ops::Sub for Expr {
     type Output = Expr;
 
     fn sub(self, rhs: Expr) -> Expr {
-        Expr::Sub(Box::new(self), Box::new(rhs))
+        Expr::Sub(Rc::new(self), Rc::new(rhs))
     }
 }
 
@@ -67,7 +271,7 @@ impl ops::Sub<f64> for Expr {
     type Output = Expr;
 
     fn sub(self, rhs: f64) -> Expr {
-        Expr::Sub(Box::new(self), Box::new(Expr::new_val(rhs)))
+        Expr::Sub(Rc::new(self), Rc::new(Expr::new_val(rhs)))
     }
 }
 
@@ -75,7 +279,7 @@ impl ops::Sub<Expr> for f64 {
     type Output = Expr;
 
     fn sub(self, rhs: Expr) -> Expr {
-        Expr::Sub(Box::new(Expr::new_val(self)), Box::new(rhs))
+        Expr::Sub(Rc::new(Expr::new_val(self)), Rc::new(rhs))
     }
 }
 
@@ -84,7 +288,7 @@ impl ops::Mul for Expr {
     type Output = Expr;
 
     fn mul(self, rhs: Expr) -> Expr {
-        Expr::Mul(Box::new(self), Box::new(rhs))
+        Expr::Mul(Rc::new(self), Rc::new(rhs))
     }
 }
 
@@ -92,7 +296,7 @@ impl ops::Mul<f64> for Expr {
     type Output = Expr;
 
     fn mul(self, rhs: f64) -> Expr {
-        Expr::Mul(Box::new(self), Box::new(Expr::new_val(rhs)))
+        Expr::Mul(Rc::new(self), Rc::new(Expr::new_val(rhs)))
     }
 }
 
@@ -100,7 +304,7 @@ impl ops::Mul<Expr> for f64 {
     type Output = Expr;
 
     fn mul(self, rhs: Expr) -> Expr {
-        Expr::Mul(Box::new(Expr::new_val(self)), Box::new(rhs))
+        Expr::Mul(Rc::new(Expr::new_val(self)), Rc::new(rhs))
     }
 }
 
@@ -109,7 +313,7 @@ impl ops::Div for Expr {
     type Output = Expr;
 
     fn div(self, rhs: Expr) -> Expr {
-        Expr::Div(Box::new(self), Box::new(rhs))
+        Expr::Div(Rc::new(self), Rc::new(rhs))
     }
 }
 
@@ -117,7 +321,7 @@ impl ops::Div<f64> for Expr {
     type Output = Expr;
 
     fn div(self, rhs: f64) -> Expr {
-        Expr::Div(Box::new(self), Box::new(Expr::new_val(rhs)))
+        Expr::Div(Rc::new(self), Rc::new(Expr::new_val(rhs)))
     }
 }
 
@@ -125,7 +329,7 @@ impl ops::Div<Expr> for f64 {
     type Output = Expr;
 
     fn div(self, rhs: Expr) -> Expr {
-        Expr::Div(Box::new(Expr::new_val(self)), Box::new(rhs))
+        Expr::Div(Rc::new(Expr::new_val(self)), Rc::new(rhs))
     }
 }
 
@@ -134,13 +338,258 @@ impl ops::Neg for Expr {
     type Output = Expr;
 
     fn neg(self) -> Expr {
-        Expr::Neg(Box::new(self))
+        Expr::Neg(Rc::new(self))
+    }
+}
+
+// Reference Overload Operation implementations, which clone each operand once
+impl ops::Add<&Expr> for &Expr {
+    type Output = Expr;
+
+    fn add(self, rhs: &Expr) -> Expr {
+        Expr::Add(Rc::new(self.clone()), Rc::new(rhs.clone()))
+    }
+}
+
+impl ops::Add<f64> for &Expr {
+    type Output = Expr;
+
+    fn add(self, rhs: f64) -> Expr {
+        Expr::Add(Rc::new(self.clone()), Rc::new(Expr::new_val(rhs)))
+    }
+}
+
+impl ops::Add<&Expr> for f64 {
+    type Output = Expr;
+
+    fn add(self, rhs: &Expr) -> Expr {
+        Expr::Add(Rc::new(Expr::new_val(self)), Rc::new(rhs.clone()))
+    }
+}
+
+impl ops::Sub<&Expr> for &Expr {
+    type Output = Expr;
+
+    fn sub(self, rhs: &Expr) -> Expr {
+        Expr::Sub(Rc::new(self.clone()), Rc::new(rhs.clone()))
+    }
+}
+
+impl ops::Sub<f64> for &Expr {
+    type Output = Expr;
+
+    fn sub(self, rhs: f64) -> Expr {
+        Expr::Sub(Rc::new(self.clone()), Rc::new(Expr::new_val(rhs)))
+    }
+}
+
+impl ops::Sub<&Expr> for f64 {
+    type Output = Expr;
+
+    fn sub(self, rhs: &Expr) -> Expr {
+        Expr::Sub(Rc::new(Expr::new_val(self)), Rc::new(rhs.clone()))
+    }
+}
+
+impl ops::Mul<&Expr> for &Expr {
+    type Output = Expr;
+
+    fn mul(self, rhs: &Expr) -> Expr {
+        Expr::Mul(Rc::new(self.clone()), Rc::new(rhs.clone()))
+    }
+}
+
+impl ops::Mul<f64> for &Expr {
+    type Output = Expr;
+
+    fn mul(self, rhs: f64) -> Expr {
+        Expr::Mul(Rc::new(self.clone()), Rc::new(Expr::new_val(rhs)))
+    }
+}
+
+impl ops::Mul<&Expr> for f64 {
+    type Output = Expr;
+
+    fn mul(self, rhs: &Expr) -> Expr {
+        Expr::Mul(Rc::new(Expr::new_val(self)), Rc::new(rhs.clone()))
+    }
+}
+
+impl ops::Div<&Expr> for &Expr {
+    type Output = Expr;
+
+    fn div(self, rhs: &Expr) -> Expr {
+        Expr::Div(Rc::new(self.clone()), Rc::new(rhs.clone()))
+    }
+}
+
+impl ops::Div<f64> for &Expr {
+    type Output = Expr;
+
+    fn div(self, rhs: f64) -> Expr {
+        Expr::Div(Rc::new(self.clone()), Rc::new(Expr::new_val(rhs)))
+    }
+}
+
+impl ops::Div<&Expr> for f64 {
+    type Output = Expr;
+
+    fn div(self, rhs: &Expr) -> Expr {
+        Expr::Div(Rc::new(Expr::new_val(self)), Rc::new(rhs.clone()))
+    }
+}
+
+impl ops::Neg for &Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::Neg(Rc::new(self.clone()))
+    }
+}
+
+// Compound assignment implementations, which move the current value into the new node
+impl ops::AddAssign for Expr {
+    fn add_assign(&mut self, rhs: Expr) {
+        *self = mem::replace(self, Expr::Const(0.0)) + rhs;
+    }
+}
+
+impl ops::AddAssign<f64> for Expr {
+    fn add_assign(&mut self, rhs: f64) {
+        *self = mem::replace(self, Expr::Const(0.0)) + rhs;
+    }
+}
+
+impl ops::SubAssign for Expr {
+    fn sub_assign(&mut self, rhs: Expr) {
+        *self = mem::replace(self, Expr::Const(0.0)) - rhs;
+    }
+}
+
+impl ops::SubAssign<f64> for Expr {
+    fn sub_assign(&mut self, rhs: f64) {
+        *self = mem::replace(self, Expr::Const(0.0)) - rhs;
+    }
+}
+
+impl ops::MulAssign for Expr {
+    fn mul_assign(&mut self, rhs: Expr) {
+        *self = mem::replace(self, Expr::Const(0.0)) * rhs;
+    }
+}
+
+impl ops::MulAssign<f64> for Expr {
+    fn mul_assign(&mut self, rhs: f64) {
+        *self = mem::replace(self, Expr::Const(0.0)) * rhs;
+    }
+}
+
+impl ops::DivAssign for Expr {
+    fn div_assign(&mut self, rhs: Expr) {
+        *self = mem::replace(self, Expr::Const(0.0)) / rhs;
+    }
+}
+
+impl ops::DivAssign<f64> for Expr {
+    fn div_assign(&mut self, rhs: f64) {
+        *self = mem::replace(self, Expr::Const(0.0)) / rhs;
+    }
+}
+
+// Conversion implementations
+impl From<f64> for Expr {
+    fn from(val: f64) -> Expr {
+        Expr::new_val(val)
+    }
+}
+
+impl From<i32> for Expr {
+    fn from(val: i32) -> Expr {
+        Expr::new_val(val as f64)
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(name: &str) -> Expr {
+        Expr::new_var(name)
+    }
+}
+
+// Iterator folding implementations
+impl iter::Sum for Expr {
+    /// Folds the terms into a left-nested chain of additions, or `Const(0.0)` if there are
+    /// none.
+    fn sum<I: Iterator<Item = Expr>>(iter: I) -> Expr {
+        iter.reduce(|acc, term| acc + term).unwrap_or_else(Expr::zero)
+    }
+}
+
+impl<'a> iter::Sum<&'a Expr> for Expr {
+    fn sum<I: Iterator<Item = &'a Expr>>(iter: I) -> Expr {
+        iter.cloned().sum()
+    }
+}
+
+impl iter::Product for Expr {
+    /// Folds the factors into a left-nested chain of multiplications, or `Const(1.0)` if
+    /// there are none.
+    fn product<I: Iterator<Item = Expr>>(iter: I) -> Expr {
+        iter.reduce(|acc, factor| acc * factor).unwrap_or_else(Expr::one)
+    }
+}
+
+impl<'a> iter::Product<&'a Expr> for Expr {
+    fn product<I: Iterator<Item = &'a Expr>>(iter: I) -> Expr {
+        iter.cloned().product()
+    }
+}
+
+// Numeric identity implementations
+#[cfg(feature = "num-traits")]
+impl num_traits::Zero for Expr {
+    fn zero() -> Expr {
+        Expr::zero()
+    }
+
+    fn is_zero(&self) -> bool {
+        Expr::is_zero(self)
+    }
+}
+
+#[cfg(feature = "num-traits")]
+impl num_traits::One for Expr {
+    fn one() -> Expr {
+        Expr::one()
+    }
+
+    fn is_one(&self) -> bool {
+        Expr::is_one(self)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+    use crate::symbol::Symbol;
+
+    #[test]
+    fn checked_div_rejects_zero() {
+        let x = Expr::new_var("x");
+        assert_eq!(x.clone().checked_div(Expr::new_val(0.0)), Err("division by zero"));
+        assert_eq!(x.clone().checked_div(Expr::new_val(-0.0)), Err("division by zero"));
+        assert_eq!(x.clone().checked_div(Expr::new_rational(0, 3)), Err("division by zero"));
+        assert!(Expr::new_val(0.0).checked_div(Expr::new_val(0.0)).is_err());
+    }
+
+    #[test]
+    fn checked_div_allows_non_zero() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        assert_eq!(x.clone().checked_div(y.clone()), Ok(x.clone() / y.clone()));
+        assert_eq!(x.clone().checked_div(Expr::new_val(2.0)), Ok(x.clone() / 2.0));
+        assert_eq!(x.clone().checked_div(y.clone() - y.clone()), Ok(x / (y.clone() - y)));
+    }
 
     #[test]
     fn test_add() {
@@ -169,4 +618,147 @@ mod tests {
         assert_eq!(x.clone() / Expr::new_val(2.0), x.clone() / 2.0);
         assert_eq!(Expr::new_val(2.0) / x.clone(), 2.0 / x.clone());
     }
+
+    #[test]
+    fn test_from() {
+        let two: Expr = 2.0.into();
+        assert_eq!(two, Expr::Const(2.0));
+        assert_eq!(Expr::from(-3), Expr::Const(-3.0));
+        assert_eq!(Expr::from("x"), Expr::new_var("x"));
+        assert!(matches!(Expr::from("x"), Expr::Symbol(_)));
+    }
+
+    #[test]
+    fn test_from_in_generic_argument() {
+        fn square<T: Into<Expr>>(val: T) -> Expr {
+            let expr = val.into();
+            expr.clone() * expr
+        }
+        assert_eq!(square(2), Expr::new_val(2.0) * Expr::new_val(2.0));
+        assert_eq!(square("y"), Expr::new_var("y") * Expr::new_var("y"));
+        assert_eq!(Expr::from("x").pow(3.into()), Expr::new_var("x").pow(Expr::new_val(3.0)));
+    }
+
+    #[test]
+    fn test_assign_ops() {
+        let x = Expr::new_var("x");
+        let mut res = x.clone();
+        res += Expr::new_val(2.0);
+        res -= 1.0;
+        res *= x.clone();
+        res /= 4.0;
+        assert_eq!(res, (x.clone() + 2.0 - 1.0) * x.clone() / 4.0);
+
+        res -= x.clone();
+        res += 3.0;
+        res *= 2.0;
+        res /= x.clone();
+        assert_eq!(res, ((x.clone() + 2.0 - 1.0) * x.clone() / 4.0 - x.clone() + 3.0) * 2.0 / x.clone());
+    }
+
+    #[test]
+    fn test_assign_accumulate() {
+        let x = Expr::new_var("x");
+        let mut sum = Expr::new_val(0.0);
+        let mut product = Expr::new_val(1.0);
+        for i in 1..=3 {
+            sum += Expr::new_val(i as f64) * x.clone();
+            product *= x.clone() + i as f64;
+        }
+        assert_eq!(sum, Expr::new_val(0.0) + Expr::new_val(1.0) * x.clone() + Expr::new_val(2.0) * x.clone() + Expr::new_val(3.0) * x.clone());
+        assert_eq!(product, Expr::new_val(1.0) * (x.clone() + 1.0) * (x.clone() + 2.0) * (x.clone() + 3.0));
+    }
+
+    #[test]
+    fn test_reference_ops() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        assert_eq!(&a + &b, a.clone() + b.clone());
+        assert_eq!(&a - &b, a.clone() - b.clone());
+        assert_eq!(&a * &b, a.clone() * b.clone());
+        assert_eq!(&a / &b, a.clone() / b.clone());
+        assert_eq!(-&a, -a.clone());
+        assert_eq!(&a + 2.0, a.clone() + 2.0);
+        assert_eq!(2.0 / &b, 2.0 / b.clone());
+
+        // The operands are still usable afterwards
+        let res = &(&a * &a) + &b;
+        assert_eq!(res, a.clone() * a + b);
+    }
+
+    #[test]
+    fn test_zero_and_one() {
+        let x = Expr::new_var("x");
+        assert_eq!(Expr::zero(), Expr::Const(0.0));
+        assert_eq!(Expr::one(), Expr::Const(1.0));
+        assert_eq!((Expr::zero() + x.clone()).simplify(), x.clone());
+        assert_eq!((x.clone() * Expr::one()).simplify(), x.clone());
+
+        let terms = [x.clone(), 2.0 * x.clone(), Expr::new_var("y")];
+        let sum = terms.iter().fold(Expr::zero(), |acc, term| acc + term.clone());
+        assert_eq!(sum, Expr::zero() + x.clone() + 2.0 * x.clone() + Expr::new_var("y"));
+        assert_eq!(sum.simplify(), 3.0 * x + Expr::new_var("y"));
+    }
+
+    #[cfg(feature = "num-traits")]
+    #[test]
+    fn test_num_traits_identities() {
+        use num_traits::{One, Zero};
+
+        fn sum_generic<T: Zero + Clone>(items: &[T]) -> T {
+            items.iter().cloned().fold(T::zero(), |acc, item| acc + item)
+        }
+
+        let x = Expr::new_var("x");
+        assert_eq!(sum_generic(&[x.clone(), x.clone()]).simplify(), 2.0 * x.clone());
+        assert!(<Expr as Zero>::zero().is_zero());
+        assert!(<Expr as One>::one().is_one());
+        assert!(!Zero::is_zero(&x));
+    }
+
+    #[test]
+    fn test_sum_and_product() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let terms = vec![x.clone(), 2.0 * y.clone(), x.clone().sin()];
+
+        assert_eq!(terms.iter().sum::<Expr>(), x.clone() + 2.0 * y.clone() + x.clone().sin());
+        assert_eq!(terms.iter().product::<Expr>(), x.clone() * (2.0 * y.clone()) * x.clone().sin());
+        assert_eq!(terms.into_iter().sum::<Expr>().eval(&HashMap::from([(Symbol::new("x"), 0.0), (Symbol::new("y"), 1.5)])).unwrap(), 3.0);
+        assert_eq!(vec![x.clone()].into_iter().sum::<Expr>(), x.clone());
+        assert_eq!((1..=3).map(|i| x.clone() + i as f64).product::<Expr>(), (x.clone() + 1.0) * (x.clone() + 2.0) * (x + 3.0));
+    }
+
+    #[test]
+    fn test_empty_sum_and_product() {
+        assert_eq!(Vec::<Expr>::new().into_iter().sum::<Expr>(), Expr::Const(0.0));
+        assert_eq!(Vec::<Expr>::new().into_iter().product::<Expr>(), Expr::Const(1.0));
+        assert_eq!([].iter().sum::<Expr>(), Expr::zero());
+    }
+
+    #[test]
+    fn summation_evaluates() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let vars = HashMap::from([(Symbol::new("x"), 2.0)]);
+
+        assert_eq!(i.clone().pow(Expr::new_val(2.0)).summation(&index, 1, 3).eval(&HashMap::new()).unwrap(), 14.0);
+        assert_eq!((x.clone() * i.clone()).summation(&index, 1, 4).eval(&vars).unwrap(), 20.0);
+        assert_eq!(i.clone().summation(&index, -2, 2).eval_memo(&vars).unwrap(), 0.0);
+        assert_eq!(x.summation(&index, 3, 2).eval(&vars).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn product_evaluates() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let vars = HashMap::from([(Symbol::new("x"), 2.0)]);
+
+        assert_eq!(Expr::product(i.clone(), &index, 1, 4).eval(&HashMap::new()).unwrap(), 24.0);
+        assert_eq!((x.clone() + i.clone()).product(&index, 0, 2).eval(&vars).unwrap(), 24.0);
+        assert_eq!(i.clone().summation(&index, 1, 2).product(&Symbol::new("j"), 1, 3).eval_memo(&vars).unwrap(), 27.0);
+        assert_eq!(x.product(&index, 1, 0).eval(&vars).unwrap(), 1.0);
+    }
 }
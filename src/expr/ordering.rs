@@ -0,0 +1,500 @@
+//! Ordering and structural equality of expressions.
+//!
+//! Expressions are ordered first by variant, ranked
+//! `Const < Rational < Pi < E < Symbol < Neg < Add < Sub < Mul < Div < Pow < Sin < Cos < Exp`
+//! `< Ln < Abs < Sign < Sqrt < Sum < Product`, and then by their contents: constants by
+//! value, symbols by name, and operations by their children from left to right. Constant values are compared numerically, so `0.0` and
+//! `-0.0` are equal, and `NaN` is treated as equal to itself and greater than every number.
+//! Equality is defined by this ordering, which keeps `PartialEq`, `Eq`, and `Ord` consistent.
+//! `Hash` follows the same equality, so every `NaN` hashes alike and `-0.0` hashes as `0.0`.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use crate::expr::Expr;
+
+impl Expr {
+    /// Rewrites the current expression into a canonical form by sorting the operands of
+    /// commutative operations.
+    ///
+    /// Chains of additions and of multiplications are flattened, their operands sorted into a
+    /// deterministic order (constants first, then symbols alphabetically, then compound terms),
+    /// and rebuilt left to right. Two expressions that differ only in the order or grouping of
+    /// their sums and products canonicalize to the same tree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let lhs = x.clone() + y.clone();
+    /// let rhs = y + x;
+    /// assert_ne!(lhs, rhs);
+    /// assert_eq!(lhs.canonicalize(), rhs.canonicalize());
+    /// ```
+    pub fn canonicalize(&self) -> Expr {
+        // Nodes are canonicalized after their children, or after the operands of the chain
+        // they head, with the frames still to process on an explicit stack so deep trees do
+        // not overflow the call stack
+        enum Frame<'a> {
+            Visit(&'a Expr),
+            Combine(&'a Expr, usize),
+        }
+        let mut frames = vec![Frame::Visit(self)];
+        let mut results: Vec<Expr> = Vec::new();
+        while let Some(frame) = frames.pop() {
+            match frame {
+                Frame::Visit(node) => {
+                    let children = match node {
+                        Expr::Add(_, _) => node.operands(&|expr| match expr {
+                            Expr::Add(lhs, rhs) => Some((lhs, rhs)),
+                            _ => None,
+                        }),
+                        Expr::Mul(_, _) => node.operands(&|expr| match expr {
+                            Expr::Mul(lhs, rhs) => Some((lhs, rhs)),
+                            _ => None,
+                        }),
+                        _ => node.children(),
+                    };
+                    frames.push(Frame::Combine(node, children.len()));
+                    frames.extend(children.into_iter().rev().map(Frame::Visit));
+                }
+                Frame::Combine(node, arity) => {
+                    let args = results.split_off(results.len() - arity);
+                    results.push(match node {
+                        Expr::Add(_, _) => rebuild_sorted(args, |lhs, rhs| Expr::Add(Rc::new(lhs), Rc::new(rhs))),
+                        Expr::Mul(_, _) => rebuild_sorted(args, |lhs, rhs| Expr::Mul(Rc::new(lhs), Rc::new(rhs))),
+                        _ => node.with_children(args),
+                    });
+                }
+            }
+        }
+        results.pop().expect("canonicalizing always produces a result for the root")
+    }
+
+    /// Returns `true` if the current expression and `other` appear to be mathematically equal.
+    ///
+    /// Both sides are first simplified and canonicalized and compared structurally. If that
+    /// is inconclusive, both are evaluated at a fixed set of sample points for their
+    /// variables and compared with a small relative tolerance; points where either side is
+    /// undefined are skipped. This is a heuristic: structurally different expressions can
+    /// only be told apart where they differ at one of the sample points, so `true` does not
+    /// prove equality, and expressions without a single common defined point are reported
+    /// as not equivalent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// assert!((x.clone() + y.clone()).is_equivalent(&(y.clone() + x.clone())));
+    /// assert!(!(x.clone() + y.clone()).is_equivalent(&(x * y)));
+    /// ```
+    pub fn is_equivalent(&self, other: &Expr) -> bool {
+        let normalize = |expr: &Expr| expr.simplify().canonicalize().simplify().canonicalize();
+        if normalize(self) == normalize(other) {
+            return true;
+        }
+
+        const SAMPLES: [f64; 8] = [0.37, -1.21, 2.53, -0.83, 1.77, 3.11, -2.47, 0.59];
+        // Give each variable its own sequence of values so they do not coincide
+        let sample = |i: usize, j: usize| SAMPLES[(i + 3 * j) % SAMPLES.len()] + 0.13 * j as f64;
+        self.agrees_at_samples(other, SAMPLES.len(), sample) == Some(true)
+    }
+
+    /// Returns `true` unless the current expression and `other` evaluate to significantly
+    /// different values at one of `trials` pseudo-random points.
+    ///
+    /// Each free variable of either expression is assigned a value in `[-3, 3)` drawn from a
+    /// fixed-seed generator, so results are reproducible. Trials where either side fails to
+    /// evaluate, for example because of a division by zero, are skipped. This is a cheap
+    /// check rather than a proof: expressions that differ only outside the sampled values,
+    /// or by less than the relative tolerance of `1e-9`, are reported as equal, and so are
+    /// expressions for which every trial was skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let lhs = (x.clone() + 1.0).pow(Expr::new_val(2.0));
+    /// assert!(lhs.probably_equal(&(x.clone() * x.clone() + 2.0 * x.clone() + 1.0), 20));
+    /// assert!(!lhs.probably_equal(&(x.clone() * x + 1.0), 20));
+    /// ```
+    pub fn probably_equal(&self, other: &Expr, trials: usize) -> bool {
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let random = |_: usize, _: usize| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64 * 6.0 - 3.0
+        };
+        self.agrees_at_samples(other, trials, random) != Some(false)
+    }
+
+    /// Evaluates the current expression and `other` at `trials` points, where `sample(i, j)`
+    /// gives the value of the `j`-th variable (in sorted order) in trial `i`.
+    ///
+    /// Returns `Some(false)` on the first point where the values differ beyond a small
+    /// relative tolerance, `Some(true)` if they agree everywhere both are defined, and `None`
+    /// if no point could be compared.
+    fn agrees_at_samples<F: FnMut(usize, usize) -> f64>(&self, other: &Expr, trials: usize, mut sample: F) -> Option<bool> {
+        let mut vars: Vec<_> = self.variables().into_iter().collect();
+        vars.extend(other.variables());
+        vars.sort();
+        vars.dedup();
+        let mut compared = false;
+        for i in 0..trials {
+            let point: HashMap<_, _> = vars
+                .iter()
+                .enumerate()
+                .map(|(j, var)| (var.clone(), sample(i, j)))
+                .collect();
+            if let (Ok(lhs), Ok(rhs)) = (self.eval(&point), other.eval(&point)) {
+                if (lhs - rhs).abs() > 1e-9 * lhs.abs().max(rhs.abs()).max(1.0) {
+                    return Some(false);
+                }
+                compared = true;
+            }
+        }
+        compared.then_some(true)
+    }
+
+    /// Returns the operands of a chain of one binary operation, from left to right.
+    ///
+    /// `split` returns the two operands when an expression is the operation being flattened.
+    /// The chain is walked with an explicit stack, so long chains do not recurse.
+    fn operands<'a, F>(&'a self, split: &F) -> Vec<&'a Expr>
+    where
+        F: Fn(&'a Expr) -> Option<(&'a Expr, &'a Expr)>,
+    {
+        let mut operands = Vec::new();
+        let mut stack = vec![self];
+        while let Some(expr) = stack.pop() {
+            match split(expr) {
+                Some((lhs, rhs)) => stack.extend([rhs, lhs]),
+                None => operands.push(expr),
+            }
+        }
+        operands
+    }
+
+    /// Returns the position of the expression's variant in the canonical ordering.
+    fn rank(&self) -> u8 {
+        match self {
+            Expr::Const(_) => 0,
+            Expr::Rational(_, _) => 1,
+            Expr::Pi => 2,
+            Expr::E => 3,
+            Expr::Symbol(_) => 4,
+            Expr::Neg(_) => 5,
+            Expr::Add(_, _) => 6,
+            Expr::Sub(_, _) => 7,
+            Expr::Mul(_, _) => 8,
+            Expr::Div(_, _) => 9,
+            Expr::Pow(_, _) => 10,
+            Expr::Sin(_) => 11,
+            Expr::Cos(_) => 12,
+            Expr::Exp(_) => 13,
+            Expr::Ln(_) => 14,
+            Expr::Abs(_) => 15,
+            Expr::Sign(_) => 16,
+            Expr::Sqrt(_) => 17,
+            Expr::Sum { .. } => 18,
+            Expr::Product { .. } => 19,
+        }
+    }
+}
+
+impl PartialEq for Expr {
+    fn eq(&self, other: &Expr) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Expr {}
+
+impl PartialOrd for Expr {
+    fn partial_cmp(&self, other: &Expr) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Expr {
+    fn cmp(&self, other: &Expr) -> Ordering {
+        // Nodes are compared in pre-order, with the pairs still to compare on an explicit
+        // stack, the next one last, so deep trees do not overflow the call stack. A subtree
+        // shared through the same `Rc` is equal to itself and is not walked
+        let mut stack = vec![(self, other)];
+        while let Some((lhs, rhs)) = stack.pop() {
+            if std::ptr::eq(lhs, rhs) {
+                continue;
+            }
+            let ord = match (lhs, rhs) {
+                (Expr::Const(c1), Expr::Const(c2)) => cmp_f64(*c1, *c2),
+                (Expr::Rational(n1, d1), Expr::Rational(n2, d2)) =>
+                    (*n1 as i128 * *d2 as i128).cmp(&(*n2 as i128 * *d1 as i128)).then(n1.cmp(n2)),
+                (Expr::Symbol(s1), Expr::Symbol(s2)) => s1.cmp(s2),
+                (Expr::Sum { index: i1, from: f1, to: t1, .. }, Expr::Sum { index: i2, from: f2, to: t2, .. })
+                    | (Expr::Product { index: i1, from: f1, to: t1, .. }, Expr::Product { index: i2, from: f2, to: t2, .. }) =>
+                    i1.cmp(i2).then(f1.cmp(f2)).then(t1.cmp(t2)),
+                _ => lhs.rank().cmp(&rhs.rank()),
+            };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+            // Equal ranks mean the same variant, and so the same number of children
+            stack.extend(lhs.children().into_iter().zip(rhs.children()).rev());
+        }
+        Ordering::Equal
+    }
+}
+
+impl Hash for Expr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Every variant has a fixed number of children, so the pre-order sequence of nodes
+        // identifies the tree without recursing into it.
+        for node in self.iter() {
+            state.write_u8(node.rank());
+            match node {
+                Expr::Const(c) => hash_f64(*c, state),
+                // Rationals compare equal when their numerators match and their cross
+                // products agree, which for a zero numerator ignores the denominator.
+                Expr::Rational(0, _) => 0i64.hash(state),
+                Expr::Rational(n, d) => (n, d).hash(state),
+                Expr::Symbol(s) => s.hash(state),
+                Expr::Sum { index, from, to, .. } | Expr::Product { index, from, to, .. } =>
+                    (index, from, to).hash(state),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Hashes a float consistently with `cmp_f64`.
+fn hash_f64<H: Hasher>(val: f64, state: &mut H) {
+    let bits = if val.is_nan() {
+        f64::NAN.to_bits()
+    } else if val == 0.0 {
+        0.0f64.to_bits()
+    } else {
+        val.to_bits()
+    };
+    state.write_u64(bits);
+}
+
+/// Compares two floats numerically, treating `NaN` as equal to itself and greater than
+/// every other value.
+fn cmp_f64(lhs: f64, rhs: f64) -> Ordering {
+    lhs.partial_cmp(&rhs).unwrap_or_else(|| lhs.is_nan().cmp(&rhs.is_nan()))
+}
+
+/// Sorts `operands` canonically and folds them back together from left to right with `op`.
+fn rebuild_sorted<F: Fn(Expr, Expr) -> Expr>(mut operands: Vec<Expr>, op: F) -> Expr {
+    operands.sort();
+    let mut operands = operands.into_iter();
+    let first = operands.next().expect("a chain always has at least two operands");
+    operands.fold(first, op)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commuted_sum() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((x.clone() + y.clone()).canonicalize(), (y.clone() + x.clone()).canonicalize());
+        assert_eq!((y.clone() + x.clone()).canonicalize(), x + y);
+    }
+
+    #[test]
+    fn commuted_product_constant_first() {
+        let x = Expr::new_var("x");
+
+        assert_eq!((x.clone() * 2.0).canonicalize(), 2.0 * x.clone());
+        assert_eq!((2.0 * x.clone()).canonicalize(), 2.0 * x);
+    }
+
+    #[test]
+    fn regrouped_chain() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let z = Expr::new_var("z");
+        let lhs = (z.clone() + x.clone()) + y.clone();
+        let rhs = x.clone() + (y.clone() + z.clone());
+
+        assert_eq!(lhs.canonicalize(), rhs.canonicalize());
+        assert_eq!(lhs.canonicalize(), x + y + z);
+    }
+
+    #[test]
+    fn nested_commutative_terms() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let lhs = (y.clone() * x.clone()).sin() + 3.0 * y.clone().pow(Expr::new_val(2.0));
+        let rhs = y.clone().pow(Expr::new_val(2.0)) * 3.0 + (x.clone() * y.clone()).sin();
+
+        assert_eq!(lhs.canonicalize(), rhs.canonicalize());
+    }
+
+    #[test]
+    fn variant_ranking() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let ranked = vec![
+            Expr::new_val(1.0),
+            x.clone(),
+            -x.clone(),
+            x.clone() + y.clone(),
+            x.clone() - y.clone(),
+            x.clone() * y.clone(),
+            x.clone() / y.clone(),
+            x.clone().pow(y.clone()),
+        ];
+
+        let mut sorted = ranked.clone();
+        sorted.reverse();
+        sorted.sort();
+        assert_eq!(sorted, ranked);
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let mut exprs = vec![b.clone() + 1.0, Expr::new_val(3.0), a.clone(), Expr::new_val(-1.0), a.clone() + 1.0, b.clone()];
+        exprs.sort();
+
+        assert_eq!(exprs, vec![Expr::new_val(-1.0), Expr::new_val(3.0), a.clone(), b.clone(), a.clone() + 1.0, b.clone() + 1.0]);
+        let mut again = exprs.clone();
+        again.sort();
+        assert_eq!(again, exprs);
+    }
+
+    #[test]
+    fn equal_expressions_compare_equal() {
+        let lhs = (Expr::new_var("x") + 2.0).pow(Expr::new_var("y"));
+        let rhs = (Expr::new_var("x") + 2.0).pow(Expr::new_var("y"));
+
+        assert_eq!(lhs.cmp(&rhs), Ordering::Equal);
+        assert_eq!(lhs, rhs);
+        assert_eq!(Expr::new_val(0.0), Expr::new_val(-0.0));
+        assert_eq!(Expr::new_val(f64::NAN), Expr::new_val(f64::NAN));
+        assert!(Expr::new_val(f64::NAN) > Expr::new_val(f64::INFINITY));
+    }
+
+    #[test]
+    fn hash_set_collapses_duplicates() {
+        use std::collections::HashSet;
+
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let set: HashSet<Expr> = vec![
+            x.clone() + y.clone(),
+            Expr::new_var("x") + Expr::new_var("y"),
+            y.clone() + x.clone(),
+            Expr::new_val(0.0),
+            Expr::new_val(-0.0),
+            Expr::new_val(f64::NAN),
+            Expr::new_val(-f64::NAN),
+            Expr::Rational(0, 1),
+            Expr::Rational(0, 3),
+            Expr::new_rational(2, 4),
+            Expr::new_rational(1, 2),
+            x.clone().sin(),
+            x.clone().cos(),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(set.len(), 8);
+        assert!(set.contains(&(x.clone() + y.clone())));
+        assert!(set.contains(&Expr::new_val(-0.0)));
+        assert!(!set.contains(&(x * y)));
+    }
+
+    #[test]
+    fn equivalent_commuted_and_collected() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert!((x.clone() + y.clone()).is_equivalent(&(y.clone() + x.clone())));
+        assert!((x.clone() * y.clone() * 3.0).is_equivalent(&(3.0 * y.clone() * x.clone())));
+        assert!((2.0 * x.clone()).is_equivalent(&(x.clone() + x.clone())));
+    }
+
+    #[test]
+    fn equivalent_by_sampling() {
+        let x = Expr::new_var("x");
+
+        let lhs = (x.clone() + 1.0) * (x.clone() - 1.0);
+        let rhs = x.clone().pow(Expr::new_val(2.0)) - 1.0;
+        assert!(lhs.is_equivalent(&rhs));
+        assert!((x.clone() / x.clone()).is_equivalent(&Expr::new_val(1.0)));
+    }
+
+    #[test]
+    fn not_equivalent() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert!(!(x.clone() + 1.0).is_equivalent(&(x.clone() + 2.0)));
+        assert!(!(x.clone() * x.clone()).is_equivalent(&(x.clone() * x.clone().abs())));
+        assert!(!x.clone().is_equivalent(&y));
+        assert!(!(x.clone() - 5.0).ln().is_equivalent(&(5.0 - x.clone()).ln()));
+    }
+
+    #[test]
+    fn probably_equal_square() {
+        let x = Expr::new_var("x");
+        let lhs = (x.clone() + 1.0).pow(Expr::new_val(2.0));
+
+        assert!(lhs.probably_equal(&(x.clone().pow(Expr::new_val(2.0)) + 2.0 * x.clone() + 1.0), 10));
+        assert!(!lhs.probably_equal(&(x.clone().pow(Expr::new_val(2.0)) + 1.0), 10));
+    }
+
+    #[test]
+    fn probably_equal_skips_errors() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert!((x.clone() * y.clone() / y.clone()).probably_equal(&x, 10));
+        assert!(!(x.clone() * y.clone()).probably_equal(&(x.clone() + y.clone()), 10));
+        // Every trial is skipped, so nothing contradicts equality
+        assert!((x.clone() - 5.0).ln().probably_equal(&x, 10));
+        assert!(x.probably_equal(&y, 0));
+    }
+
+    #[test]
+    fn non_commutative_untouched() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((y.clone() - x.clone()).canonicalize(), y.clone() - x.clone());
+        assert_ne!((y.clone() / x.clone()).canonicalize(), (x / y).canonicalize());
+    }
+
+    #[test]
+    fn compare_deep_chain() {
+        let mut lhs = Expr::new_var("x");
+        let mut rhs = Expr::new_var("y");
+        for _ in 0..100_000 {
+            lhs = lhs.sin();
+            rhs = rhs.sin();
+        }
+
+        assert_eq!(lhs, lhs.clone());
+        assert_ne!(lhs, rhs);
+        assert_eq!(lhs.cmp(&rhs), Ordering::Less);
+    }
+}
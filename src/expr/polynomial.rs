@@ -0,0 +1,866 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::rc::Rc;
+use crate::expr::Expr;
+use crate::expr::simplify::{is_negative_number, negate_number};
+use crate::symbol::Symbol;
+
+/// Relative tolerance below which a polynomial coefficient is treated as zero during division.
+const EPSILON: f64 = 1e-9;
+
+/// The highest degree `poly_coeffs` will expand to; anything larger is rejected rather than
+/// spending time and memory on coefficients that cannot be represented usefully.
+const MAX_DEGREE: usize = 10_000;
+
+impl Expr {
+    /// Computes the greatest common divisor of two expressions treated as univariate
+    /// polynomials in `var`.
+    ///
+    /// Runs the Euclidean algorithm on the coefficients of both polynomials and returns the
+    /// monic gcd (leading coefficient one), so the result is only defined up to a constant
+    /// factor. Returns `None` if either expression is not a polynomial in `var` with numeric
+    /// coefficients.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let lhs = x.clone().pow(Expr::new_val(2.0)) - 1.0;
+    /// let rhs = x.clone() - 1.0;
+    /// assert_eq!(lhs.poly_gcd(&rhs, &Symbol::new("x")), Some(x - 1.0));
+    /// ```
+    pub fn poly_gcd(&self, other: &Expr, var: &Symbol) -> Option<Expr> {
+        let mut a = self.poly_coeffs(var)?;
+        let mut b = other.poly_coeffs(var)?;
+        while !is_zero_poly(&b) {
+            let r = poly_rem(&a, &b);
+            a = b;
+            b = r;
+        }
+        let lead = *a.last().expect("coefficient vectors are never empty");
+        if lead == 0.0 {
+            return Some(Expr::new_val(0.0));
+        }
+        let monic: Vec<f64> = a.iter().map(|c| c / lead).collect();
+        Some(Expr::from_poly_coeffs(&monic, var))
+    }
+
+    /// Finds the real roots of the current expression as a polynomial in `var`.
+    ///
+    /// Linear and quadratic polynomials are solved in closed form, the latter through the
+    /// discriminant. The roots are returned in ascending order, with a repeated root listed
+    /// once. A quadratic without real roots and a non-zero constant both give an empty vector.
+    /// Returns `None` for polynomials of degree three or higher, for the zero polynomial, and
+    /// for expressions that are not polynomials in `var`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = x.clone().pow(Expr::new_val(2.0)) - 5.0 * x + 6.0;
+    /// assert_eq!(res.roots(&Symbol::new("x")), Some(vec![2.0, 3.0]));
+    /// ```
+    pub fn roots(&self, var: &Symbol) -> Option<Vec<f64>> {
+        match *self.poly_coeffs(var)?.as_slice() {
+            [0.0] => None,
+            [_] => Some(Vec::new()),
+            [c, b] => Some(vec![-c / b]),
+            [c, b, a] => {
+                let disc = b * b - 4.0 * a * c;
+                if disc < 0.0 {
+                    return Some(Vec::new());
+                }
+                if disc == 0.0 {
+                    return Some(vec![-b / (2.0 * a)]);
+                }
+                let sqrt = disc.sqrt();
+                let mut res = vec![(-b - sqrt) / (2.0 * a), (-b + sqrt) / (2.0 * a)];
+                res.sort_by(f64::total_cmp);
+                Some(res)
+            }
+            _ => None,
+        }
+    }
+
+    /// Factors the current expression as a quadratic polynomial in `var` with rational roots.
+    ///
+    /// The polynomial `a*x^2 + b*x + c` must have whole number coefficients. If its
+    /// discriminant is a perfect square, it returns `a*(x - r1)*(x - r2)` with `r1 < r2`, or
+    /// `a*(x - r)^2` for a repeated root, where the factor `a` is left out when it is `1`.
+    /// Integer roots are constants and the others exact rationals, a factor `x - r` with a
+    /// negative root is written `x + |r|`, and a zero root gives the factor `x` alone.
+    /// Returns `None` if the expression is not a quadratic in `var` with whole number
+    /// coefficients, or if its roots are irrational or complex.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = x.clone().pow(Expr::new_val(2.0)) - 5.0 * x.clone() + 6.0;
+    /// assert_eq!(res.factor_quadratic(&Symbol::new("x")), Some((x.clone() - 2.0) * (x - 3.0)));
+    /// ```
+    pub fn factor_quadratic(&self, var: &Symbol) -> Option<Expr> {
+        let (c, b, a) = match *self.poly_coeffs(var)?.as_slice() {
+            [c, b, a] => (whole(c)?, whole(b)?, whole(a)?),
+            _ => return None,
+        };
+        let disc = b as i128 * b as i128 - 4 * a as i128 * c as i128;
+        if disc < 0 {
+            return None;
+        }
+        let sqrt = disc.isqrt();
+        if sqrt * sqrt != disc {
+            return None;
+        }
+
+        // The numerators fit, since the coefficients and so the square root are below 2^53
+        let sqrt = sqrt as i64;
+        let (mut low, mut high) = ((-b - sqrt, 2 * a), (-b + sqrt, 2 * a));
+        if a < 0 {
+            (low, high) = (high, low);
+        }
+        let x = Rc::new(Expr::Symbol(var.clone()));
+        let factor = |(num, den): (i64, i64)| {
+            let root = match Expr::new_rational(num, den) {
+                Expr::Rational(n, 1) => Expr::new_val(n as f64),
+                root => root,
+            };
+            if root.is_zero() {
+                (*x).clone()
+            } else if is_negative_number(&root) {
+                Expr::Add(x.clone(), Rc::new(negate_number(&root)))
+            } else {
+                Expr::Sub(x.clone(), Rc::new(root))
+            }
+        };
+        let scale = |expr: Expr| if a == 1 { expr } else { Expr::Mul(Rc::new(Expr::new_val(a as f64)), Rc::new(expr)) };
+        Some(if sqrt == 0 {
+            scale(Expr::Pow(Rc::new(factor(low)), Rc::new(Expr::new_val(2.0))))
+        } else {
+            Expr::Mul(Rc::new(scale(factor(low))), Rc::new(factor(high)))
+        })
+    }
+
+    /// Finds the candidate singular points of the current expression in `var`, the real
+    /// values at which it is undefined because a denominator vanishes.
+    ///
+    /// The denominators are those of every division in the tree, and the bases of powers with
+    /// a negative numeric exponent. Their roots are found with `roots`, so denominators that
+    /// are not polynomials in `var` of degree at most two, or that contain other symbols, are
+    /// skipped. Other sources of undefined points, such as the logarithm of a non-positive
+    /// number, are not reported. A point may be a removable singularity, as `x / x` has one
+    /// at `0`. The points are returned in ascending order, without repeats.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = (x.clone() + 1.0) / (x - 2.0);
+    /// assert_eq!(res.singularities(&Symbol::new("x")), vec![2.0]);
+    /// ```
+    pub fn singularities(&self, var: &Symbol) -> Vec<f64> {
+        let mut points: Vec<f64> = self
+            .iter()
+            .filter_map(|node| match node {
+                Expr::Div(_, den) => Some(den),
+                Expr::Pow(base, exp) if is_negative_number(exp) => Some(base),
+                _ => None,
+            })
+            .filter_map(|den| den.roots(var))
+            .flatten()
+            .collect();
+        points.sort_by(f64::total_cmp);
+        points.dedup();
+        points
+    }
+
+    /// Solves the equation `self = 0` for `var`, provided `self` is linear in `var`.
+    ///
+    /// The solution is returned as an `Expr` so it can be substituted back into other
+    /// expressions. Returns `None` if `var` does not appear, appears non-linearly, or the
+    /// expression is not a polynomial in `var` with numeric coefficients.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = 2.0 * x + 4.0;
+    /// assert_eq!(res.solve_linear(&Symbol::new("x")), Some(Expr::new_val(-2.0)));
+    /// ```
+    pub fn solve_linear(&self, var: &Symbol) -> Option<Expr> {
+        match *self.poly_coeffs(var)?.as_slice() {
+            [c, b] => Some(Expr::new_val(-c / b)),
+            _ => None,
+        }
+    }
+
+    /// Evaluates the current expression at `var = x` using Horner's method, provided it is a
+    /// univariate polynomial in `var`.
+    ///
+    /// The coefficients are extracted with `poly_coeffs` and combined by nested
+    /// multiplication, which needs one multiplication and one addition per degree and
+    /// accumulates less rounding error than evaluating the tree. Unlike `eval`, the result is
+    /// not rounded. Returns `None` if the expression is not a polynomial in `var` with numeric
+    /// coefficients.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = 2.0 * x.clone().pow(Expr::new_val(2.0)) + 3.0 * x + 1.0;
+    /// assert_eq!(res.eval_horner(&Symbol::new("x"), 2.0), Some(15.0));
+    /// ```
+    pub fn eval_horner(&self, var: &Symbol, x: f64) -> Option<f64> {
+        let coeffs = self.poly_coeffs(var)?;
+        Some(coeffs.iter().rev().fold(0.0, |acc, c| acc * x + c))
+    }
+
+    /// Returns the degree of the current expression as a polynomial in `var`, or `None` if it
+    /// is not a polynomial in `var` with numeric coefficients.
+    ///
+    /// The expression does not need to be expanded first. The degree is that of the
+    /// polynomial the expression equals, so terms that cancel do not count: `x^3 - x^3 + x`
+    /// has degree 1. Constants, including zero, have degree 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = x.clone().pow(Expr::new_val(3.0)) + x;
+    /// assert_eq!(res.degree(&Symbol::new("x")), Some(3));
+    /// ```
+    pub fn degree(&self, var: &Symbol) -> Option<i64> {
+        Some(self.poly_coeffs(var)?.len() as i64 - 1)
+    }
+
+    /// Checks whether the current expression is a polynomial in `var`.
+    ///
+    /// The expression is a polynomial if `var` only appears in sums, differences, products
+    /// and negations, in the numerator of a division whose denominator does not contain
+    /// `var`, and in the base of a power with a non-negative whole number exponent. Any
+    /// subexpression without `var`, such as another symbol or `sin(y)`, counts as a
+    /// coefficient. The expression does not need to be expanded first, but the check is
+    /// structural, so `x^2 / x` is not a polynomial.
+    ///
+    /// Unlike `poly_coeffs` and `degree`, this accepts symbolic coefficients, so it returns
+    /// `true` for `x * y` while those return `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let var = Symbol::new("x");
+    /// assert!((x.clone().pow(Expr::new_val(2.0)) * y.clone() + 1.0).is_polynomial(&var));
+    /// assert!(!(y / x.clone()).is_polynomial(&var));
+    /// assert!(!x.sin().is_polynomial(&var));
+    /// ```
+    pub fn is_polynomial(&self, var: &Symbol) -> bool {
+        // Each node folds to whether it is a polynomial in `var` and whether it contains `var`
+        let res: Result<(bool, bool), Infallible> = self.try_fold_up(|node, children| {
+            let contains = matches!(node, Expr::Symbol(s) if s == var) || children.iter().any(|(_, c)| *c);
+            let polynomial = !contains || match node {
+                Expr::Symbol(_) => true,
+                Expr::Add(_, _) | Expr::Sub(_, _) | Expr::Mul(_, _) | Expr::Neg(_) => children.iter().all(|(p, _)| *p),
+                Expr::Div(_, _) => children[0].0 && !children[1].1,
+                Expr::Pow(_, exp) => children[0].0 && !children[1].1 && match **exp {
+                    Expr::Const(n) => n >= 0.0 && n.fract() == 0.0,
+                    Expr::Rational(n, d) => n >= 0 && d == 1,
+                    _ => false,
+                },
+                _ => false,
+            };
+            Ok((polynomial, contains))
+        });
+        match res {
+            Ok((polynomial, _)) => polynomial,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Builds a polynomial expression in `var` from coefficients indexed by degree, so index
+    /// 0 holds the constant term.
+    ///
+    /// The highest degree term is written first, zero coefficients are omitted, and negative
+    /// coefficients after the leading term are written as subtractions. An empty or all-zero
+    /// slice gives `Const(0.0)`. This is the inverse of `poly_coeffs`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = Expr::from_poly_coeffs(&[1.0, 0.0, -3.0], &Symbol::new("x"));
+    /// assert_eq!(res, -3.0 * x.pow(Expr::new_val(2.0)) + 1.0);
+    /// ```
+    pub fn from_poly_coeffs(coeffs: &[f64], var: &Symbol) -> Expr {
+        let mut acc: Option<Expr> = None;
+        for (degree, coeff) in coeffs.iter().enumerate().rev().filter(|(_, c)| **c != 0.0) {
+            let power = match degree {
+                0 => None,
+                1 => Some(Expr::Symbol(var.clone())),
+                _ => Some(Expr::Pow(Rc::new(Expr::Symbol(var.clone())), Rc::new(Expr::new_val(degree as f64)))),
+            };
+            let term = |c: f64| match &power {
+                None => Expr::new_val(c),
+                Some(power) if c == 1.0 => power.clone(),
+                Some(power) => Expr::Mul(Rc::new(Expr::new_val(c)), Rc::new(power.clone())),
+            };
+            acc = Some(match acc {
+                None => term(*coeff),
+                Some(acc) if *coeff < 0.0 => Expr::Sub(Rc::new(acc), Rc::new(term(-coeff))),
+                Some(acc) => Expr::Add(Rc::new(acc), Rc::new(term(*coeff))),
+            });
+        }
+        acc.unwrap_or(Expr::new_val(0.0))
+    }
+
+    /// Returns the coefficients of the current expression as a univariate polynomial in `var`.
+    ///
+    /// The coefficients are indexed by degree, so index 0 holds the constant term. The vector
+    /// has no trailing zeros, except that the zero polynomial is `[0.0]`. Returns `None` if the
+    /// expression is not a polynomial in `var` with numeric coefficients, for example when
+    /// `var` appears in a denominator, under a function, or with a negative or non-integer
+    /// exponent, or when another symbol appears. Polynomials of degree above 10000 are also
+    /// rejected with `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = 2.0 * x.clone().pow(Expr::new_val(2.0)) + 3.0 * x + 1.0;
+    /// assert_eq!(res.poly_coeffs(&Symbol::new("x")), Some(vec![1.0, 3.0, 2.0]));
+    /// ```
+    pub fn poly_coeffs(&self, var: &Symbol) -> Option<Vec<f64>> {
+        // A subtree without `var` is evaluated as a whole once its parent mentions `var`, so
+        // each maximal constant subtree is evaluated exactly once
+        enum Part {
+            Constant,
+            Poly(Vec<f64>),
+        }
+        let coeffs = |expr: &Expr, part: Part| match part {
+            Part::Poly(coeffs) => Ok(coeffs),
+            Part::Constant => expr.eval(&HashMap::new()).map(|c| vec![c]).map_err(|_| ()),
+        };
+        let res = self.try_fold_up(|node, parts| {
+            if !matches!(node, Expr::Symbol(s) if s == var) && parts.iter().all(|part| matches!(part, Part::Constant)) {
+                return Ok(Part::Constant);
+            }
+            let children = node.children();
+            let mut parts = children.iter().zip(parts).map(|(child, part)| coeffs(child, part));
+            let mut next = || parts.next().expect("each child has a part");
+            let res = match node {
+                Expr::Symbol(_) => vec![0.0, 1.0],
+                Expr::Add(_, _) => poly_add(&next()?, &next()?, 1.0),
+                Expr::Sub(_, _) => poly_add(&next()?, &next()?, -1.0),
+                Expr::Mul(_, _) => {
+                    let (lhs, rhs) = (next()?, next()?);
+                    if lhs.len() + rhs.len() - 2 > MAX_DEGREE {
+                        return Err(());
+                    }
+                    poly_mul(&lhs, &rhs)
+                }
+                Expr::Div(_, _) => {
+                    let lhs = next()?;
+                    match next()?.as_slice() {
+                        [c] if *c != 0.0 => lhs.iter().map(|a| a / c).collect(),
+                        _ => return Err(()),
+                    }
+                }
+                Expr::Pow(base, exp) => {
+                    let n = match **exp {
+                        Expr::Const(n) if (0.0..=MAX_DEGREE as f64).contains(&n) && n.fract() == 0.0 => n as usize,
+                        Expr::Rational(n, 1) if (0..=MAX_DEGREE as i64).contains(&n) => n as usize,
+                        _ => return Err(()),
+                    };
+                    match &**base {
+                        // A power of the variable itself is a single coefficient
+                        Expr::Symbol(s) if s == var => {
+                            let mut res = vec![0.0; n + 1];
+                            res[n] = 1.0;
+                            res
+                        }
+                        _ => poly_pow(&next()?, n).ok_or(())?,
+                    }
+                }
+                Expr::Neg(_) => next()?.iter().map(|c| -c).collect(),
+                _ => return Err(()),
+            };
+            Ok(Part::Poly(trim(res)))
+        });
+        coeffs(self, res.ok()?).ok()
+    }
+}
+
+/// Converts a coefficient to an integer if it is a whole number small enough to be exact.
+fn whole(c: f64) -> Option<i64> {
+    if c.fract() == 0.0 && c.abs() < 2f64.powi(53) {
+        Some(c as i64)
+    } else {
+        None
+    }
+}
+
+/// Removes trailing zero coefficients, keeping at least one coefficient.
+fn trim(mut coeffs: Vec<f64>) -> Vec<f64> {
+    while coeffs.len() > 1 && coeffs.last() == Some(&0.0) {
+        coeffs.pop();
+    }
+    coeffs
+}
+
+/// Returns `true` if every coefficient is zero.
+fn is_zero_poly(coeffs: &[f64]) -> bool {
+    coeffs.iter().all(|c| *c == 0.0)
+}
+
+/// Returns `a + sign * b`.
+fn poly_add(a: &[f64], b: &[f64], sign: f64) -> Vec<f64> {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0.0) + sign * b.get(i).unwrap_or(&0.0))
+        .collect()
+}
+
+/// Returns the product of two polynomials.
+fn poly_mul(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut res = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            res[i + j] += x * y;
+        }
+    }
+    res
+}
+
+/// Returns `base` raised to the power `n` by repeated squaring, or `None` if the result would
+/// have a degree above `MAX_DEGREE`.
+fn poly_pow(base: &[f64], mut n: usize) -> Option<Vec<f64>> {
+    if (base.len() - 1).checked_mul(n)? > MAX_DEGREE {
+        return None;
+    }
+    let mut res = vec![1.0];
+    let mut square = base.to_vec();
+    while n > 0 {
+        if n & 1 == 1 {
+            res = poly_mul(&res, &square);
+        }
+        n >>= 1;
+        if n > 0 {
+            square = poly_mul(&square, &square);
+        }
+    }
+    Some(res)
+}
+
+/// Returns the remainder of dividing `a` by the non-zero polynomial `b`.
+///
+/// Coefficients that are negligible relative to the largest coefficient of `a` are rounded to
+/// zero so that floating point noise does not prevent the Euclidean algorithm from finishing.
+fn poly_rem(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let b = trim(b.to_vec());
+    let scale = a.iter().fold(0.0f64, |m, c| m.max(c.abs())).max(1.0);
+    let mut rem = trim(a.to_vec());
+    let lead = *b.last().expect("coefficient vectors are never empty");
+    while rem.len() >= b.len() && !is_zero_poly(&rem) {
+        let shift = rem.len() - b.len();
+        let factor = rem.last().expect("coefficient vectors are never empty") / lead;
+        for (i, c) in b.iter().enumerate() {
+            rem[i + shift] -= factor * c;
+        }
+        for c in rem.iter_mut() {
+            if c.abs() < EPSILON * scale {
+                *c = 0.0;
+            }
+        }
+        rem.pop();
+        rem = trim(rem);
+        if rem.is_empty() {
+            rem.push(0.0);
+        }
+    }
+    rem
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coeffs_quadratic() {
+        let x = Expr::new_var("x");
+        let res = 2.0 * x.clone().pow(Expr::new_val(2.0)) + 3.0 * x.clone() + 1.0;
+
+        assert_eq!(res.poly_coeffs(&Symbol::new("x")), Some(vec![1.0, 3.0, 2.0]));
+    }
+
+    #[test]
+    fn coeffs_unexpanded() {
+        let x = Expr::new_var("x");
+        let res = (x.clone() + 1.0) * (x.clone() - 1.0) / 2.0;
+
+        assert_eq!(res.poly_coeffs(&Symbol::new("x")), Some(vec![-0.5, 0.0, 0.5]));
+        assert_eq!((x.clone() - x.clone()).poly_coeffs(&Symbol::new("x")), Some(vec![0.0]));
+        assert_eq!(Expr::new_val(4.0).poly_coeffs(&Symbol::new("x")), Some(vec![4.0]));
+    }
+
+    #[test]
+    fn coeffs_non_polynomial() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!((1.0 / x.clone()).poly_coeffs(&var), None);
+        assert_eq!(x.clone().pow(Expr::new_val(0.5)).poly_coeffs(&var), None);
+        assert_eq!(x.clone().pow(Expr::new_val(-1.0)).poly_coeffs(&var), None);
+        assert_eq!(x.clone().exp().poly_coeffs(&var), None);
+    }
+
+    #[test]
+    fn coeffs_large_powers() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!(x.clone().pow(Expr::new_val(10_000.0)).degree(&var), Some(10_000));
+        assert_eq!((x.clone() + 1.0).pow(Expr::new_val(5.0)).poly_coeffs(&var), Some(vec![1.0, 5.0, 10.0, 10.0, 5.0, 1.0]));
+        assert_eq!((x.clone() + 1.0).pow(Expr::new_val(1000.0)).degree(&var), Some(1000));
+
+        // Degrees beyond the limit are rejected quickly instead of expanded
+        assert_eq!(x.clone().pow(Expr::new_val(1e9)).degree(&var), None);
+        assert_eq!(x.clone().pow(Expr::new_val(1e300)).degree(&var), None);
+        assert_eq!((x.clone() + 1.0).pow(Expr::new_val(6000.0)).pow(Expr::new_val(2.0)).degree(&var), None);
+        let res = x.clone().pow(Expr::new_val(6000.0)) * x.clone().pow(Expr::new_val(6000.0));
+        assert_eq!(res.degree(&var), None);
+    }
+
+    #[test]
+    fn roots_linear() {
+        let x = Expr::new_var("x");
+
+        assert_eq!((2.0 * x.clone() - 3.0).roots(&Symbol::new("x")), Some(vec![1.5]));
+    }
+
+    #[test]
+    fn roots_quadratic() {
+        let x = Expr::new_var("x");
+        let res = -(x.clone().pow(Expr::new_val(2.0))) + x.clone() + 6.0;
+
+        assert_eq!(res.roots(&Symbol::new("x")), Some(vec![-2.0, 3.0]));
+    }
+
+    #[test]
+    fn roots_repeated() {
+        let x = Expr::new_var("x");
+        let res = (x.clone() - 2.0).pow(Expr::new_val(2.0));
+
+        assert_eq!(res.roots(&Symbol::new("x")), Some(vec![2.0]));
+    }
+
+    #[test]
+    fn roots_none_real() {
+        let x = Expr::new_var("x");
+        let res = x.clone().pow(Expr::new_val(2.0)) + 1.0;
+
+        assert_eq!(res.roots(&Symbol::new("x")), Some(vec![]));
+        assert_eq!(Expr::new_val(3.0).roots(&Symbol::new("x")), Some(vec![]));
+    }
+
+    #[test]
+    fn roots_unsupported() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!(x.clone().pow(Expr::new_val(3.0)).roots(&var), None);
+        assert_eq!(x.clone().sin().roots(&var), None);
+        assert_eq!(Expr::new_val(0.0).roots(&var), None);
+    }
+
+    #[test]
+    fn factor_quadratic_integer_roots() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+        let sq = |e: Expr| e.pow(Expr::new_val(2.0));
+
+        let res = sq(x.clone()) - 5.0 * x.clone() + 6.0;
+        assert_eq!(res.factor_quadratic(&var), Some((x.clone() - 2.0) * (x.clone() - 3.0)));
+        assert_eq!(res.factor_quadratic(&var).unwrap().to_string(), "(x - 2) * (x - 3)");
+
+        let res = 2.0 * sq(x.clone()) + 2.0 * x.clone() - 4.0;
+        assert_eq!(res.factor_quadratic(&var), Some(2.0 * (x.clone() + 2.0) * (x.clone() - 1.0)));
+        let res = sq(x.clone()) - 3.0 * x.clone();
+        assert_eq!(res.factor_quadratic(&var), Some(x.clone() * (x.clone() - 3.0)));
+        let res = -sq(x.clone()) + 1.0;
+        assert_eq!(res.factor_quadratic(&var), Some(-1.0 * (x.clone() + 1.0) * (x.clone() - 1.0)));
+    }
+
+    #[test]
+    fn factor_quadratic_perfect_square_and_rational_roots() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+        let sq = |e: Expr| e.pow(Expr::new_val(2.0));
+
+        let res = sq(x.clone()) + 6.0 * x.clone() + 9.0;
+        assert_eq!(res.factor_quadratic(&var), Some(sq(x.clone() + 3.0)));
+        assert_eq!((3.0 * sq(x.clone())).factor_quadratic(&var), Some(3.0 * sq(x.clone())));
+
+        let res = 2.0 * sq(x.clone()) - 3.0 * x.clone() + 1.0;
+        let expected = 2.0 * (x.clone() - Expr::new_rational(1, 2)) * (x.clone() - 1.0);
+        assert_eq!(res.factor_quadratic(&var), Some(expected));
+        let res = 4.0 * sq(x.clone()) + 4.0 * x.clone() + 1.0;
+        assert_eq!(res.factor_quadratic(&var), Some(4.0 * sq(x.clone() + Expr::new_rational(1, 2))));
+    }
+
+    #[test]
+    fn factor_quadratic_none() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+        let sq = |e: Expr| e.pow(Expr::new_val(2.0));
+
+        assert_eq!((sq(x.clone()) - 2.0).factor_quadratic(&var), None);
+        assert_eq!((sq(x.clone()) + 1.0).factor_quadratic(&var), None);
+        assert_eq!((x.clone() - 1.0).factor_quadratic(&var), None);
+        assert_eq!(x.clone().pow(Expr::new_val(3.0)).factor_quadratic(&var), None);
+        assert_eq!((sq(x.clone()) - 0.25).factor_quadratic(&var), None);
+        assert_eq!((sq(x.clone()) + Expr::new_var("y")).factor_quadratic(&var), None);
+        assert_eq!(x.sin().factor_quadratic(&var), None);
+    }
+
+    #[test]
+    fn solve_linear_simple() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!((2.0 * x.clone() + 4.0).solve_linear(&var), Some(Expr::new_val(-2.0)));
+        assert_eq!((3.0 - x.clone() / 2.0).solve_linear(&var), Some(Expr::new_val(6.0)));
+        // x^2 - x^2 + x cancels to a linear polynomial
+        let res = x.clone().pow(Expr::new_val(2.0)) - x.clone().pow(Expr::new_val(2.0)) + x.clone();
+        assert_eq!(res.solve_linear(&var), Some(Expr::new_val(0.0)));
+    }
+
+    #[test]
+    fn solve_linear_none() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!(x.clone().pow(Expr::new_val(2.0)).solve_linear(&var), None);
+        assert_eq!(Expr::new_val(4.0).solve_linear(&var), None);
+        assert_eq!((x.clone() - x.clone()).solve_linear(&var), None);
+        assert_eq!(x.clone().ln().solve_linear(&var), None);
+    }
+
+    #[test]
+    fn from_coeffs_round_trip() {
+        let var = Symbol::new("x");
+        for coeffs in [vec![1.0, 3.0, 2.0], vec![0.0, -1.0], vec![5.0], vec![-2.0, 0.0, 0.0, 4.5]] {
+            assert_eq!(Expr::from_poly_coeffs(&coeffs, &var).poly_coeffs(&var), Some(coeffs));
+        }
+
+        let x = Expr::new_var("x");
+        let res = 2.0 * x.clone().pow(Expr::new_val(2.0)) - 3.0 * x.clone() + 1.0;
+        assert_eq!(Expr::from_poly_coeffs(&res.poly_coeffs(&var).unwrap(), &var), res);
+    }
+
+    #[test]
+    fn from_coeffs_zeros() {
+        let var = Symbol::new("x");
+        let x = Expr::new_var("x");
+
+        assert_eq!(Expr::from_poly_coeffs(&[], &var), Expr::new_val(0.0));
+        assert_eq!(Expr::from_poly_coeffs(&[0.0, 0.0, 0.0], &var), Expr::new_val(0.0));
+        assert_eq!(Expr::from_poly_coeffs(&[0.0, 0.0, 1.0], &var), x.clone().pow(Expr::new_val(2.0)));
+        assert_eq!(Expr::from_poly_coeffs(&[3.0, 0.0, 1.0], &var), x.pow(Expr::new_val(2.0)) + 3.0);
+    }
+
+    #[test]
+    fn horner_matches_eval() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+        let polys = vec![
+            2.0 * x.clone().pow(Expr::new_val(2.0)) + 3.0 * x.clone() + 1.0,
+            (x.clone() - 1.0) * (x.clone() + 2.0) * (x.clone() - 3.0),
+            x.clone().pow(Expr::new_val(5.0)) - x.clone() / 4.0,
+            -(x.clone() + 1.0).pow(Expr::new_val(3.0)),
+            Expr::new_val(7.0),
+        ];
+        for poly in polys {
+            for val in [-2.0, -0.5, 0.0, 1.0, 3.0] {
+                let mut vars = HashMap::new();
+                vars.insert(var.clone(), val);
+                let expected = poly.eval(&vars).unwrap();
+                let res = poly.eval_horner(&var, val).unwrap();
+                assert!((res - expected).abs() < 1e-12, "{} at {}: {} != {}", poly, val, res, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn horner_non_polynomial() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let var = Symbol::new("x");
+
+        assert_eq!(x.clone().sin().eval_horner(&var, 1.0), None);
+        assert_eq!((x.clone() + y).eval_horner(&var, 1.0), None);
+        assert_eq!((1.0 / x).eval_horner(&var, 1.0), None);
+    }
+
+    #[test]
+    fn degree_polynomials() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!((x.clone().pow(Expr::new_val(3.0)) + x.clone()).degree(&var), Some(3));
+        assert_eq!(x.clone().degree(&var), Some(1));
+        assert_eq!(Expr::new_val(4.0).degree(&var), Some(0));
+        assert_eq!(Expr::new_val(0.0).degree(&var), Some(0));
+        assert_eq!(((x.clone() + 1.0) * (x.clone() - 1.0).pow(Expr::new_val(2.0))).degree(&var), Some(3));
+        let res = x.clone().pow(Expr::new_val(3.0)) - x.clone().pow(Expr::new_val(3.0)) + x.clone();
+        assert_eq!(res.degree(&var), Some(1));
+        let res = x.clone().pow(Expr::new_rational(4, 2)) + 1.0;
+        assert_eq!(res.degree(&var), Some(2));
+        assert_eq!(res.poly_coeffs(&var), Some(vec![1.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn degree_non_polynomial() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!(x.clone().exp().degree(&var), None);
+        assert_eq!(x.clone().pow(Expr::new_val(-2.0)).degree(&var), None);
+        assert_eq!((x.clone() * Expr::new_var("y")).degree(&var), None);
+    }
+
+    #[test]
+    fn is_polynomial_polynomials() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let var = Symbol::new("x");
+
+        assert!((3.0 * x.clone().pow(Expr::new_val(2.0)) - x.clone() + 5.0).is_polynomial(&var));
+        assert!(((x.clone() + 1.0) * (x.clone() - y.clone()).pow(Expr::new_val(3.0))).is_polynomial(&var));
+        assert!((-x.clone() / 2.0).is_polynomial(&var));
+        assert!((x.clone() * y.clone().sin() / y.clone().exp()).is_polynomial(&var));
+        assert!(x.clone().pow(Expr::new_rational(4, 2)).is_polynomial(&var));
+        assert!(x.clone().pow(Expr::new_val(0.0)).is_polynomial(&var));
+        assert!(Expr::new_val(7.0).is_polynomial(&var));
+        assert!(y.clone().ln().is_polynomial(&var));
+    }
+
+    #[test]
+    fn is_polynomial_rational_functions() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert!(!(1.0 / x.clone()).is_polynomial(&var));
+        assert!(!(x.clone() / (x.clone() + 1.0)).is_polynomial(&var));
+        assert!(!x.clone().pow(Expr::new_val(-2.0)).is_polynomial(&var));
+        assert!(!x.clone().pow(Expr::new_val(1.5)).is_polynomial(&var));
+        assert!(!x.clone().pow(Expr::new_rational(1, 2)).is_polynomial(&var));
+        assert!(!x.clone().pow(Expr::new_var("n")).is_polynomial(&var));
+        assert!(!(x.clone().pow(Expr::new_val(2.0)) / x).is_polynomial(&var));
+    }
+
+    #[test]
+    fn is_polynomial_transcendental() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert!(!x.clone().sin().is_polynomial(&var));
+        assert!(!(x.clone() + x.clone().exp()).is_polynomial(&var));
+        assert!(!(2.0 * x.clone().ln()).is_polynomial(&var));
+        assert!(!x.clone().sqrt().is_polynomial(&var));
+        assert!(!x.clone().abs().is_polynomial(&var));
+        assert!(!Expr::new_val(2.0).pow(x.clone()).is_polynomial(&var));
+        assert!(!x.clone().pow(x).is_polynomial(&var));
+    }
+
+    #[test]
+    fn gcd_difference_of_squares() {
+        let x = Expr::new_var("x");
+        let lhs = x.clone().pow(Expr::new_val(2.0)) - 1.0;
+        let rhs = x.clone() - 1.0;
+
+        assert_eq!(lhs.poly_gcd(&rhs, &Symbol::new("x")), Some(x.clone() - 1.0));
+        assert_eq!(rhs.poly_gcd(&lhs, &Symbol::new("x")), Some(x - 1.0));
+    }
+
+    #[test]
+    fn gcd_shared_quadratic() {
+        let x = Expr::new_var("x");
+        // (x - 1)(x - 2)(x + 3) and 2(x - 1)(x - 2)
+        let lhs = (x.clone() - 1.0) * (x.clone() - 2.0) * (x.clone() + 3.0);
+        let rhs = 2.0 * (x.clone() - 1.0) * (x.clone() - 2.0);
+        let expected = x.clone().pow(Expr::new_val(2.0)) - 3.0 * x.clone() + 2.0;
+
+        assert_eq!(lhs.poly_gcd(&rhs, &Symbol::new("x")), Some(expected));
+    }
+
+    #[test]
+    fn gcd_coprime() {
+        let x = Expr::new_var("x");
+        let lhs = x.clone() + 1.0;
+        let rhs = x.clone() - 1.0;
+
+        assert_eq!(lhs.poly_gcd(&rhs, &Symbol::new("x")), Some(Expr::new_val(1.0)));
+    }
+
+    #[test]
+    fn gcd_non_polynomial() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!(x.clone().sin().poly_gcd(&x, &Symbol::new("x")), None);
+        assert_eq!((1.0 / x.clone()).poly_gcd(&x, &Symbol::new("x")), None);
+        assert_eq!((x.clone() * y).poly_gcd(&x, &Symbol::new("x")), None);
+    }
+
+    #[test]
+    fn singularities_of_rational_function() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!((1.0 / (x.clone() - 2.0)).singularities(&var), vec![2.0]);
+        let res = x.clone() / (x.clone().pow(Expr::new_val(2.0)) - 1.0) + 1.0 / (x.clone() + 1.0);
+        assert_eq!(res.singularities(&var), vec![-1.0, 1.0]);
+        assert_eq!((x.clone() + 3.0).pow(Expr::new_val(-2.0)).singularities(&var), vec![-3.0]);
+    }
+
+    #[test]
+    fn singularities_skipped() {
+        let x = Expr::new_var("x");
+        let var = Symbol::new("x");
+
+        assert_eq!((x.clone() * 2.0 + 1.0).singularities(&var), Vec::<f64>::new());
+        assert_eq!((1.0 / (x.clone().pow(Expr::new_val(2.0)) + 1.0)).singularities(&var), Vec::<f64>::new());
+        assert_eq!((1.0 / x.clone().sin()).singularities(&var), Vec::<f64>::new());
+        assert_eq!((1.0 / (x.clone() - Expr::new_var("y"))).singularities(&var), Vec::<f64>::new());
+    }
+}
@@ -0,0 +1,255 @@
+use crate::expr::Expr;
+
+impl Expr {
+    /// Renders the current expression as multi-line text for display in a terminal.
+    ///
+    /// Divisions are stacked with the numerator centered over an ASCII bar and the denominator
+    /// centered below it, and exponents are raised one line above their base. A minus sign in
+    /// front of a stacked fraction is followed by a space. Everything else is written as in
+    /// `Display`. Trailing whitespace is removed from every line, and the lines are joined
+    /// with `\n`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = (x.clone() + 1.0) / (x - 1.0);
+    /// assert_eq!(res.pretty(), "x + 1\n-----\nx - 1");
+    /// ```
+    pub fn pretty(&self) -> String {
+        let block = self.to_block();
+        let lines: Vec<&str> = block.lines.iter().map(|line| line.trim_end()).collect();
+        lines.join("\n")
+    }
+
+    /// Lays out the current expression as a block of text.
+    fn to_block(&self) -> Block {
+        match self {
+            Expr::Rational(n, d) if *d != 1 => {
+                let fraction = Block::fraction(Block::text(&n.abs().to_string()), Block::text(&d.to_string()));
+                if *n < 0 { negate(fraction) } else { fraction }
+            }
+            Expr::Const(_) | Expr::Rational(_, _) | Expr::Pi | Expr::E | Expr::Symbol(_) =>
+                Block::text(&self.to_string()),
+            Expr::Add(lhs, rhs) => match (&**lhs, &**rhs) {
+                (_, Expr::Neg(rhs)) => Block::infix(wrap(lhs, 1), " - ", wrap(rhs, 2)),
+                (Expr::Neg(lhs), _) => Block::infix(wrap(rhs, 1), " - ", wrap(lhs, 2)),
+                _ => Block::infix(wrap(lhs, 1), " + ", wrap(rhs, 1)),
+            },
+            Expr::Sub(lhs, rhs) => match &**rhs {
+                Expr::Neg(rhs) => Block::infix(wrap(lhs, 1), " + ", wrap(rhs, 1)),
+                _ => Block::infix(wrap(lhs, 1), " - ", wrap(rhs, 2)),
+            },
+            Expr::Mul(lhs, rhs) => match (&**lhs, &**rhs) {
+                (Expr::Const(_), Expr::Symbol(_)) | (Expr::Symbol(_), Expr::Const(_)) =>
+                    Block::text(&self.to_string()),
+                _ => Block::infix(wrap(lhs, 2), " * ", wrap(rhs, 2)),
+            },
+            Expr::Div(lhs, rhs) => Block::fraction(lhs.to_block(), rhs.to_block()),
+            Expr::Pow(lhs, rhs) => {
+                let base = match &**lhs {
+                    Expr::Div(_, _) | Expr::Rational(_, _) => lhs.to_block().parens(),
+                    _ => wrap(lhs, 5),
+                };
+                base.raised(rhs.to_block())
+            }
+            Expr::Neg(expr) => negate(wrap(expr, 3)),
+            Expr::Sin(expr) => Block::text("sin").beside(expr.to_block().parens()),
+            Expr::Cos(expr) => Block::text("cos").beside(expr.to_block().parens()),
+            Expr::Exp(expr) => Block::text("e").raised(expr.to_block()),
+            Expr::Ln(expr) => Block::text("ln").beside(expr.to_block().parens()),
+            Expr::Abs(expr) => expr.to_block().enclose('|', '|'),
+            Expr::Sign(expr) => Block::text("sign").beside(expr.to_block().parens()),
+            Expr::Sqrt(expr) => Block::text("sqrt").beside(expr.to_block().parens()),
+            Expr::Sum { body, index, from, to } => Block::text(&format!("sum({}={}..{}, ", index, from, to))
+                .beside(body.to_block())
+                .beside(Block::text(")")),
+            Expr::Product { body, index, from, to } => Block::text(&format!("prod({}={}..{}, ", index, from, to))
+                .beside(body.to_block())
+                .beside(Block::text(")")),
+        }
+    }
+}
+
+/// Lays out `expr`, surrounding it with parentheses if it binds looser than `min_prec`.
+///
+/// Stacked fractions are visually self-contained, so they never need parentheses here.
+fn wrap(expr: &Expr, min_prec: u8) -> Block {
+    let prec = match expr {
+        Expr::Div(_, _) => 5,
+        Expr::Rational(n, d) if *d != 1 && *n > 0 => 5,
+        _ => expr.precedence(),
+    };
+    if prec < min_prec {
+        expr.to_block().parens()
+    } else {
+        expr.to_block()
+    }
+}
+
+/// Puts a minus sign in front of `block`, separated by a space when the block is taller than
+/// one line, so the sign does not run into a fraction bar and read as part of it.
+fn negate(block: Block) -> Block {
+    let sign = if block.height() > 1 { "- " } else { "-" };
+    Block::text(sign).beside(block)
+}
+
+/// A rectangle of text lines of equal width, with one line marked as the baseline that
+/// neighbouring blocks are aligned to.
+struct Block {
+    lines: Vec<String>,
+    baseline: usize,
+}
+
+impl Block {
+    /// Creates a single-line block.
+    fn text(text: &str) -> Block {
+        Block { lines: vec![text.to_string()], baseline: 0 }
+    }
+
+    fn width(&self) -> usize {
+        self.lines[0].chars().count()
+    }
+
+    fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Places `other` to the right of `self`, aligning their baselines.
+    fn beside(self, other: Block) -> Block {
+        let above = self.baseline.max(other.baseline);
+        let below = (self.height() - self.baseline).max(other.height() - other.baseline);
+        let lhs = self.pad_vertical(above, below);
+        let rhs = other.pad_vertical(above, below);
+        let lines = lhs.lines.into_iter().zip(rhs.lines).map(|(l, r)| l + &r).collect();
+        Block { lines, baseline: above }
+    }
+
+    /// Places `op` between `lhs` and `rhs`, aligned on their baselines.
+    fn infix(lhs: Block, op: &str, rhs: Block) -> Block {
+        lhs.beside(Block::text(op)).beside(rhs)
+    }
+
+    /// Stacks `num` over `den`, separated by a bar as wide as the wider of the two.
+    fn fraction(num: Block, den: Block) -> Block {
+        let width = num.width().max(den.width());
+        let baseline = num.height();
+        let mut lines = num.center(width).lines;
+        lines.push("-".repeat(width));
+        lines.extend(den.center(width).lines);
+        Block { lines, baseline }
+    }
+
+    /// Places `exp` to the upper right of `self`, ending one line above its top line.
+    fn raised(self, exp: Block) -> Block {
+        let (base_width, exp_width) = (self.width(), exp.width());
+        let baseline = exp.height() + self.baseline;
+        let mut lines: Vec<String> = exp.lines.into_iter().map(|line| " ".repeat(base_width) + &line).collect();
+        lines.extend(self.lines.into_iter().map(|line| line + &" ".repeat(exp_width)));
+        Block { lines, baseline }
+    }
+
+    /// Surrounds the block with parentheses on every line.
+    fn parens(self) -> Block {
+        self.enclose('(', ')')
+    }
+
+    /// Surrounds every line of the block with `open` and `close`.
+    fn enclose(self, open: char, close: char) -> Block {
+        let lines = self.lines.into_iter().map(|line| format!("{}{}{}", open, line, close)).collect();
+        Block { lines, baseline: self.baseline }
+    }
+
+    /// Pads the block horizontally to `width`, keeping it centered.
+    fn center(self, width: usize) -> Block {
+        let extra = width - self.width();
+        let (left, right) = (" ".repeat(extra / 2), " ".repeat(extra - extra / 2));
+        let lines = self.lines.into_iter().map(|line| format!("{}{}{}", left, line, right)).collect();
+        Block { lines, baseline: self.baseline }
+    }
+
+    /// Adds blank lines so the block has `above` lines over its baseline and `below` lines
+    /// from its baseline down.
+    fn pad_vertical(self, above: usize, below: usize) -> Block {
+        let blank = " ".repeat(self.width());
+        let top = above - self.baseline;
+        let bottom = below - (self.height() - self.baseline);
+        let mut lines = vec![blank.clone(); top];
+        lines.extend(self.lines);
+        lines.extend(vec![blank; bottom]);
+        Block { lines, baseline: above }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pretty_fraction() {
+        let x = Expr::new_var("x");
+        let res = (x.clone() + 1.0) / (x.clone() - 1.0);
+
+        assert_eq!(res.pretty(), "x + 1\n-----\nx - 1");
+    }
+
+    #[test]
+    fn pretty_centered_fraction_in_sum() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = y.clone() + x.clone() / (x.clone() * y.clone());
+
+        assert_eq!(res.pretty(), "      x\ny + -----\n    x * y");
+    }
+
+    #[test]
+    fn pretty_raised_exponent() {
+        let x = Expr::new_var("x");
+        let res = x.clone().pow(Expr::new_val(2.0)) + Expr::new_rational(1, 2);
+
+        assert_eq!(res.pretty(), " 2   1\nx  + -\n     2");
+    }
+
+    #[test]
+    fn pretty_single_line() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = (x.clone() - y.clone()) * 2.0 * x.clone().sin();
+
+        assert_eq!(res.pretty(), res.to_string());
+    }
+
+    #[test]
+    fn pretty_negation_in_sums() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((x.clone() + -(y.clone() / 2.0)).pretty(), "    y\nx - -\n    2");
+        assert_eq!((-x.clone() + y.clone()).pretty(), "y - x");
+    }
+
+    #[test]
+    fn pretty_negated_fraction() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((-(x.clone() / y.clone())).pretty(), "  x
+- -
+  y");
+        assert_eq!(Expr::new_rational(-1, 2).pretty(), "  1
+- -
+  2");
+        assert_eq!((-x.clone()).pretty(), "-x");
+        assert_eq!((-(x + y)).pretty(), "-(x + y)");
+    }
+
+    #[test]
+    fn pretty_parenthesized_fraction_base() {
+        let x = Expr::new_var("x");
+        let res = (1.0 / x.clone()).pow(Expr::new_var("n"));
+
+        assert_eq!(res.pretty(), "   n\n(1)\n(-)\n(x)");
+    }
+}
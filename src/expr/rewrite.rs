@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::mem;
+use crate::expr::Expr;
+use crate::symbol::Symbol;
+
+/// A user-supplied rewrite rule that replaces subexpressions matching `pattern` with
+/// `replacement`.
+///
+/// Symbols whose name starts with `?` are wildcards: in the pattern, a wildcard matches any
+/// subexpression, and every occurrence of the same wildcard must match equal
+/// subexpressions. In the replacement, each wildcard is replaced by the subexpression it
+/// matched. All other nodes must match exactly.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    /// The expression to look for.
+    pub pattern: Expr,
+    /// The expression to put in place of each match.
+    pub replacement: Expr,
+}
+
+impl Rule {
+    /// Creates a new `Rule` rewriting `pattern` to `replacement`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::expr::rewrite::Rule;
+    ///
+    /// let a = Expr::new_var("?a");
+    /// let rule = Rule::new(a.clone() + a.clone(), 2.0 * a);
+    /// ```
+    pub fn new(pattern: Expr, replacement: Expr) -> Rule {
+        Rule { pattern, replacement }
+    }
+}
+
+impl Expr {
+    /// Rewrites the current expression with user-supplied rules, returning a new expression.
+    ///
+    /// The tree is rewritten bottom-up, so a subexpression is rewritten after its children.
+    /// At each node the rules are tried in order, and the first rule whose pattern matches
+    /// replaces the node. The result of a rewrite is not rewritten again, so a rule like
+    /// `?a -> ?a + 0` applies once per node instead of looping; call `rewrite` again to
+    /// apply rules to their own output. Matching is structural, so `x + y` does not match
+    /// `?a * ?b` and operand order matters.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `rules` - The rules to apply, in order of priority.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::expr::rewrite::Rule;
+    ///
+    /// let a = Expr::new_var("?a");
+    /// let b = Expr::new_var("?b");
+    /// let rules = [Rule::new(a.clone().ln() + b.clone().ln(), (a * b).ln())];
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = x.clone().ln() + y.clone().ln();
+    /// assert_eq!(res.rewrite(&rules), (x * y).ln());
+    /// ```
+    pub fn rewrite(&self, rules: &[Rule]) -> Expr {
+        self.transform_up(|node| {
+            for rule in rules {
+                let mut bindings = HashMap::new();
+                if node.matches_pattern(&rule.pattern, &mut bindings) {
+                    return rule.replacement.bind_wildcards(&bindings);
+                }
+            }
+            node
+        })
+    }
+
+    /// Checks whether the current expression matches `pattern`, recording the subexpression
+    /// matched by each wildcard in `bindings`.
+    ///
+    /// A wildcard that is already bound only matches a subexpression equal to its binding.
+    /// The recursion follows the pattern, so its depth is bounded by the pattern's depth.
+    fn matches_pattern(&self, pattern: &Expr, bindings: &mut HashMap<Symbol, Expr>) -> bool {
+        if let Expr::Symbol(s) = pattern {
+            if is_wildcard(s) {
+                return match bindings.get(s) {
+                    Some(bound) => bound == self,
+                    None => {
+                        bindings.insert(s.clone(), self.clone());
+                        true
+                    }
+                };
+            }
+        }
+        if mem::discriminant(self) != mem::discriminant(pattern) {
+            return false;
+        }
+        // A sum or product only matches one over the same index and range
+        if let (
+            Expr::Sum { index, from, to, .. } | Expr::Product { index, from, to, .. },
+            Expr::Sum { index: p_index, from: p_from, to: p_to, .. } | Expr::Product { index: p_index, from: p_from, to: p_to, .. },
+        ) = (self, pattern)
+        {
+            if (index, from, to) != (p_index, p_from, p_to) {
+                return false;
+            }
+        }
+        let (children, pattern_children) = (self.children(), pattern.children());
+        if pattern_children.is_empty() {
+            return self == pattern;
+        }
+        children
+            .into_iter()
+            .zip(pattern_children)
+            .all(|(child, pattern_child)| child.matches_pattern(pattern_child, bindings))
+    }
+
+    /// Replaces every bound wildcard in the current expression by its binding.
+    fn bind_wildcards(&self, bindings: &HashMap<Symbol, Expr>) -> Expr {
+        self.transform_up(|node| match &node {
+            Expr::Symbol(s) => bindings.get(s).cloned().unwrap_or(node),
+            _ => node,
+        })
+    }
+}
+
+/// Returns `true` if `symbol` is a pattern wildcard, that is its name starts with `?`.
+fn is_wildcard(symbol: &Symbol) -> bool {
+    symbol.name.starts_with('?')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_repeated_wildcard() {
+        let a = Expr::new_var("?a");
+        let rules = [Rule::new(a.clone() + a.clone(), 2.0 * a.clone())];
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((x.clone() + x.clone()).rewrite(&rules), 2.0 * x.clone());
+        assert_eq!((x.clone().sin() + x.clone().sin()).rewrite(&rules), 2.0 * x.clone().sin());
+        // The second occurrence of ?a must match the same subexpression as the first
+        assert_eq!((x.clone() + y.clone()).rewrite(&rules), x.clone() + y.clone());
+    }
+
+    #[test]
+    fn rewrite_bottom_up() {
+        let a = Expr::new_var("?a");
+        let rules = [Rule::new(a.clone() + a.clone(), 2.0 * a.clone())];
+        let x = Expr::new_var("x");
+
+        // The inner sums are rewritten first, exposing the outer match
+        let res = (x.clone() + x.clone()) + (x.clone() + x.clone());
+        assert_eq!(res.rewrite(&rules), 2.0 * (2.0 * x.clone()));
+    }
+
+    #[test]
+    fn rewrite_rule_priority_and_literals() {
+        let a = Expr::new_var("?a");
+        let rules = [
+            Rule::new(a.clone() * 0.0, Expr::new_val(0.0)),
+            Rule::new(a.clone() * Expr::new_var("?b"), Expr::new_var("?b") * a.clone()),
+        ];
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((x.clone() * 0.0).rewrite(&rules), Expr::new_val(0.0));
+        assert_eq!((x.clone() * y.clone()).rewrite(&rules), y.clone() * x.clone());
+        assert_eq!((x.clone() + y.clone()).rewrite(&rules), x.clone() + y.clone());
+    }
+
+    #[test]
+    fn rewrite_not_reapplied() {
+        let a = Expr::new_var("?a");
+        let rules = [Rule::new(a.clone().sin(), a.clone().sin().sin())];
+        let x = Expr::new_var("x");
+
+        assert_eq!(x.clone().sin().rewrite(&rules), x.clone().sin().sin());
+    }
+
+    #[test]
+    fn rewrite_unbound_wildcard_kept() {
+        let rules = [Rule::new(Expr::new_var("x"), Expr::new_var("?c"))];
+        let x = Expr::new_var("x");
+
+        assert_eq!((x.clone() + 1.0).rewrite(&rules), Expr::new_var("?c") + 1.0);
+    }
+
+    #[test]
+    fn rewrite_sum_requires_same_range() {
+        let i = Expr::new_var("i");
+        let a = Expr::new_var("?a");
+        let index = Symbol::new("i");
+        let rules = [Rule::new(a.clone().summation(&index, 1, 3), Expr::new_val(42.0))];
+
+        let other = i.clone().summation(&Symbol::new("j"), 5, 100);
+        assert_eq!(other.rewrite(&rules), other);
+        let shifted = i.clone().summation(&index, 1, 4);
+        assert_eq!(shifted.rewrite(&rules), shifted);
+        assert_eq!(i.clone().summation(&index, 1, 3).rewrite(&rules), Expr::new_val(42.0));
+
+        let rules = [Rule::new(a.product(&index, 1, 3), Expr::new_val(42.0))];
+        let other = i.clone().product(&index, 0, 3);
+        assert_eq!(other.rewrite(&rules), other);
+        assert_eq!(i.product(&index, 1, 3).rewrite(&rules), Expr::new_val(42.0));
+    }
+}
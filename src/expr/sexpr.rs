@@ -0,0 +1,443 @@
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use crate::expr::Expr;
+use crate::symbol::Symbol;
+
+/// A failure to parse an expression, with the position and text of the token at fault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+    /// The byte offset of the offending token in the input, or the length of the input if it
+    /// ended too early.
+    pub position: usize,
+    /// The offending token, or the empty string if the input ended too early.
+    pub token: String,
+}
+
+/// Enum representing the kinds of error that can occur while parsing an expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The input ended before the expression was complete.
+    UnexpectedEnd,
+    /// A token appeared where it is not allowed, such as a stray `)` or input after the end
+    /// of the expression.
+    UnexpectedToken,
+    /// The head of a list is not a known operator.
+    UnknownOperator,
+    /// The operator was applied to the wrong number of arguments, given here.
+    WrongArity(usize),
+    /// The arguments of the operator have the wrong form, such as a non-symbol in `index`.
+    InvalidArgument,
+    /// An atom containing `/` is not a rational with a non-zero denominator.
+    InvalidNumber,
+}
+
+impl ParseError {
+    /// Creates a new `ParseError` of the given kind for the token at `position`.
+    pub fn new(kind: ParseErrorKind, position: usize, token: &str) -> ParseError {
+        ParseError { kind, position, token: token.to_string() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::UnexpectedEnd => write!(f, "unexpected end of input at position {}", self.position),
+            ParseErrorKind::UnexpectedToken => write!(f, "unexpected '{}' at position {}", self.token, self.position),
+            ParseErrorKind::UnknownOperator => write!(f, "unknown operator '{}' at position {}", self.token, self.position),
+            ParseErrorKind::WrongArity(n) =>
+                write!(f, "operator '{}' at position {} cannot take {} arguments", self.token, self.position, n),
+            ParseErrorKind::InvalidArgument => write!(f, "invalid arguments for '{}' at position {}", self.token, self.position),
+            ParseErrorKind::InvalidNumber => write!(f, "invalid number '{}' at position {}", self.token, self.position),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl Expr {
+    /// Renders the current expression as a fully parenthesized prefix S-expression.
+    ///
+    /// Every operation is written as a list whose head is the operator: `+`, `-`, `*`, `/` and
+    /// `^` take two arguments, and `neg`, `sin`, `cos`, `exp`, `ln`, `abs`, `sign` and `sqrt`
+    /// take one. A sum is written `(sum i from to body)`, and a product likewise with `prod`.
+    /// Constants are written with the shortest digits that read back to the same value,
+    /// rationals as `n/d`, the named constants as `pi` and `e`, symbols by name, and indexed
+    /// symbols as `(index x 1)`. `from_sexpr` reads the result back into an equal expression,
+    /// provided no symbol is named like a number, an arithmetic operator, `pi` or `e`, or
+    /// contains whitespace or parentheses.
+    ///
+    /// Like `from_sexpr`, this does not recurse, so deeply nested expressions are written
+    /// without overflowing the stack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// assert_eq!((x + 2.0 * y).to_sexpr(), "(+ x (* 2 y))");
+    /// ```
+    pub fn to_sexpr(&self) -> String {
+        let mut out = String::new();
+        // The nodes still to be written with the text between them, the next one last
+        let mut stack = vec![Piece::Node(self)];
+        while let Some(piece) = stack.pop() {
+            let node = match piece {
+                Piece::Text(text) => {
+                    out.push_str(text);
+                    continue;
+                }
+                Piece::Node(node) => node,
+            };
+            let head = match node {
+                Expr::Const(c) => {
+                    out.push_str(&c.to_string());
+                    continue;
+                }
+                Expr::Rational(n, d) => {
+                    out.push_str(&format!("{}/{}", n, d));
+                    continue;
+                }
+                Expr::Pi => {
+                    out.push_str("pi");
+                    continue;
+                }
+                Expr::E => {
+                    out.push('e');
+                    continue;
+                }
+                Expr::Symbol(s) => {
+                    write_symbol(&mut out, s);
+                    continue;
+                }
+                Expr::Add(_, _) => "+",
+                Expr::Sub(_, _) => "-",
+                Expr::Mul(_, _) => "*",
+                Expr::Div(_, _) => "/",
+                Expr::Pow(_, _) => "^",
+                Expr::Neg(_) => "neg",
+                Expr::Sin(_) => "sin",
+                Expr::Cos(_) => "cos",
+                Expr::Exp(_) => "exp",
+                Expr::Ln(_) => "ln",
+                Expr::Abs(_) => "abs",
+                Expr::Sign(_) => "sign",
+                Expr::Sqrt(_) => "sqrt",
+                Expr::Sum { .. } => "sum",
+                Expr::Product { .. } => "prod",
+            };
+            out.push('(');
+            out.push_str(head);
+            if let Expr::Sum { index, from, to, .. } | Expr::Product { index, from, to, .. } = node {
+                out.push(' ');
+                write_symbol(&mut out, index);
+                out.push_str(&format!(" {} {}", from, to));
+            }
+            stack.push(Piece::Text(")"));
+            for child in node.children().into_iter().rev() {
+                stack.push(Piece::Node(child));
+                stack.push(Piece::Text(" "));
+            }
+        }
+        out
+    }
+
+    /// Parses an expression from the prefix S-expression format written by `to_sexpr`.
+    ///
+    /// Atoms that parse as an `f64` are constants, atoms of the form `n/d` are rationals,
+    /// `pi` and `e` are the named constants, and any other atom is a symbol. `(- a)` is
+    /// accepted as a synonym for `(neg a)`. Tokens are separated by whitespace or
+    /// parentheses. The parser does not recurse, so deeply nested input cannot overflow the
+    /// stack.
+    ///
+    /// A `ParseError` reports the byte offset and text of the offending token; errors about an
+    /// operator's arguments point at the operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The S-expression to parse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::expr::sexpr::{ParseError, ParseErrorKind};
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// assert_eq!(Expr::from_sexpr("(+ x (* 2 y))"), Ok(x.clone() + 2.0 * y));
+    /// assert_eq!(Expr::from_sexpr("(sin x"), Err(ParseError::new(ParseErrorKind::UnexpectedEnd, 6, "")));
+    ///
+    /// let err = Expr::from_sexpr("(+ x * y)").unwrap_err();
+    /// assert_eq!(err.to_string(), "unexpected '*' at position 5");
+    /// ```
+    pub fn from_sexpr(input: &str) -> Result<Expr, ParseError> {
+        let mut tokens = tokenize(input).into_iter();
+        let end = || ParseError::new(ParseErrorKind::UnexpectedEnd, input.len(), "");
+        // The open lists, innermost last, each with its operator, the operator's position and
+        // the arguments read so far
+        let mut stack: Vec<(&str, usize, Vec<Expr>)> = Vec::new();
+        let mut result = None;
+        while let Some((pos, token)) = tokens.next() {
+            if result.is_some() {
+                return Err(ParseError::new(ParseErrorKind::UnexpectedToken, pos, token));
+            }
+            let node = match token {
+                "(" => {
+                    match tokens.next() {
+                        None => return Err(end()),
+                        Some((pos, op @ ("(" | ")"))) => return Err(ParseError::new(ParseErrorKind::UnexpectedToken, pos, op)),
+                        Some((pos, op)) => stack.push((op, pos, Vec::new())),
+                    }
+                    continue;
+                }
+                ")" => match stack.pop() {
+                    Some((op, op_pos, args)) => build_node(op, args).map_err(|kind| ParseError::new(kind, op_pos, op))?,
+                    None => return Err(ParseError::new(ParseErrorKind::UnexpectedToken, pos, token)),
+                },
+                atom => parse_atom(atom).map_err(|kind| ParseError::new(kind, pos, atom))?,
+            };
+            match stack.last_mut() {
+                Some((_, _, args)) => args.push(node),
+                None => result = Some(node),
+            }
+        }
+        match result {
+            Some(expr) if stack.is_empty() => Ok(expr),
+            _ => Err(end()),
+        }
+    }
+}
+
+/// A part of the output of `to_sexpr` that is still to be written.
+enum Piece<'a> {
+    /// A subexpression.
+    Node(&'a Expr),
+    /// Literal text, such as a closing parenthesis.
+    Text(&'static str),
+}
+
+/// Writes a symbol by name, or as `(index x 1)` if it is indexed.
+fn write_symbol(out: &mut String, s: &Symbol) {
+    match s.index {
+        Some(index) => out.push_str(&format!("(index {} {})", s.name, index)),
+        None => out.push_str(&s.name),
+    }
+}
+
+/// Splits `input` into parentheses and the atoms between them, each with its byte offset.
+fn tokenize(input: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in input.char_indices() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if let Some(s) = start.take() {
+                tokens.push((s, &input[s..i]));
+            }
+            if !c.is_whitespace() {
+                tokens.push((i, &input[i..i + 1]));
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s, &input[s..]));
+    }
+    tokens
+}
+
+/// Parses an atom into a constant, a rational, a named constant or a symbol.
+///
+/// The arithmetic operators are not values, so they are rejected outside the head of a list.
+fn parse_atom(atom: &str) -> Result<Expr, ParseErrorKind> {
+    if matches!(atom, "+" | "-" | "*" | "/" | "^") {
+        return Err(ParseErrorKind::UnexpectedToken);
+    }
+    if let Ok(c) = atom.parse::<f64>() {
+        return Ok(Expr::new_val(c));
+    }
+    if let Some((num, den)) = atom.split_once('/') {
+        return match (num.parse::<i64>(), den.parse::<i64>()) {
+            (Ok(num), Ok(den)) if den != 0 => Ok(Expr::new_rational(num, den)),
+            _ => Err(ParseErrorKind::InvalidNumber),
+        };
+    }
+    match atom {
+        "pi" => Ok(Expr::Pi),
+        "e" => Ok(Expr::E),
+        name => Ok(Expr::new_var(name)),
+    }
+}
+
+/// Applies the operator `op` to the parsed arguments of its list.
+fn build_node(op: &str, args: Vec<Expr>) -> Result<Expr, ParseErrorKind> {
+    let arity = args.len();
+    let mut args = args.into_iter().map(Rc::new);
+    let mut next = || args.next().expect("the arity was checked");
+    let node = match (op, arity) {
+        ("+", 2) => Expr::Add(next(), next()),
+        ("-", 2) => Expr::Sub(next(), next()),
+        ("*", 2) => Expr::Mul(next(), next()),
+        ("/", 2) => Expr::Div(next(), next()),
+        ("^", 2) => Expr::Pow(next(), next()),
+        ("neg" | "-", 1) => Expr::Neg(next()),
+        ("sin", 1) => Expr::Sin(next()),
+        ("cos", 1) => Expr::Cos(next()),
+        ("exp", 1) => Expr::Exp(next()),
+        ("ln", 1) => Expr::Ln(next()),
+        ("abs", 1) => Expr::Abs(next()),
+        ("sign", 1) => Expr::Sign(next()),
+        ("sqrt", 1) => Expr::Sqrt(next()),
+        ("index", 2) => match (&*next(), &*next()) {
+            (Expr::Symbol(s), Expr::Const(i)) if s.index.is_none() && *i >= 0.0 && i.fract() == 0.0 =>
+                Expr::Symbol(Symbol::indexed(&s.name, *i as usize)),
+            _ => return Err(ParseErrorKind::InvalidArgument),
+        },
+        ("sum" | "prod", 4) => {
+            let (index, from, to) = (next(), next(), next());
+            match (&*index, integer(&from), integer(&to)) {
+                (Expr::Symbol(s), Some(from), Some(to)) if op == "sum" =>
+                    Expr::Sum { body: next(), index: s.clone(), from, to },
+                (Expr::Symbol(s), Some(from), Some(to)) => Expr::Product { body: next(), index: s.clone(), from, to },
+                _ => return Err(ParseErrorKind::InvalidArgument),
+            }
+        }
+        ("+" | "-" | "*" | "/" | "^" | "neg" | "sin" | "cos" | "exp" | "ln" | "abs" | "sign" | "sqrt" | "index" | "sum" | "prod", _) =>
+            return Err(ParseErrorKind::WrongArity(arity)),
+        _ => return Err(ParseErrorKind::UnknownOperator),
+    };
+    Ok(node)
+}
+
+/// Returns the value of `expr` if it is a constant holding a whole number that fits in an
+/// `i64`.
+fn integer(expr: &Expr) -> Option<i64> {
+    match *expr {
+        Expr::Const(c) if c.fract() == 0.0 && c.abs() < i64::MAX as f64 => Some(c as i64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sexpr_round_trip() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let x1 = Expr::Symbol(Symbol::indexed("x", 1));
+        let exprs = [
+            (x.clone() + 2.0 * y.clone()).pow(Expr::new_rational(1, 3)),
+            -(x.clone() - y.clone()) / (x1.clone() * Expr::Pi),
+            (x.clone().sin() + x.clone().cos()).exp().ln().abs().sign().sqrt(),
+            Expr::new_val(0.1) * Expr::new_val(-1e-12) + Expr::new_val(6.02e23) - Expr::E,
+            Expr::new_rational(4, 2) + Expr::new_val(2.0),
+            Expr::new_val(f64::INFINITY) - x1.clone(),
+            (x.clone() * x1.clone()).summation(&Symbol::indexed("x", 1), -2, 5),
+            Expr::product(y.clone().sin(), &Symbol::new("y"), 1, 3) - y.clone(),
+        ];
+
+        for expr in exprs {
+            let text = expr.to_sexpr();
+            assert_eq!(Expr::from_sexpr(&text), Ok(expr), "{}", text);
+        }
+    }
+
+    #[test]
+    fn sexpr_format() {
+        let x = Expr::new_var("x");
+
+        assert_eq!((-x.clone()).pow(Expr::new_val(0.5)).to_sexpr(), "(^ (neg x) 0.5)");
+        assert_eq!(Expr::new_rational(-1, 2).to_sexpr(), "-1/2");
+        assert_eq!(Expr::Symbol(Symbol::indexed("a", 3)).sin().to_sexpr(), "(sin (index a 3))");
+        assert_eq!(x.clone().pow(x.clone()).summation(&Symbol::new("x"), 1, 3).to_sexpr(), "(sum x 1 3 (^ x x))");
+    }
+
+    #[test]
+    fn sexpr_lenient_input() {
+        let x = Expr::new_var("x");
+
+        assert_eq!(Expr::from_sexpr("  (-\n x)  "), Ok(-x.clone()));
+        assert_eq!(Expr::from_sexpr("(*(+ x 1)x)"), Ok((x.clone() + 1.0) * x.clone()));
+        assert_eq!(Expr::from_sexpr("2/4"), Ok(Expr::new_rational(1, 2)));
+        assert_eq!(Expr::from_sexpr("?a"), Ok(Expr::new_var("?a")));
+    }
+
+    /// Parses `input`, which must be malformed, and returns the kind, position and token of the
+    /// error.
+    fn parse_err(input: &str) -> (ParseErrorKind, usize, String) {
+        let err = Expr::from_sexpr(input).unwrap_err();
+        (err.kind, err.position, err.token)
+    }
+
+    #[test]
+    fn sexpr_errors() {
+        use ParseErrorKind::*;
+
+        assert_eq!(parse_err(""), (UnexpectedEnd, 0, "".to_string()));
+        assert_eq!(parse_err("(+ x"), (UnexpectedEnd, 4, "".to_string()));
+        assert_eq!(parse_err("("), (UnexpectedEnd, 1, "".to_string()));
+        assert_eq!(parse_err("x)"), (UnexpectedToken, 1, ")".to_string()));
+        assert_eq!(parse_err("x  y"), (UnexpectedToken, 3, "y".to_string()));
+        assert_eq!(parse_err("()"), (UnexpectedToken, 1, ")".to_string()));
+        assert_eq!(parse_err("((+ x y) 1)"), (UnexpectedToken, 1, "(".to_string()));
+        assert_eq!(parse_err("(+ x * y)"), (UnexpectedToken, 5, "*".to_string()));
+        assert_eq!(parse_err("(- /)"), (UnexpectedToken, 3, "/".to_string()));
+        assert_eq!(parse_err("(* 2 (tan x))"), (UnknownOperator, 6, "tan".to_string()));
+        assert_eq!(parse_err("(+ x y z)"), (WrongArity(3), 1, "+".to_string()));
+        assert_eq!(parse_err("(neg (index 1 x))"), (InvalidArgument, 6, "index".to_string()));
+        assert_eq!(parse_err("(sum i 1 2.5 i)"), (InvalidArgument, 1, "sum".to_string()));
+        assert_eq!(parse_err("(sum i 1 3)"), (WrongArity(3), 1, "sum".to_string()));
+        assert_eq!(parse_err("(prod 2 1 3 x)"), (InvalidArgument, 1, "prod".to_string()));
+        assert_eq!(parse_err("(sin 1/0)"), (InvalidNumber, 5, "1/0".to_string()));
+    }
+
+    #[test]
+    fn sexpr_error_messages() {
+        let message = |input: &str| Expr::from_sexpr(input).unwrap_err().to_string();
+
+        assert_eq!(message("(+ x * y)"), "unexpected '*' at position 5");
+        assert_eq!(message("(sin x"), "unexpected end of input at position 6");
+        assert_eq!(message("(tan x)"), "unknown operator 'tan' at position 1");
+        assert_eq!(message("(sin x x)"), "operator 'sin' at position 1 cannot take 2 arguments");
+        assert_eq!(message("(index x y)"), "invalid arguments for 'index' at position 1");
+        assert_eq!(message("1/x"), "invalid number '1/x' at position 0");
+    }
+
+    #[test]
+    fn sexpr_positions_are_byte_offsets() {
+        let err = Expr::from_sexpr("(+ \u{3b1} \u{3b2}) )").unwrap_err();
+
+        assert_eq!(err.kind, ParseErrorKind::UnexpectedToken);
+        assert_eq!(err.position, 10);
+        assert_eq!(err.token, ")");
+    }
+
+    #[test]
+    fn sexpr_deep_nesting() {
+        let depth = 100_000;
+        let text = "(neg ".repeat(depth) + "x" + &")".repeat(depth);
+        let expr = Expr::from_sexpr(&text).unwrap();
+
+        assert_eq!(expr.depth(), depth + 1);
+    }
+
+    #[test]
+    fn sexpr_deep_chain_round_trip() {
+        let mut expr = Expr::new_var("x");
+        for i in 0..100_000 {
+            expr += i as f64;
+        }
+        let text = expr.to_sexpr();
+
+        assert!(text.starts_with("(+ (+ (+ "));
+        assert!(text.ends_with(" 99998) 99999)"));
+        assert_eq!(Expr::from_sexpr(&text).unwrap().to_sexpr(), text);
+    }
+}
@@ -1,10 +1,27 @@
-use crate::expr::Expr;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use crate::expr::{gcd, Expr};
+use crate::expr::eval::BinaryOp;
 
 impl Expr {
     /// Simplifies the current expression to a possibly simpler form.
     ///
     /// The method simplifies the mathematical expressions based on several
-    /// algebraic rules.
+    /// algebraic rules. The tree is rebuilt bottom-up with an explicit stack rather than
+    /// recursion, so deeply nested expressions do not overflow the call stack.
+    ///
+    /// Constant subexpressions are folded only when the result is finite. Operations such as
+    /// `1 / 0` or `0 / 0` are left unevaluated rather than becoming `inf` or `NaN`, so no
+    /// infinities are introduced into the tree. `x^0` becomes `1` for every `x`, so `0^0` is `1`,
+    /// matching IEEE `pow` and `eval`.
+    ///
+    /// Factors shared by the numerator and denominator of a division are cancelled, so `x / x`
+    /// becomes `1` and `(a * b) / a` becomes `b`. Like most computer algebra systems, this
+    /// assumes the cancelled factors are non-zero; only literal zeros are kept. Powers with a
+    /// negative numeric exponent are written as reciprocals, so `x^(-2)` becomes `1 / x^2`, and
+    /// `x^(1/2)` with an exact rational exponent becomes `sqrt(x)`. Double negations cancel and
+    /// negated numbers are folded, but negation is not distributed over sums.
     ///
     /// # Example
     ///
@@ -17,21 +34,107 @@ impl Expr {
     /// assert_eq!(res.simplify(), Expr::new_val(2.0) * Expr::new_var("x"));
     /// ```
     pub fn simplify(&self) -> Expr {
-        match self {
+        self.transform_up(Expr::simplify_node)
+    }
+
+    /// Simplifies the current expression in place, leaving it equal to what `simplify` would
+    /// return.
+    ///
+    /// Unlike `simplify`, which builds a new tree next to the original, this takes the nodes
+    /// out of the tree and reuses them, so it avoids copying the parts of a large expression
+    /// that no other expression shares. Subexpressions shared with clones of the expression
+    /// are copied before they are changed, so the clones are unaffected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let mut res = x.clone() + x.clone() * 1.0;
+    /// let expected = res.simplify();
+    /// res.simplify_mut();
+    /// assert_eq!(res, expected);
+    /// ```
+    pub fn simplify_mut(&mut self) {
+        self.transform_up_mut(Expr::simplify_node)
+    }
+
+    /// The largest number of `simplify` passes that `simplify_fully` will run.
+    pub const MAX_SIMPLIFY_PASSES: u32 = 32;
+
+    /// Simplifies the current expression repeatedly until the result stops changing.
+    ///
+    /// A single `simplify` pass can leave a rewrite result that itself matches a rule, such as
+    /// `exp(ln(y))` produced from `e^ln(y)`. Running passes until a fixed point makes the
+    /// result stable under further simplification. At most `Expr::MAX_SIMPLIFY_PASSES` passes
+    /// are run, so a set of rules that keeps rewriting back and forth still terminates.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let y = Expr::new_var("y");
+    /// let res = Expr::E.pow(y.clone().ln()) * 2.0;
+    /// assert_eq!(res.simplify_fully(), y * Expr::new_val(2.0));
+    /// ```
+    pub fn simplify_fully(&self) -> Expr {
+        let mut current = self.simplify();
+        for _ in 1..Expr::MAX_SIMPLIFY_PASSES {
+            let next = current.simplify();
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// Applies the simplification rules to the outermost operation of an expression whose
+    /// children have already been simplified.
+    fn simplify_node(self) -> Expr {
+        if let Some(folded) = self.fold_constants() {
+            return folded;
+        }
+        let original = self.clone();
+        match self.apply_rules() {
+            // A rule folded constants into a non-finite value, keep the operation instead
+            Expr::Const(c) if !c.is_finite() && !original.is_const() => original,
+            res => res,
+        }
+    }
+
+    /// Applies the algebraic rewrite rules to the outermost operation of an expression.
+    fn apply_rules(self) -> Expr {
+        match &self {
             Expr::Add(lhs, rhs) => {
-                let lhs = lhs.simplify();
-                let rhs = rhs.simplify();
-                match (&lhs, &rhs) {
+                match (&**lhs, &**rhs) {
+                    // Both rationals, return exact sum
+                    (Expr::Rational(n1, d1), Expr::Rational(n2, d2)) => checked_rational(
+                        n1.checked_mul(*d2).zip(n2.checked_mul(*d1)).and_then(|(a, b)| a.checked_add(b)),
+                        d1.checked_mul(*d2),
+                        ratio(*n1, *d1) + ratio(*n2, *d2),
+                    ),
+                    // Rational and constant, return float sum
+                    (Expr::Rational(n, d), Expr::Const(c))
+                        | (Expr::Const(c), Expr::Rational(n, d)) =>
+                        Expr::new_val(ratio(*n, *d) + c),
                     // lhs == rhs, return 2 * lhs
                     (lhs, rhs) if *lhs == *rhs =>
-                        Expr::Mul(Box::new(Expr::new_val(2.0)), Box::new(lhs.clone())),
+                        Expr::Mul(Rc::new(Expr::new_val(2.0)), Rc::new(lhs.clone())),
+                    // sin(x)^2 + cos(x)^2 in either order, return 1
+                    (lhs, rhs) if is_pythagorean_pair(lhs, rhs) || is_pythagorean_pair(rhs, lhs) =>
+                        Expr::Const(1.0),
                     // cx + x, return (c+1)x 
                     (Expr::Mul(c, inside), out)
                         | (Expr::Mul(inside, c), out)
                         | (out, Expr::Mul(inside, c))
                         | (out, Expr::Mul(c, inside))
-                        if ((**inside == *out ) && c.is_const()) =>
-                        Expr::Mul(Box::new(Expr::new_val(c.get_const() + 1.0)), Box::new(out.clone())),
+                        if **inside == *out => match c.as_const() {
+                            Some(c) => Expr::Mul(Rc::new(Expr::new_val(c + 1.0)), Rc::new(out.clone())),
+                            None => self.collect_terms(),
+                        },
                     // Both constants, return mul
                     (Expr::Const(c1), Expr::Const(c2)) =>
                         Expr::new_val(c1 + c2),
@@ -39,34 +142,73 @@ impl Expr {
                     (Expr::Const(c), x)
                         | (x, Expr::Const(c))
                         if *c == 0.0 => x.clone(),
-                    // Else
-                    _ => Expr::Add(Box::new(lhs), Box::new(rhs)),
+                    // Else, combine like terms across the chain
+                    _ => self.collect_terms(),
                 }
             },
             Expr::Sub(lhs, rhs) => {
-                let lhs = lhs.simplify();
-                let rhs = rhs.simplify();
-                match (&lhs, &rhs) {
+                match (&**lhs, &**rhs) {
+                    // Both rationals, return exact diff
+                    (Expr::Rational(n1, d1), Expr::Rational(n2, d2)) => checked_rational(
+                        n1.checked_mul(*d2).zip(n2.checked_mul(*d1)).and_then(|(a, b)| a.checked_sub(b)),
+                        d1.checked_mul(*d2),
+                        ratio(*n1, *d1) - ratio(*n2, *d2),
+                    ),
+                    // Rational and constant, return float diff
+                    (Expr::Rational(n, d), Expr::Const(c)) => Expr::new_val(ratio(*n, *d) - c),
+                    (Expr::Const(c), Expr::Rational(n, d)) => Expr::new_val(c - ratio(*n, *d)),
                     // Both constants, return diff
                     (Expr::Const(c1), Expr::Const(c2)) => Expr::new_val(c1 - c2),
-                    // Constant == 0, return Expr unchanged
-                    (Expr::Const(c), x)
-                        | (x, Expr::Const(c))
-                        if *c == 0.0 => x.clone(),
-                    // Else
-                    _ => Expr::Sub(Box::new(lhs), Box::new(rhs)),
+                    // x - 0, return x
+                    (x, Expr::Const(c)) if *c == 0.0 => x.clone(),
+                    // 0 - x, return -x
+                    (Expr::Const(c), x) if *c == 0.0 => Expr::Neg(Rc::new(x.clone())).simplify_node(),
+                    // lhs == rhs, return 0
+                    (lhs, rhs) if *lhs == *rhs => Expr::Const(0.0),
+                    // cx - x, return (c-1)x
+                    (Expr::Mul(c, inside), out)
+                        | (Expr::Mul(inside, c), out)
+                        if **inside == *out => match c.as_const() {
+                            Some(c) => Expr::Mul(Rc::new(Expr::new_val(c - 1.0)), Rc::new(out.clone())).simplify_node(),
+                            None => self.collect_terms(),
+                        },
+                    // x - cx, return (1-c)x
+                    (out, Expr::Mul(c, inside))
+                        | (out, Expr::Mul(inside, c))
+                        if **inside == *out => match c.as_const() {
+                            Some(c) => Expr::Mul(Rc::new(Expr::new_val(1.0 - c)), Rc::new(out.clone())).simplify_node(),
+                            None => self.collect_terms(),
+                        },
+                    // c1x - c2x, return (c1-c2)x
+                    (Expr::Mul(_, _), Expr::Mul(_, _)) => match (lhs.as_coeff_term(), rhs.as_coeff_term()) {
+                        ((c1, t1), (c2, t2)) if t1 == t2 =>
+                            Expr::Mul(Rc::new(Expr::new_val(c1 - c2)), Rc::new(t1)).simplify_node(),
+                        _ => self.collect_terms(),
+                    },
+                    // Else, combine like terms across the chain
+                    _ => self.collect_terms(),
                 }
             },
             Expr::Mul(lhs, rhs) => {
-                let lhs = lhs.simplify();
-                let rhs = rhs.simplify();
-                match (&lhs, &rhs) {
+                match (&**lhs, &**rhs) {
+                    // Both rationals, return exact product
+                    (Expr::Rational(n1, d1), Expr::Rational(n2, d2)) => checked_rational(
+                        n1.checked_mul(*n2),
+                        d1.checked_mul(*d2),
+                        ratio(*n1, *d1) * ratio(*n2, *d2),
+                    ),
+                    // Rational and constant, return float product
+                    (Expr::Rational(n, d), Expr::Const(c))
+                        | (Expr::Const(c), Expr::Rational(n, d)) =>
+                        Expr::new_val(ratio(*n, *d) * c),
                     // lhs == rhs, return lhs^2
                     (lhs, rhs) if *lhs == *rhs =>
-                        Expr::Pow(Box::new(lhs.clone()), Box::new(Expr::new_val(2.0))),
+                        Expr::Pow(Rc::new(lhs.clone()), Rc::new(Expr::new_val(2.0))),
                     // x^a * x^b, return x^(a+b)
-                    (Expr::Pow(base1, a), Expr::Pow(base2, b)) if *base1 == *base2 =>
-                        Expr::Pow(base1.clone(), Box::new(Expr::Add(a.clone(), b.clone()))),
+                    (Expr::Pow(base1, a), Expr::Pow(base2, b)) if *base1 == *base2 => {
+                        let exp = Expr::Add(a.clone(), b.clone()).simplify_node();
+                        Expr::Pow(base1.clone(), Rc::new(exp)).simplify_node()
+                    }
                     // Both constants, return mul
                     (Expr::Const(c1), Expr::Const(c2)) => Expr::new_val(c1 * c2),
                     // Constant == 1, return Expr unchanged
@@ -80,51 +222,493 @@ impl Expr {
                     // Constant == -1, return Neg
                     (Expr::Const(c), x)
                         | (x, Expr::Const(c))
-                        if *c == -1.0 => Expr::Neg(Box::new(x.clone())),
+                        if *c == -1.0 => Expr::Neg(Rc::new(x.clone())),
                     // Else
-                    _ => Expr::Mul(Box::new(lhs), Box::new(rhs)),
+                    _ => self,
                 }
             },
             Expr::Div(lhs, rhs) => {
-                let lhs = lhs.simplify();
-                let rhs = rhs.simplify();
-                match (&lhs, &rhs) {
+                match (&**lhs, &**rhs) {
+                    // Both rationals, return exact quotient
+                    (Expr::Rational(n1, d1), Expr::Rational(n2, d2)) if *n2 != 0 => checked_rational(
+                        n1.checked_mul(*d2),
+                        d1.checked_mul(*n2),
+                        ratio(*n1, *d1) / ratio(*n2, *d2),
+                    ),
+                    // Rational and constant, return float quotient
+                    (Expr::Rational(n, d), Expr::Const(c)) => Expr::new_val(ratio(*n, *d) / c),
+                    (Expr::Const(c), Expr::Rational(n, d)) => Expr::new_val(c / ratio(*n, *d)),
                     // Both constants, return div
                     (Expr::Const(c1), Expr::Const(c2)) => Expr::new_val(c1 / c2),
-                    // Symbol, constant == 1, return symbol
-                    (x, Expr::Const(c))
-                        | (Expr::Const(c), x)
-                        if *c == 1.0 => x.clone(),
+                    // x divided by 1, return x
+                    (x, Expr::Const(c)) if *c == 1.0 => x.clone(),
                     // 0 divided by x, return 0
                     (Expr::Const(c), _) if *c == 0.0 => Expr::Const(0.0),
-                    // Else
-                    _ => Expr::Div(Box::new(lhs), Box::new(rhs)),
+                    // x divided by itself, return 1
+                    (lhs, rhs) if *lhs == *rhs && !lhs.is_zero() => Expr::Const(1.0),
+                    // (a * b) / a, return b, else leave unchanged
+                    (lhs, rhs) => cancel_common_factors(lhs, rhs).unwrap_or(self),
                 }
             },
             Expr::Pow(lhs, rhs) => {
-                let lhs = lhs.simplify();
-                let rhs = rhs.simplify();
-                match (&lhs, &rhs) {
-                    // (x^a)^b, returns x^(a*b)
-                    (Expr::Pow(base, p1), p2) =>
-                        Expr::Pow(
-                            base.clone(),
-                            Box::new(Expr::Mul(p1.clone(), Box::new(p2.clone())))
-                        ),
+                match (&**lhs, &**rhs) {
+                    // x^(1/2) or x^0.5, returns sqrt(x), before (x^a)^b so that (x^2)^(1/2)
+                    // becomes |x|
+                    (x, Expr::Rational(1, 2)) => Expr::Sqrt(Rc::new(x.clone())).simplify_node(),
+                    (x, Expr::Const(c)) if *c == 0.5 => Expr::Sqrt(Rc::new(x.clone())).simplify_node(),
+                    // sqrt(x)^2, returns x, as both sides are only defined for x >= 0
+                    (Expr::Sqrt(x), Expr::Const(c)) if *c == 2.0 => (**x).clone(),
+                    // (x^a)^b for an integer b, returns x^(a*b) with the new exponent
+                    // simplified. A fractional b is left alone, as (x^2)^0.25 is not x^0.5
+                    // for negative x
+                    (Expr::Pow(base, p1), p2) if is_integer(p2) => {
+                        let exp = Expr::Mul(p1.clone(), Rc::new(p2.clone())).simplify_node();
+                        Expr::Pow(base.clone(), Rc::new(exp)).simplify_node()
+                    }
                     // x^1, returns x
                     (x, Expr::Const(c)) if *c == 1.0 => x.clone(),
-                    // x^0, returns 1
-                    (_, Expr::Const(c)) if *c == 0.0 => Expr::Const(1.0), // TODO: Only if x != 0
+                    // x^0, returns 1, including for x = 0 as in IEEE pow and eval
+                    (_, Expr::Const(c)) if *c == 0.0 => Expr::Const(1.0),
                     // 1^x, returns 1
                     (Expr::Const(c), _) if *c == 1.0 => Expr::Const(1.0),
+                    // e^x, returns exp(x)
+                    (Expr::E, x) => Expr::Exp(Rc::new(x.clone())),
+                    // x^(-n) for a negative number n and non-constant x, returns 1 / x^n
+                    (x, exp) if !x.is_const() && is_negative_number(exp) => {
+                        let pow = Expr::Pow(Rc::new(x.clone()), Rc::new(negate_number(exp))).simplify_node();
+                        Expr::Div(Rc::new(Expr::new_val(1.0)), Rc::new(pow))
+                    }
                     // Else
-                    _ => Expr::Pow(Box::new(lhs), Box::new(rhs))
+                    _ => self,
                 }
             },
+            // Only the safe direction is applied: ln(a * b) is not split into ln(a) + ln(b),
+            // and ln(a) + ln(b) is not combined either
+            Expr::Ln(expr) => match &**expr {
+                // ln(e), returns 1
+                Expr::E => Expr::Const(1.0),
+                // ln(1), returns 0
+                Expr::Const(c) if *c == 1.0 => Expr::Const(0.0),
+                // ln(x^n) for an even integer n, returns n*ln(|x|), which keeps negative x
+                Expr::Pow(base, exp) if matches!(**exp, Expr::Const(n) if n % 2.0 == 0.0) =>
+                    Expr::Mul(exp.clone(), Rc::new(Expr::Ln(Rc::new(Expr::Abs(base.clone()))))),
+                // ln(x^n), returns n*ln(x) assuming x > 0
+                Expr::Pow(base, exp) if exp.is_const() =>
+                    Expr::Mul(exp.clone(), Rc::new(Expr::Ln(base.clone()))),
+                // Else
+                _ => self,
+            },
+            Expr::Exp(expr) => match &**expr {
+                // exp(ln(x)), returns x assuming x > 0
+                Expr::Ln(inner) => (**inner).clone(),
+                // Else
+                _ => self,
+            },
+            Expr::Sin(_)
+                | Expr::Cos(_)
+                | Expr::Abs(_)
+                | Expr::Sign(_) => self,
+            Expr::Sqrt(expr) => match &**expr {
+                // sqrt(x^2), returns |x|
+                Expr::Pow(base, exp) if **exp == Expr::new_val(2.0) => Expr::Abs(base.clone()),
+                // sqrt(x * x), returns |x|
+                Expr::Mul(lhs, rhs) if lhs == rhs => Expr::Abs(lhs.clone()),
+                // Else
+                _ => self,
+            },
+            // Sums are not distributed over, so -(a + b) keeps its form
+            Expr::Neg(expr) => match &**expr {
+                // -(n/d), returns the exact rational -n/d
+                Expr::Rational(n, d) => match n.checked_neg() {
+                    Some(n) => Expr::Rational(n, *d),
+                    None => self,
+                },
+                // -(-x), returns x
+                Expr::Neg(inner) => (**inner).clone(),
+                // -(a - b), returns b - a
+                Expr::Sub(lhs, rhs) => Expr::Sub(rhs.clone(), lhs.clone()),
+                // -(c * x) or -(x * c) for a constant c, returns (-c) * x
+                Expr::Mul(c, x) | Expr::Mul(x, c) if c.is_const() =>
+                    Expr::Mul(Rc::new(negate_number(c)), x.clone()).simplify_node(),
+                // Else
+                _ => self,
+            },
             _ => self.clone()
         }
     }
 
+    /// Combines like terms across the outermost chain of additions and subtractions.
+    ///
+    /// The chain is flattened into signed terms, and each term is split into a numeric
+    /// coefficient and its canonical symbolic factor. Coefficients of matching factors are
+    /// summed, numeric terms are folded into a single constant placed last, and the chain is
+    /// rebuilt in order of first appearance. Terms `c * sin(u)^2` and `c * cos(u)^2` with the
+    /// same coefficient anywhere in the chain are combined into the constant `c`. Terms whose
+    /// coefficients cancel to zero are dropped, and a sum that cancels entirely becomes
+    /// `Const(0.0)`. Expressions that are not sums are returned unchanged, and the terms
+    /// themselves are not simplified.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = x.clone() + y.clone() + x.clone();
+    /// assert_eq!(res.collect_terms(), Expr::new_val(2.0) * x + y);
+    /// ```
+    pub fn collect_terms(&self) -> Expr {
+        if !matches!(self, Expr::Add(_, _) | Expr::Sub(_, _)) {
+            return self.clone();
+        }
+        let mut signed = Vec::new();
+        self.signed_terms(1.0, &mut signed);
+
+        let mut terms: Vec<(Expr, f64)> = Vec::new();
+        let mut index: BTreeMap<Expr, usize> = BTreeMap::new();
+        let mut constant: Option<Expr> = None;
+        for (sign, term) in signed {
+            match term {
+                Expr::Const(_) | Expr::Rational(_, _) => {
+                    let term = if sign < 0.0 { negate_number(term) } else { term.clone() };
+                    constant = Some(match constant {
+                        Some(acc) => Expr::Add(Rc::new(acc), Rc::new(term)).simplify_node(),
+                        None => term,
+                    });
+                }
+                _ => {
+                    let (coeff, factor) = term.as_coeff_term();
+                    let factor = factor.canonicalize();
+                    match index.get(&factor) {
+                        Some(&i) => terms[i].1 += sign * coeff,
+                        None => {
+                            index.insert(factor.clone(), terms.len());
+                            terms.push((factor, sign * coeff));
+                        }
+                    }
+                }
+            }
+        }
+        // c * sin(u)^2 + c * cos(u)^2 = c, wherever the two terms sit in the chain
+        for i in 0..terms.len() {
+            let Expr::Pow(base, exp) = &terms[i].0 else { continue };
+            let Expr::Sin(arg) = &**base else { continue };
+            let cos = Expr::Pow(Rc::new(Expr::Cos(arg.clone())), exp.clone());
+            let Some(&j) = index.get(&cos) else { continue };
+            let coeff = terms[i].1;
+            if coeff != 0.0 && terms[j].1 == coeff && is_pythagorean_pair(&terms[i].0, &terms[j].0) {
+                terms[i].1 = 0.0;
+                terms[j].1 = 0.0;
+                let term = Expr::new_val(coeff);
+                constant = Some(match constant {
+                    Some(acc) => Expr::Add(Rc::new(acc), Rc::new(term)).simplify_node(),
+                    None => term,
+                });
+            }
+        }
+        rebuild_sum(terms, constant)
+    }
+
+    /// Rewrites the current expression as a normalized sum of monomials.
+    ///
+    /// The expression is expanded, and every term is split into a numeric coefficient and a
+    /// product of bases raised to numeric exponents, merging repeated bases (`x * x^2` is
+    /// `x^3`). Terms with the same product are combined, and the result is rebuilt with the
+    /// terms sorted by descending total degree and then lexicographically by their bases in
+    /// canonical order, so `x^2` comes before `x * y`, with the constant term last. Factors
+    /// other than symbols and their numeric powers, such as `sin(x)`, are treated as opaque
+    /// bases. Two polynomials that are equal after expansion normalize to the same tree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let lhs = (x.clone() + 1.0) * (x.clone() - 1.0);
+    /// let rhs = x.clone().pow(Expr::new_val(2.0)) - 1.0;
+    /// assert_eq!(lhs.to_sum_of_products(), rhs.to_sum_of_products());
+    /// assert_eq!(lhs.to_sum_of_products(), rhs);
+    /// ```
+    pub fn to_sum_of_products(&self) -> Expr {
+        let expanded = self.expand();
+        let mut signed = Vec::new();
+        expanded.signed_terms(1.0, &mut signed);
+
+        let mut monomials: BTreeMap<Expr, (f64, Vec<(Expr, f64)>)> = BTreeMap::new();
+        let mut constant = 0.0;
+        for (sign, term) in signed {
+            let (coeff, powers) = term.monomial();
+            if powers.is_empty() {
+                constant += sign * coeff;
+                continue;
+            }
+            let product = powers
+                .iter()
+                .map(|(base, exp)| if *exp == 1.0 { base.clone() } else { Expr::Pow(Rc::new(base.clone()), Rc::new(Expr::new_val(*exp))) })
+                .reduce(|acc, factor| Expr::Mul(Rc::new(acc), Rc::new(factor)))
+                .expect("a monomial with powers has at least one factor");
+            monomials.entry(product).or_insert_with(|| (0.0, powers.into_iter().collect())).0 += sign * coeff;
+        }
+
+        let mut terms: Vec<_> = monomials.into_iter().collect();
+        terms.sort_by(|(_, (_, lhs)), (_, (_, rhs))| graded_lex(lhs, rhs));
+        let terms = terms.into_iter().map(|(product, (coeff, _))| (product, coeff)).collect();
+        rebuild_sum(terms, Some(Expr::new_val(constant)))
+    }
+
+    /// Splits a term into its numeric coefficient and a map from each of its bases, in
+    /// canonical form, to the total numeric exponent it is raised to.
+    ///
+    /// Bases whose exponents cancel out are dropped.
+    fn monomial(&self) -> (f64, BTreeMap<Expr, f64>) {
+        let mut coeff = 1.0;
+        let mut powers: BTreeMap<Expr, f64> = BTreeMap::new();
+        let mut factors = Vec::new();
+        self.push_factors(&mut factors);
+        while let Some(factor) = factors.pop() {
+            let (base, exp) = match factor {
+                Expr::Const(c) => {
+                    coeff *= c;
+                    continue;
+                }
+                Expr::Rational(n, d) => {
+                    coeff *= ratio(*n, *d);
+                    continue;
+                }
+                Expr::Neg(expr) => {
+                    coeff = -coeff;
+                    expr.push_factors(&mut factors);
+                    continue;
+                }
+                Expr::Pow(base, exp) => match exp.as_const() {
+                    Some(exp) => (base.canonicalize(), exp),
+                    None => (factor.canonicalize(), 1.0),
+                },
+                _ => (factor.canonicalize(), 1.0),
+            };
+            *powers.entry(base).or_insert(0.0) += exp;
+        }
+        powers.retain(|_, exp| *exp != 0.0);
+        (coeff, powers)
+    }
+
+    /// Pulls the greatest common integer factor out of the terms of a sum, returning the factor
+    /// and the reduced expression.
+    ///
+    /// The outermost chain of additions and subtractions is flattened, and every term must
+    /// have an integral numeric coefficient (a bare constant or a constant times a factor).
+    /// The sign of the factor follows the first term, so the reduced expression always
+    /// starts with a positive coefficient; `-4x + 6` gives `(-2, 2x - 3)`. The reduced sum is
+    /// rebuilt like `collect_terms`, with the constant term last, but like terms are not
+    /// combined. Returns `(1.0, self.clone())` for expressions that are not sums, that have a
+    /// non-integral coefficient, or whose terms share no factor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let res = 4.0 * x.clone() + 6.0;
+    /// assert_eq!(res.factor_constant(), (2.0, 2.0 * x + 3.0));
+    /// ```
+    pub fn factor_constant(&self) -> (f64, Expr) {
+        let unchanged = (1.0, self.clone());
+        if !matches!(self, Expr::Add(_, _) | Expr::Sub(_, _)) {
+            return unchanged;
+        }
+        let mut signed = Vec::new();
+        self.signed_terms(1.0, &mut signed);
+
+        let mut terms: Vec<(Option<Expr>, f64)> = Vec::new();
+        for (sign, term) in signed {
+            let (coeff, factor) = match term {
+                Expr::Const(c) => (*c, None),
+                Expr::Rational(n, 1) => (*n as f64, None),
+                _ => {
+                    let (coeff, factor) = term.as_coeff_term();
+                    (coeff, Some(factor))
+                }
+            };
+            // Integral and small enough to be represented exactly as an i64
+            if coeff.fract() != 0.0 || coeff.abs() >= 2f64.powi(53) {
+                return unchanged;
+            }
+            terms.push((factor, sign * coeff));
+        }
+        let divisor = terms.iter().fold(0, |acc, (_, coeff)| gcd(acc, *coeff as i64));
+        let factor = if terms[0].1 < 0.0 { -divisor } else { divisor } as f64;
+        if factor == 1.0 || divisor == 0 {
+            return unchanged;
+        }
+
+        let mut constant: Option<f64> = None;
+        let mut reduced = Vec::new();
+        for (term, coeff) in terms {
+            match term {
+                Some(term) => reduced.push((term, coeff / factor)),
+                None => constant = Some(constant.unwrap_or(0.0) + coeff / factor),
+            }
+        }
+        (factor, rebuild_sum(reduced, constant.map(Expr::new_val)))
+    }
+
+    /// Splits a multiplicative term into its numeric coefficient and the remaining factor.
+    ///
+    /// The outermost chain of multiplications is flattened, its `Const` factors are
+    /// multiplied into the coefficient, and the other factors are multiplied back together
+    /// in order. A negation negates the coefficient. A bare constant `c` gives
+    /// `(c, Const(1.0))`, and a term without a numeric factor gives `(1.0, self.clone())`.
+    /// Exact rationals and named constants are kept in the factor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = 3.0 * x.clone() * y.clone();
+    /// assert_eq!(res.as_coeff_term(), (3.0, x * y));
+    /// ```
+    pub fn as_coeff_term(&self) -> (f64, Expr) {
+        match self {
+            Expr::Const(c) => (*c, Expr::new_val(1.0)),
+            Expr::Neg(expr) => {
+                let (coeff, term) = expr.as_coeff_term();
+                (-coeff, term)
+            }
+            Expr::Mul(_, _) => {
+                let mut factors = Vec::new();
+                self.push_factors(&mut factors);
+                if !factors.iter().any(|factor| factor.is_const()) {
+                    return (1.0, self.clone());
+                }
+                let coeff = factors.iter().filter_map(|factor| factor.as_const()).product();
+                let rest: Vec<&Expr> = factors.into_iter().filter(|factor| !factor.is_const()).collect();
+                let term = rest
+                    .into_iter()
+                    .cloned()
+                    .reduce(|lhs, rhs| Expr::Mul(Rc::new(lhs), Rc::new(rhs)))
+                    .unwrap_or(Expr::new_val(1.0));
+                (coeff, term)
+            }
+            _ => (1.0, self.clone()),
+        }
+    }
+
+    /// Pushes the operands of the outermost chain of multiplications onto `factors`.
+    ///
+    /// The chain is walked with an explicit stack, so long products do not overflow the call
+    /// stack.
+    fn push_factors<'a>(&'a self, factors: &mut Vec<&'a Expr>) {
+        let mut stack = vec![self];
+        while let Some(expr) = stack.pop() {
+            match expr {
+                Expr::Mul(lhs, rhs) => {
+                    stack.push(rhs);
+                    stack.push(lhs);
+                }
+                _ => factors.push(expr),
+            }
+        }
+    }
+
+    /// Returns the summands of the outermost chain of additions and subtractions, from left
+    /// to right, regardless of how the chain is grouped.
+    ///
+    /// Subtracted and negated terms are returned wrapped in `Neg`, so adding the terms back
+    /// together gives an expression equal in value to the original. An expression that is
+    /// not a sum is its own single term.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let a = Expr::new_var("a");
+    /// let b = Expr::new_var("b");
+    /// let c = Expr::new_var("c");
+    /// let res = a.clone() + (b.clone() - c.clone());
+    /// assert_eq!(res.terms(), vec![a, b, -c]);
+    /// ```
+    pub fn terms(&self) -> Vec<Expr> {
+        let mut signed = Vec::new();
+        self.signed_terms(1.0, &mut signed);
+        signed
+            .into_iter()
+            .map(|(sign, term)| if sign < 0.0 { Expr::Neg(Rc::new(term.clone())) } else { term.clone() })
+            .collect()
+    }
+
+    /// Returns the operands of the outermost chain of multiplications, from left to right,
+    /// regardless of how the chain is grouped.
+    ///
+    /// An expression that is not a product is its own single factor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let a = Expr::new_var("a");
+    /// let b = Expr::new_var("b");
+    /// let c = Expr::new_var("c");
+    /// let res = a.clone() * (b.clone() * c.clone());
+    /// assert_eq!(res.factors(), vec![a, b, c]);
+    /// ```
+    pub fn factors(&self) -> Vec<Expr> {
+        let mut factors = Vec::new();
+        self.push_factors(&mut factors);
+        factors.into_iter().cloned().collect()
+    }
+
+    /// Pushes the terms of a chain of additions and subtractions onto `terms`, each paired
+    /// with the sign (`1.0` or `-1.0`) it contributes with.
+    ///
+    /// The chain is walked with an explicit stack, so long sums do not overflow the call stack.
+    pub(crate) fn signed_terms<'a>(&'a self, sign: f64, terms: &mut Vec<(f64, &'a Expr)>) {
+        let mut stack = vec![(sign, self)];
+        while let Some((sign, expr)) = stack.pop() {
+            match expr {
+                Expr::Add(lhs, rhs) => {
+                    stack.push((sign, rhs));
+                    stack.push((sign, lhs));
+                }
+                Expr::Sub(lhs, rhs) => {
+                    stack.push((-sign, rhs));
+                    stack.push((sign, lhs));
+                }
+                Expr::Neg(expr) => stack.push((-sign, expr)),
+                _ => terms.push((sign, expr)),
+            }
+        }
+    }
+
+    /// Folds an operation whose children are all `Const` into a single `Const`.
+    ///
+    /// Because `simplify` works bottom-up, applying this at every node guarantees that any
+    /// subtree made up solely of constants collapses to one value. The arithmetic is that of
+    /// `eval` without its rounding, so small values such as `1e-20` survive. Rationals are
+    /// left to the exact folding rules, and folds producing a non-finite value are not
+    /// applied here.
+    fn fold_constants(&self) -> Option<Expr> {
+        let children = self.children();
+        if children.is_empty() || !children.iter().all(|child| child.is_const()) {
+            return None;
+        }
+        let args: Vec<f64> = children.iter().map(|child| child.get_const()).collect();
+        let res = match self {
+            // A sum or product of a constant is a multiple or power of it
+            Expr::Sum { from, to, .. } => BinaryOp::Mul.apply(args[0], range_len(*from, *to), None),
+            Expr::Product { from, to, .. } => BinaryOp::Pow.apply(args[0], range_len(*from, *to), None),
+            _ => self.eval_node(&args, &HashMap::new(), None),
+        };
+        res.ok().map(Expr::new_val)
+    }
+
     /// Checks if the current expression is a constant.
     ///
     /// Returns `true` if the current instance of `Expr` is a `Const` variant, and
@@ -139,11 +723,13 @@ impl Expr {
     /// assert_eq!(expr.is_const(), true);
     /// ```
     pub fn is_const(&self) -> bool {
-        if let Expr::Const(_) = self { true } else { false }
+        matches!(self, Expr::Const(_))
     }
 
     /// Returns the `f64` value inside the `Const` variant of `Expr`.
     ///
+    /// Prefer `as_const`, which returns `None` instead of panicking.
+    ///
     /// # Panics
     ///
     /// This function will panic if called on a non-`Const` `Expr`.
@@ -162,11 +748,749 @@ impl Expr {
             _ => panic!("Cannot call get_const on non-const Expr")
         }
     }
+
+    /// Returns the `f64` value inside the `Const` variant of `Expr`, if any.
+    ///
+    /// Returns `Some(c)` if the current instance of `Expr` is `Const(c)`, and `None`
+    /// otherwise. Other numeric variants such as `Rational` and `pi` are not converted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// assert_eq!(Expr::new_val(2.0).as_const(), Some(2.0));
+    /// assert_eq!(Expr::new_var("x").as_const(), None);
+    /// ```
+    pub fn as_const(&self) -> Option<f64> {
+        match self {
+            Expr::Const(c) => Some(*c),
+            _ => None,
+        }
+    }
+
+    /// Checks if the current expression is a numeric constant with a finite value.
+    ///
+    /// Returns `true` for a `Const` holding neither an infinity nor `NaN`, and for a
+    /// `Rational`, and `false` otherwise, including for `pi` and `e`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// assert!(Expr::new_val(2.0).is_finite_const());
+    /// assert!(Expr::new_rational(1, 3).is_finite_const());
+    /// assert!(!Expr::new_val(f64::INFINITY).is_finite_const());
+    /// assert!(!Expr::new_var("x").is_finite_const());
+    /// ```
+    pub fn is_finite_const(&self) -> bool {
+        match self {
+            Expr::Const(c) => c.is_finite(),
+            Expr::Rational(_, _) => true,
+            _ => false,
+        }
+    }
+
+    /// Checks if the current expression is a literal zero.
+    ///
+    /// Returns `true` for a zero `Const` or `Rational`, either sign of zero included, and for
+    /// the negation of such a zero, and `false` otherwise. No simplification is done, so `x - x` is
+    /// not a literal zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// assert!(Expr::new_val(0.0).is_zero());
+    /// assert!((-Expr::new_val(0.0)).is_zero());
+    /// assert!(!(Expr::new_var("x") - Expr::new_var("x")).is_zero());
+    /// ```
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Expr::Const(c) => *c == 0.0,
+            Expr::Rational(n, _) => *n == 0,
+            Expr::Neg(expr) => matches!(**expr, Expr::Const(c) if c == 0.0) || matches!(**expr, Expr::Rational(0, _)),
+            _ => false,
+        }
+    }
+
+    /// Checks if the current expression is a literal one.
+    ///
+    /// Returns `true` for a `Const` or `Rational` equal to one, and `false` otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// assert!(Expr::new_val(1.0).is_one());
+    /// assert!(Expr::new_rational(3, 3).is_one());
+    /// assert!(!Expr::new_val(-1.0).is_one());
+    /// ```
+    pub fn is_one(&self) -> bool {
+        match self {
+            Expr::Const(c) => *c == 1.0,
+            Expr::Rational(n, d) => n == d,
+            _ => false,
+        }
+    }
+}
+
+/// Checks whether `expr` is a constant or rational holding a whole number.
+fn is_integer(expr: &Expr) -> bool {
+    match expr {
+        Expr::Const(c) => c.fract() == 0.0,
+        Expr::Rational(_, d) => *d == 1,
+        _ => false,
+    }
+}
+
+/// Returns the number of integers from `from` to `to` inclusive, which is zero if `from > to`.
+fn range_len(from: i64, to: i64) -> f64 {
+    (to as i128 - from as i128 + 1).max(0) as f64
+}
+
+/// Returns the negation of a numeric constant.
+pub(crate) fn negate_number(num: &Expr) -> Expr {
+    match num {
+        Expr::Const(c) => Expr::new_val(-c),
+        Expr::Rational(n, d) => Expr::Rational(-n, *d),
+        _ => Expr::Neg(Rc::new(num.clone())),
+    }
+}
+
+/// Rebuilds a chain of additions and subtractions from coefficient and factor pairs, followed
+/// by an optional constant term.
+fn rebuild_sum(terms: Vec<(Expr, f64)>, constant: Option<Expr>) -> Expr {
+    let mut acc: Option<Expr> = None;
+    for (factor, coeff) in terms.into_iter().filter(|(_, coeff)| *coeff != 0.0) {
+        let scaled = |c: f64| {
+            if c == 1.0 { factor.clone() } else { Expr::Mul(Rc::new(Expr::new_val(c)), Rc::new(factor.clone())) }
+        };
+        acc = Some(match acc {
+            None if coeff == -1.0 => Expr::Neg(Rc::new(factor)),
+            None => scaled(coeff),
+            Some(acc) if coeff < 0.0 => Expr::Sub(Rc::new(acc), Rc::new(scaled(-coeff))),
+            Some(acc) => Expr::Add(Rc::new(acc), Rc::new(scaled(coeff))),
+        });
+    }
+    match (acc, constant) {
+        (None, None) => Expr::new_val(0.0),
+        (None, Some(c)) => c,
+        (Some(acc), None) => acc,
+        (Some(acc), Some(Expr::Const(0.0))) => acc,
+        (Some(acc), Some(c)) if matches!(c, Expr::Const(v) if v < 0.0) || matches!(c, Expr::Rational(n, _) if n < 0) =>
+            Expr::Sub(Rc::new(acc), Rc::new(negate_number(&c))),
+        (Some(acc), Some(c)) => Expr::Add(Rc::new(acc), Rc::new(c)),
+    }
+}
+
+/// Orders monomials, given as bases sorted in canonical order with their exponents, by
+/// descending total degree and then lexicographically, so `x^2` comes before `x * y`, which
+/// comes before `y^2`.
+fn graded_lex(lhs: &[(Expr, f64)], rhs: &[(Expr, f64)]) -> Ordering {
+    let degree = |powers: &[(Expr, f64)]| powers.iter().map(|(_, exp)| exp).sum::<f64>();
+    degree(rhs).total_cmp(&degree(lhs)).then_with(|| {
+        for ((lhs_base, lhs_exp), (rhs_base, rhs_exp)) in lhs.iter().zip(rhs) {
+            // The monomial with the earlier base has the higher power of it
+            let order = lhs_base.cmp(rhs_base).then_with(|| rhs_exp.total_cmp(lhs_exp));
+            if order != Ordering::Equal {
+                return order;
+            }
+        }
+        rhs.len().cmp(&lhs.len())
+    })
+}
+
+/// Returns `true` if `expr` is a negative constant or rational that can be negated exactly.
+pub(crate) fn is_negative_number(expr: &Expr) -> bool {
+    matches!(expr, Expr::Const(c) if *c < 0.0) || matches!(expr, Expr::Rational(n, _) if *n < 0 && *n != i64::MIN)
+}
+
+/// Removes the factors shared by the products `lhs` and `rhs` from a division `lhs / rhs`,
+/// returning `None` if they have none in common.
+///
+/// Zero factors are never cancelled, so `(0 * x) / 0` keeps its undefined form.
+fn cancel_common_factors(lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+    let mut num = Vec::new();
+    lhs.push_factors(&mut num);
+    let mut den = Vec::new();
+    rhs.push_factors(&mut den);
+
+    let mut cancelled = false;
+    num.retain(|factor| {
+        match den.iter().position(|other| other == factor && !factor.is_zero()) {
+            Some(i) => {
+                den.remove(i);
+                cancelled = true;
+                false
+            }
+            None => true,
+        }
+    });
+    if !cancelled {
+        return None;
+    }
+
+    let product = |factors: Vec<&Expr>| {
+        factors.into_iter().cloned().reduce(|acc, factor| Expr::Mul(Rc::new(acc), Rc::new(factor)).simplify_node())
+    };
+    let num = product(num).unwrap_or(Expr::Const(1.0));
+    Some(match product(den) {
+        Some(den) => Expr::Div(Rc::new(num), Rc::new(den)).simplify_node(),
+        None => num,
+    })
+}
+
+/// Returns `true` if `lhs` is `sin(a)^2` and `rhs` is `cos(b)^2` with `a` and `b` equal up to
+/// canonical ordering.
+fn is_pythagorean_pair(lhs: &Expr, rhs: &Expr) -> bool {
+    let two = Expr::new_val(2.0);
+    match (lhs, rhs) {
+        (Expr::Pow(sin, p1), Expr::Pow(cos, p2)) if **p1 == two && **p2 == two => match (&**sin, &**cos) {
+            (Expr::Sin(a), Expr::Cos(b)) => a == b || a.canonicalize() == b.canonicalize(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns the floating point value of the rational `num / den`.
+fn ratio(num: i64, den: i64) -> f64 {
+    num as f64 / den as f64
+}
+
+/// Builds the result of folding two rationals from a checked numerator and denominator.
+///
+/// Falls back to the floating point `fallback` value if the integer arithmetic overflowed.
+/// A result whose reduced denominator is one is returned as an integral `Const` so the
+/// constant-based rules (such as `x + 0`) continue to apply.
+fn checked_rational(num: Option<i64>, den: Option<i64>, fallback: f64) -> Expr {
+    match (num, den) {
+        (Some(n), Some(d)) if d != 0 => match Expr::new_rational(n, d) {
+            Expr::Rational(n, 1) => Expr::new_val(n as f64),
+            rational => rational,
+        },
+        _ => Expr::new_val(fallback),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::symbol::Symbol;
+
+    #[test]
+    fn pythagorean_identity() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let two = Expr::new_val(2.0);
+
+        let res = x.clone().sin().pow(two.clone()) + x.clone().cos().pow(two.clone());
+        assert_eq!(res.simplify(), Expr::new_val(1.0));
+        let res = x.clone().cos().pow(two.clone()) + x.clone().sin().pow(two.clone());
+        assert_eq!(res.simplify(), Expr::new_val(1.0));
+        let arg = x.clone() * y.clone();
+        let res = arg.clone().sin().pow(two.clone()) + (y.clone() * x.clone()).cos().pow(two.clone());
+        assert_eq!(res.simplify(), Expr::new_val(1.0));
+    }
+
+    #[test]
+    fn pythagorean_identity_within_sum() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let two = Expr::new_val(2.0);
+        let sin2 = x.clone().sin().pow(two.clone());
+        let cos2 = x.clone().cos().pow(two.clone());
+
+        let res = y.clone() + sin2.clone() + cos2.clone();
+        assert_eq!(res.simplify(), y.clone() + 1.0);
+        let res = cos2.clone() + y.clone() - 4.0 + sin2.clone();
+        assert_eq!(res.simplify(), y.clone() - 3.0);
+        let res = 3.0 * sin2.clone() + y.clone() + 3.0 * cos2.clone();
+        assert_eq!(res.simplify(), y.clone() + 3.0);
+        // Different coefficients leave both terms in place
+        let res = 2.0 * sin2.clone() + y.clone() + cos2.clone();
+        assert_eq!(res.simplify(), 2.0 * sin2 + y + cos2);
+    }
+
+    #[test]
+    fn pythagorean_mismatched_arguments() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let two = Expr::new_val(2.0);
+
+        let res = x.clone().sin().pow(two.clone()) + y.clone().cos().pow(two.clone());
+        assert_eq!(res.simplify(), res);
+        let res = x.clone().sin().pow(two.clone()) + x.clone().sin().pow(two.clone());
+        assert_eq!(res.simplify(), Expr::new_val(2.0) * x.clone().sin().pow(two.clone()));
+        let res = x.clone().sin().pow(Expr::new_val(3.0)) + x.clone().cos().pow(two);
+        assert_eq!(res.simplify(), res);
+    }
+
+    #[test]
+    fn factor_constant_common_factor() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((4.0 * x.clone() + 6.0).factor_constant(), (2.0, 2.0 * x.clone() + 3.0));
+        let res = 6.0 * x.clone() - 9.0 * y.clone() + 3.0;
+        assert_eq!(res.factor_constant(), (3.0, 2.0 * x.clone() - 3.0 * y.clone() + 1.0));
+        assert_eq!((x.clone() * 5.0 + 10.0).factor_constant(), (5.0, x.clone() + 2.0));
+    }
+
+    #[test]
+    fn factor_constant_negative_leading() {
+        let x = Expr::new_var("x");
+
+        assert_eq!((-4.0 * x.clone() + 6.0).factor_constant(), (-2.0, 2.0 * x.clone() - 3.0));
+        assert_eq!((-(3.0 * x.clone()) - 6.0).factor_constant(), (-3.0, x.clone() + 2.0));
+        assert_eq!((1.0 - x.clone()).factor_constant(), (1.0, 1.0 - x.clone()));
+    }
+
+    #[test]
+    fn factor_constant_no_factor() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        let res = 2.0 * x.clone() + 3.0;
+        assert_eq!(res.factor_constant(), (1.0, res.clone()));
+        let res = 2.0 * x.clone() + 1.5;
+        assert_eq!(res.factor_constant(), (1.0, res.clone()));
+        let res = 4.0 * x.clone() * y.clone();
+        assert_eq!(res.factor_constant(), (1.0, res.clone()));
+        let res = x.clone() - x.clone();
+        assert_eq!(res.factor_constant(), (1.0, res.clone()));
+    }
+
+    #[test]
+    fn coeff_term_constant_and_symbol() {
+        let x = Expr::new_var("x");
+
+        assert_eq!(Expr::new_val(4.0).as_coeff_term(), (4.0, Expr::new_val(1.0)));
+        assert_eq!(x.as_coeff_term(), (1.0, x.clone()));
+        assert_eq!((-x.clone()).as_coeff_term(), (-1.0, x.clone()));
+        assert_eq!(x.clone().sin().as_coeff_term(), (1.0, x.sin()));
+    }
+
+    #[test]
+    fn coeff_term_nested_product() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((3.0 * x.clone() * y.clone()).as_coeff_term(), (3.0, x.clone() * y.clone()));
+        assert_eq!((x.clone() * (2.0 * y.clone()) * 5.0).as_coeff_term(), (10.0, x.clone() * y.clone()));
+        assert_eq!((-(x.clone() * 2.0)).as_coeff_term(), (-2.0, x.clone()));
+        assert_eq!((Expr::new_val(2.0) * Expr::new_val(3.0)).as_coeff_term(), (6.0, Expr::new_val(1.0)));
+        let res = x.clone() * (y.clone() * x.clone());
+        assert_eq!(res.as_coeff_term(), (1.0, res.clone()));
+    }
+
+    #[test]
+    fn collect_terms_inner_coefficients() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = x.clone() * 3.0 * y.clone() + 2.0 * y.clone() * x.clone();
+
+        assert_eq!(res.collect_terms(), Expr::new_val(5.0) * (x * y));
+    }
+
+    #[test]
+    fn sum_of_products_identifies_polynomials() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let two = Expr::new_val(2.0);
+
+        let lhs = (x.clone() + 1.0) * (x.clone() - 1.0);
+        let rhs = x.clone().pow(two.clone()) - 1.0;
+        assert_eq!(lhs.to_sum_of_products(), rhs.to_sum_of_products());
+        assert_eq!(lhs.to_sum_of_products(), x.clone().pow(two.clone()) - 1.0);
+
+        let lhs = (x.clone() + y.clone()).pow(two.clone());
+        let rhs = y.clone() * y.clone() + x.clone() * y.clone() * 2.0 + x.clone().pow(two.clone());
+        assert_eq!(lhs.to_sum_of_products(), rhs.to_sum_of_products());
+        assert_eq!(lhs.to_sum_of_products(), x.clone().pow(two.clone()) + Expr::new_val(2.0) * (x.clone() * y.clone()) + y.clone().pow(two.clone()));
+    }
+
+    #[test]
+    fn sum_of_products_merges_powers() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((x.clone() * 3.0 * x.clone().pow(Expr::new_val(2.0))).to_sum_of_products(), Expr::new_val(3.0) * x.clone().pow(Expr::new_val(3.0)));
+        assert_eq!((y.clone().sin() * x.clone() - x.clone() * y.clone().sin()).to_sum_of_products(), Expr::new_val(0.0));
+        assert_eq!((-(x.clone() * 2.0) + 5.0).to_sum_of_products(), Expr::new_val(-2.0) * x.clone() + 5.0);
+        assert_eq!(Expr::new_val(4.0).to_sum_of_products(), Expr::new_val(4.0));
+    }
+
+    #[test]
+    fn simplify_mut_matches_simplify() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let exprs = [
+            (x.clone() + x.clone() + y.clone() * y.clone()).pow(Expr::new_var("z")),
+            (3.0 * x.clone() - x.clone()) / (x.clone() * 1.0),
+            Expr::E.pow(y.clone().ln()) * (Expr::new_val(2.0) + 3.0),
+            x.clone().sin().pow(Expr::new_val(2.0)) + x.clone().cos().pow(Expr::new_val(2.0)),
+            Expr::new_val(1.0) / 0.0 + x.clone() * 0.0,
+            x.clone(),
+        ];
+
+        for expr in exprs {
+            let mut res = expr.clone();
+            res.simplify_mut();
+            assert_eq!(res, expr.simplify());
+        }
+    }
+
+    #[test]
+    fn simplify_mut_leaves_clones_unchanged() {
+        let x = Expr::new_var("x");
+        let inner = x.clone() * 1.0 + 0.0;
+        let original = inner.clone() * inner.clone();
+        let mut res = original.clone();
+        res.simplify_mut();
+
+        assert_eq!(res, x.clone().pow(Expr::new_val(2.0)));
+        assert_eq!(original, (x.clone() * 1.0 + 0.0) * (x.clone() * 1.0 + 0.0));
+        assert_eq!(inner, x * 1.0 + 0.0);
+    }
+
+    #[test]
+    fn simplify_mut_deep_chain() {
+        let mut res = Expr::new_val(1.0);
+        for _ in 0..100_000 {
+            res += 1.0;
+        }
+        res.simplify_mut();
+        assert_eq!(res, Expr::new_val(100_001.0));
+
+        let x = Expr::new_var("x");
+        let mut res = x.clone();
+        for _ in 0..100_000 {
+            res = (res * 1.0).sin();
+        }
+        res.simplify_mut();
+        assert_eq!(res.depth(), 100_001);
+    }
+
+    #[test]
+    fn sub_like_terms() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((3.0 * x.clone() - x.clone()).simplify(), 2.0 * x.clone());
+        assert_eq!((x.clone() - x.clone()).simplify(), Expr::new_val(0.0));
+        assert_eq!((5.0 * y.clone() - 2.0 * y.clone()).simplify(), 3.0 * y.clone());
+        assert_eq!((x.clone() - x.clone() * 3.0).simplify(), -2.0 * x.clone());
+        assert_eq!((x.clone() * 2.5 - 1.5 * x.clone()).simplify(), x.clone());
+        assert_eq!((3.0 * x.clone() - x.clone() * 3.0).simplify(), Expr::new_val(0.0));
+        assert_eq!((2.0 * (x.clone() * y.clone()) - x.clone() * y.clone()).simplify(), x.clone() * y.clone());
+        assert_eq!((x.clone().sin() - x.clone().sin()).simplify(), Expr::new_val(0.0));
+        // Unlike terms are kept
+        assert_eq!((3.0 * x.clone() - y.clone()).simplify(), 3.0 * x.clone() - y.clone());
+    }
+
+    #[test]
+    fn sub_from_zero() {
+        let y = Expr::new_var("y");
+
+        assert_eq!((Expr::new_val(0.0) - y.clone()).simplify(), -y.clone());
+        assert_eq!((y.clone() - 0.0).simplify(), y.clone());
+        assert_eq!((Expr::new_val(0.0) - 2.0).simplify(), Expr::new_val(-2.0));
+    }
+
+    #[test]
+    fn zero_to_the_zero_agrees_with_eval() {
+        let x = Expr::new_var("x");
+        let zero = Expr::new_val(0.0);
+        let mut vars = HashMap::new();
+        vars.insert(Symbol::new("x"), 0.0);
+
+        assert_eq!(zero.clone().pow(zero.clone()).simplify(), Expr::new_val(1.0));
+        assert_eq!(x.clone().pow(zero.clone()).simplify(), Expr::new_val(1.0));
+        assert_eq!(x.clone().pow(zero.clone()).eval(&vars).unwrap(), 1.0);
+        assert_eq!((x.clone() - x.clone()).pow(zero.clone()).simplify(), Expr::new_val(1.0));
+        assert_eq!((x.clone() - x.clone()).pow(zero).eval(&vars).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn as_const_only_for_constants() {
+        let x = Expr::new_var("x");
+
+        assert_eq!(Expr::new_val(-1.5).as_const(), Some(-1.5));
+        assert_eq!(x.as_const(), None);
+        assert_eq!((x.clone() + 1.0).as_const(), None);
+        assert_eq!((Expr::new_val(1.0) + 2.0).as_const(), None);
+        assert_eq!(Expr::new_val(2.0).sin().as_const(), None);
+        assert_eq!(Expr::new_rational(1, 2).as_const(), None);
+        assert_eq!(Expr::Pi.as_const(), None);
+        assert_eq!((Expr::new_val(1.0) + 2.0).simplify().as_const(), Some(3.0));
+    }
+
+    #[test]
+    fn like_terms_with_symbolic_coefficient() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((3.0 * x.clone() + x.clone()).simplify(), 4.0 * x.clone());
+        assert_eq!((x.clone() * 3.0 + x.clone()).simplify(), 4.0 * x.clone());
+        // A symbolic coefficient is left to the like-term collection
+        assert_eq!((y.clone() * x.clone() + x.clone()).simplify(), (y.clone() * x.clone() + x.clone()).collect_terms());
+    }
+
+    #[test]
+    fn constant_predicates() {
+        let x = Expr::new_var("x");
+
+        assert!(Expr::new_val(-2.5).is_finite_const());
+        assert!(!Expr::new_val(f64::NAN).is_finite_const());
+        assert!(!Expr::new_val(f64::NEG_INFINITY).is_finite_const());
+        assert!(!Expr::Pi.is_finite_const());
+        assert!(!(x.clone() + 1.0).is_finite_const());
+
+        assert!(Expr::new_val(0.0).is_zero());
+        assert!(Expr::new_val(-0.0).is_zero());
+        assert!(Expr::new_rational(0, 5).is_zero());
+        assert!((-Expr::new_val(0.0)).is_zero());
+        assert!(!(-(-Expr::new_val(0.0))).is_zero());
+        assert!(!Expr::new_val(f64::NAN).is_zero());
+        assert!(!x.is_zero());
+        assert!(!(x.clone() * 0.0).is_zero());
+
+        assert!(Expr::new_val(1.0).is_one());
+        assert!(Expr::new_rational(1, 1).is_one());
+        assert!(!Expr::new_rational(1, 2).is_one());
+        assert!(!(-Expr::new_val(-1.0)).is_one());
+        assert!(!x.is_one());
+    }
+
+    #[test]
+    fn terms_flatten_grouping() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+
+        assert_eq!(((a.clone() + b.clone()) + c.clone()).terms(), vec![a.clone(), b.clone(), c.clone()]);
+        assert_eq!((a.clone() + (b.clone() + c.clone())).terms(), vec![a.clone(), b.clone(), c.clone()]);
+        assert_eq!((a.clone() - (b.clone() - c.clone())).terms(), vec![a.clone(), -b.clone(), c.clone()]);
+        assert_eq!((a.clone() * b.clone()).terms(), vec![a.clone() * b.clone()]);
+    }
+
+    #[test]
+    fn factors_flatten_grouping() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+
+        assert_eq!(((a.clone() * b.clone()) * c.clone()).factors(), vec![a.clone(), b.clone(), c.clone()]);
+        assert_eq!((a.clone() * (b.clone() + c.clone())).factors(), vec![a.clone(), b.clone() + c.clone()]);
+        assert_eq!(a.factors(), vec![a.clone()]);
+    }
+
+    #[test]
+    fn terms_deep_chain() {
+        let x = Expr::new_var("x");
+        let mut res = x.clone();
+        for _ in 0..100_000 {
+            res += x.clone();
+        }
+
+        assert_eq!(res.terms().len(), 100_001);
+    }
+
+    #[test]
+    fn log_rules() {
+        let x = Expr::new_var("x");
+
+        assert_eq!(Expr::E.ln().simplify(), Expr::new_val(1.0));
+        assert_eq!(Expr::new_val(1.0).ln().simplify(), Expr::new_val(0.0));
+        assert_eq!(
+            x.clone().pow(Expr::new_val(3.0)).ln().simplify(),
+            Expr::new_val(3.0) * x.clone().ln()
+        );
+        assert_eq!(x.clone().ln().exp().simplify(), x.clone());
+        assert_eq!((x.clone() * 2.0).ln().exp().simplify(), x.clone() * 2.0);
+    }
+
+    #[test]
+    fn log_of_even_power_keeps_negative_base() {
+        let x = Expr::new_var("x");
+        let res = x.clone().pow(Expr::new_val(2.0)).ln();
+        let simplified = res.simplify();
+
+        assert_eq!(simplified, Expr::new_val(2.0) * x.clone().abs().ln());
+        let vars = HashMap::from([(Symbol::new("x"), -2.5)]);
+        assert!((simplified.eval(&vars).unwrap() - res.eval(&vars).unwrap()).abs() < 1e-12);
+        assert_eq!(x.clone().pow(Expr::new_val(4.0)).ln().simplify(), Expr::new_val(4.0) * x.abs().ln());
+    }
+
+    #[test]
+    fn log_product_not_split() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((x.clone() * y.clone()).ln().simplify(), (x.clone() * y.clone()).ln());
+        assert_eq!(x.clone().pow(y.clone()).ln().simplify(), x.clone().pow(y.clone()).ln());
+        assert_eq!(x.clone().exp().ln().simplify(), x.exp().ln());
+    }
+
+    #[test]
+    fn constant_folding_is_unrounded() {
+        let x = Expr::new_var("x");
+        let tiny = Expr::new_val(1e-20) * 2.0;
+
+        assert_eq!(tiny.simplify(), Expr::new_val(2e-20));
+        assert_eq!((tiny + x.clone()).simplify(), x.clone() + 2e-20);
+        assert_eq!((Expr::new_val(1.0) / Expr::new_val(3.0)).simplify(), Expr::new_val(1.0 / 3.0));
+        assert_eq!(Expr::new_val(2.0).summation(&Symbol::new("i"), 1, 3).simplify(), Expr::new_val(6.0));
+        assert_eq!(Expr::new_val(2.0).product(&Symbol::new("i"), 1, 3).simplify(), Expr::new_val(8.0));
+    }
+
+    #[test]
+    fn division_by_zero_left_unfolded() {
+        let one = Expr::new_val(1.0);
+        let zero = Expr::new_val(0.0);
+
+        assert_eq!((one.clone() / zero.clone()).simplify(), one.clone() / zero.clone());
+        assert_eq!((zero.clone() / zero.clone()).simplify(), zero.clone() / zero.clone());
+        assert_eq!((Expr::new_rational(1, 2) / zero.clone()).simplify(), Expr::new_rational(1, 2) / zero.clone());
+        let x = Expr::new_var("x");
+        assert_eq!((x.clone() + one.clone() / zero.clone()).simplify(), x + one / zero);
+    }
+
+    #[test]
+    fn division_cancels_to_one() {
+        let x = Expr::new_var("x");
+        let zero = Expr::new_val(0.0);
+
+        assert_eq!((x.clone() / x.clone()).simplify(), Expr::new_val(1.0));
+        assert_eq!((x.clone().sin() / x.clone().sin()).simplify(), Expr::new_val(1.0));
+        assert_eq!((zero.clone() / zero.clone()).simplify(), zero.clone() / zero.clone());
+        assert_eq!((Expr::new_rational(0, 1) / Expr::new_rational(0, 1)).simplify(), Expr::new_rational(0, 1) / Expr::new_rational(0, 1));
+    }
+
+    #[test]
+    fn division_cancels_common_factors() {
+        let a = Expr::new_var("a");
+        let b = Expr::new_var("b");
+        let c = Expr::new_var("c");
+
+        assert_eq!(((a.clone() * b.clone()) / b.clone()).simplify(), a.clone());
+        assert_eq!(((a.clone() * b.clone()) / a.clone()).simplify(), b.clone());
+        assert_eq!((a.clone() / (a.clone() * b.clone())).simplify(), Expr::new_val(1.0) / b.clone());
+        assert_eq!(((a.clone() * b.clone() * c.clone()) / (c.clone() * a.clone())).simplify(), b.clone());
+        assert_eq!(((a.clone() * b.clone()) / c.clone()).simplify(), (a.clone() * b.clone()) / c.clone());
+    }
+
+    #[test]
+    fn division_by_one() {
+        let x = Expr::new_var("x");
+
+        assert_eq!((x.clone() / 1.0).simplify(), x.clone());
+        assert_eq!((1.0 / x.clone()).simplify(), 1.0 / x.clone());
+    }
+
+    #[test]
+    fn power_of_power_folds_exponent() {
+        let x = Expr::new_var("x");
+        let n = Expr::new_var("n");
+
+        assert_eq!(x.clone().pow(Expr::new_val(2.0)).pow(Expr::new_val(3.0)).simplify(), x.clone().pow(Expr::new_val(6.0)));
+        assert_eq!(x.clone().pow(Expr::new_val(0.5)).pow(Expr::new_val(2.0)).simplify(), x.clone());
+        assert_eq!(x.clone().pow(Expr::new_val(2.0)).pow(Expr::new_val(3.0)).pow(Expr::new_val(2.0)).simplify(), x.clone().pow(Expr::new_val(12.0)));
+        // A symbolic outer exponent might be fractional, so it is not folded
+        assert_eq!(x.clone().pow(n.clone()).pow(n.clone()).simplify(), x.clone().pow(n.clone()).pow(n.clone()));
+    }
+
+    #[test]
+    fn power_of_power_fractional_outer_exponent() {
+        let x = Expr::new_var("x");
+        let vars = HashMap::from([(Symbol::new("x"), -2.5)]);
+        let root = x.clone().pow(Expr::new_val(2.0)).pow(Expr::new_val(0.5));
+
+        assert_eq!(root.simplify(), x.clone().abs());
+        assert_eq!(root.simplify().eval(&vars).unwrap(), root.eval(&vars).unwrap());
+        assert_eq!(x.clone().pow(Expr::new_val(0.5)).simplify(), x.clone().sqrt());
+        let quarter = x.clone().pow(Expr::new_val(2.0)).pow(Expr::new_val(0.25));
+        assert_eq!(quarter.simplify(), quarter);
+    }
+
+    #[test]
+    fn negative_exponent_to_reciprocal() {
+        let x = Expr::new_var("x");
+        let one = Expr::new_val(1.0);
+
+        assert_eq!(x.clone().pow(Expr::new_val(-2.0)).simplify(), one.clone() / x.clone().pow(Expr::new_val(2.0)));
+        assert_eq!(x.clone().pow(Expr::new_val(-1.0)).simplify(), one.clone() / x.clone());
+        assert_eq!(x.clone().pow(Expr::new_rational(-1, 2)).simplify(), one.clone() / x.clone().sqrt());
+        assert_eq!(x.clone().pow(Expr::new_val(2.0)).pow(Expr::new_val(-3.0)).simplify(), one.clone() / x.clone().pow(Expr::new_val(6.0)));
+        assert_eq!(Expr::new_val(2.0).pow(Expr::new_val(-1.0)).simplify(), Expr::new_val(0.5));
+        assert_eq!(x.clone().pow(-x.clone()).simplify(), x.clone().pow(-x.clone()));
+    }
+
+    #[test]
+    fn simplify_fully_reaches_fixed_point() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = Expr::E.pow(y.clone().ln()) / y.clone() + x.clone();
+
+        assert_ne!(res.simplify(), res.simplify().simplify());
+        let full = res.simplify_fully();
+        assert_eq!(full, x.clone() + 1.0);
+        assert_eq!(full.simplify(), full);
+
+        let nested = Expr::E.pow(Expr::E.pow(y.clone().ln()).ln());
+        assert_eq!(nested.simplify_fully(), y.clone());
+        assert_eq!((x.clone() + 2.0).simplify_fully(), x.clone() + 2.0);
+    }
+
+    #[test]
+    fn simplify_deep_trees() {
+        let mut res = Expr::new_val(1.0);
+        for _ in 0..100_000 {
+            res += 1.0;
+        }
+        assert_eq!(res.simplify(), Expr::new_val(100_001.0));
+
+        let x = Expr::new_var("x");
+        let mut res = x.clone();
+        for _ in 0..100_000 {
+            res = res.sin();
+        }
+        assert_eq!(res.simplify().depth(), 100_001);
+    }
+
+    #[test]
+    fn simplify_double_negation() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((-(-x.clone())).simplify(), x.clone());
+        assert_eq!((-(-(-x.clone()))).simplify(), -x.clone());
+        assert_eq!((-(-(x.clone() + y.clone()))).simplify(), x.clone() + y.clone());
+        assert_eq!((-(x.clone() - y.clone())).simplify(), y.clone() - x.clone());
+        assert_eq!((-(x.clone() + y.clone())).simplify(), -(x.clone() + y.clone()));
+        assert_eq!((-(3.0 * x.clone())).simplify(), -3.0 * x.clone());
+        assert_eq!((-(x.clone() * -1.0)).simplify(), x.clone());
+
+        let mut res = x.clone();
+        for _ in 0..100_001 {
+            res = -(res * 1.0);
+        }
+        assert_eq!(res.simplify(), -x);
+    }
+
+    #[test]
+    fn simplify_negated_constants() {
+        assert_eq!((-Expr::new_val(2.0)).simplify(), Expr::new_val(-2.0));
+        assert_eq!((-Expr::new_val(-2.0)).simplify(), Expr::new_val(2.0));
+        assert_eq!((-Expr::new_rational(1, 3)).simplify(), Expr::Rational(-1, 3));
+        assert_eq!((-Expr::Rational(i64::MIN, 1)).simplify(), -Expr::Rational(i64::MIN, 1));
+        assert_eq!((-Expr::Pi).simplify(), -Expr::Pi);
+    }
 
     #[test]
     fn add_const() {
@@ -212,4 +1536,136 @@ mod tests {
 
         assert_eq!(res.simplify(), Expr::new_val(2.0) * Expr::new_var("x"));
     }
+
+    #[test]
+    fn fold_nested_constants() {
+        let x = Expr::new_var("x");
+        let res = x.clone() + (2.0 * (Expr::new_val(3.0) + 4.0));
+        assert_eq!(res.simplify(), x.clone() + Expr::new_val(14.0));
+
+        let res = (Expr::new_val(1.0) + 2.0) + 3.0;
+        assert_eq!(res.simplify(), Expr::new_val(6.0));
+
+        let res = (Expr::new_val(2.0) + 2.0) * (Expr::new_val(1.0) + 1.0).pow(Expr::new_val(3.0));
+        assert_eq!(res.simplify(), Expr::new_val(32.0));
+
+        let res = x.clone() * -(Expr::new_val(0.0).cos() + 1.0);
+        assert_eq!(res.simplify(), x.clone() * Expr::new_val(-2.0));
+
+        let res = x.clone().pow(Expr::new_val(2.0)) * x.clone().pow(Expr::new_val(3.0));
+        assert_eq!(res.simplify(), x.pow(Expr::new_val(5.0)));
+    }
+
+    #[test]
+    fn collect_three_terms() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = x.clone() + y.clone() + x.clone();
+
+        assert_eq!(res.simplify(), Expr::new_val(2.0) * x.clone() + y.clone());
+        assert_eq!(res.simplify().to_string(), "2x + y");
+    }
+
+    #[test]
+    fn collect_four_terms() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = x.clone() + y.clone() + x.clone() + y.clone();
+        assert_eq!(res.simplify(), Expr::new_val(2.0) * x.clone() + Expr::new_val(2.0) * y.clone());
+
+        let res = 3.0 * x.clone() - y.clone() + x.clone() - 2.0 * y.clone();
+        assert_eq!(res.simplify(), Expr::new_val(4.0) * x.clone() - Expr::new_val(3.0) * y.clone());
+    }
+
+    #[test]
+    fn collect_negative_coefficients() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = -y.clone() + x.clone() - (-x.clone()) - y.clone();
+        assert_eq!(res.simplify(), Expr::new_val(-2.0) * y.clone() + Expr::new_val(2.0) * x.clone());
+
+        let res = x.clone() + 1.0 + y.clone() - 3.0 + x.clone();
+        assert_eq!(res.simplify(), Expr::new_val(2.0) * x.clone() + y.clone() - Expr::new_val(2.0));
+    }
+
+    #[test]
+    fn cancel_additive_inverses() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+
+        assert_eq!((x.clone() - x.clone()).simplify(), Expr::new_val(0.0));
+        assert_eq!((x.clone() + (-x.clone())).simplify(), Expr::new_val(0.0));
+        assert_eq!((x.clone() + y.clone() - x.clone()).simplify(), y.clone());
+        assert_eq!((2.0 * x.clone() - x.clone() - x.clone()).simplify(), Expr::new_val(0.0));
+        assert_eq!((x.clone() + 1.0 - x.clone() - 1.0).simplify(), Expr::new_val(0.0));
+        assert_eq!((x.clone() + 2.0 - x.clone()).simplify(), Expr::new_val(2.0));
+    }
+
+    #[test]
+    fn rational_arithmetic() {
+        let third = Expr::new_rational(1, 3);
+        let sixth = Expr::new_rational(1, 6);
+
+        assert_eq!((third.clone() + sixth.clone()).simplify(), Expr::Rational(1, 2));
+        assert_eq!((third.clone() - sixth.clone()).simplify(), Expr::Rational(1, 6));
+        assert_eq!((third.clone() * sixth.clone()).simplify(), Expr::Rational(1, 18));
+        assert_eq!((third.clone() / sixth.clone()).simplify(), Expr::new_val(2.0));
+        assert_eq!((third.clone() + third.clone() + third.clone()).simplify(), Expr::new_val(1.0));
+    }
+
+    #[test]
+    fn rational_mixed_with_const() {
+        let half = Expr::new_rational(1, 2);
+
+        assert_eq!((half.clone() + Expr::new_val(0.25)).simplify(), Expr::new_val(0.75));
+        assert_eq!((Expr::new_val(1.0) - half.clone()).simplify(), Expr::new_val(0.5));
+        assert_eq!((half.clone() * Expr::new_val(3.0)).simplify(), Expr::new_val(1.5));
+        assert_eq!((Expr::new_val(1.0) / half).simplify(), Expr::new_val(2.0));
+    }
+
+    #[test]
+    fn rational_overflow_falls_back_to_float() {
+        let tiny = Expr::new_rational(1, i64::MAX);
+        assert!((tiny.clone() * tiny).simplify().is_const());
+    }
+
+    #[test]
+    fn simplify_sqrt() {
+        let x = Expr::new_var("x");
+        let a = Expr::new_var("a");
+
+        assert_eq!(Expr::new_val(4.0).sqrt().simplify(), Expr::new_val(2.0));
+        assert_eq!(x.clone().pow(Expr::new_val(2.0)).sqrt().simplify(), x.clone().abs());
+        assert_eq!((a.clone() * a.clone()).sqrt().simplify(), a.abs());
+        assert_eq!(Expr::new_val(-4.0).sqrt().simplify(), Expr::new_val(-4.0).sqrt());
+        assert_eq!(x.clone().sqrt().simplify(), x.sqrt());
+    }
+
+    #[test]
+    fn simplify_rational_square_root() {
+        let x = Expr::new_var("x");
+
+        assert_eq!(x.clone().pow(Expr::new_rational(1, 2)).simplify(), x.clone().sqrt());
+        assert_eq!(x.clone().pow(Expr::new_val(2.0)).pow(Expr::new_rational(1, 2)).simplify(), x.clone().abs());
+        assert_eq!(Expr::new_val(9.0).pow(Expr::new_rational(1, 2)).simplify(), Expr::new_val(3.0));
+        assert_eq!(x.clone().pow(Expr::new_rational(1, 3)).simplify(), x.pow(Expr::new_rational(1, 3)));
+    }
+
+    #[test]
+    fn simplify_named_constants() {
+        let x = Expr::new_var("x");
+
+        assert_eq!((Expr::Pi + Expr::Pi).simplify(), Expr::new_val(2.0) * Expr::Pi);
+        assert_eq!((Expr::Pi * 2.0).simplify(), Expr::Pi * 2.0);
+        assert_eq!(Expr::E.pow(x.clone()).simplify(), x.clone().exp());
+        assert_eq!(Expr::E.pow(x.clone().ln()).simplify(), x.ln().exp());
+    }
+
+    #[test]
+    fn simplify_function_argument() {
+        let x = Expr::new_var("x");
+        let res = (x.clone() + x.clone()).sin();
+
+        assert_eq!(res.simplify(), (Expr::new_val(2.0) * x).sin());
+    }
 }
@@ -0,0 +1,378 @@
+use crate::expr::Expr;
+use crate::symbol::Symbol;
+
+impl Expr {
+    /// Replaces every occurrence of the symbol `var` with `value`, returning a new expression.
+    ///
+    /// The substituted expression is not evaluated or simplified. If `var` does not appear in
+    /// the expression, the result is structurally equal to the original. The index of a sum
+    /// or product is bound inside its body, so it is not replaced there, and if `value`
+    /// mentions the index, the index is first renamed to a fresh symbol so that `value` keeps
+    /// referring to the outer variable.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `var` - The symbol to replace.
+    /// * `value` - The expression to put in place of `var`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let t = Expr::new_var("t");
+    /// let res = x.clone().pow(Expr::new_val(2.0)).subs(&Symbol::new("x"), &(t.clone() + 1.0));
+    /// assert_eq!(res, (t + 1.0).pow(Expr::new_val(2.0)));
+    /// ```
+    pub fn subs(&self, var: &Symbol, value: &Expr) -> Expr {
+        let pre = |node: &Expr| match node {
+            Expr::Symbol(s) if s == var => Some(value.clone()),
+            // The index of a sum or product is bound, so it is not replaced inside the body
+            Expr::Sum { index, .. } | Expr::Product { index, .. } if index == var => Some(node.clone()),
+            // Substituting a value that mentions the index would capture it, so the index is
+            // renamed to a symbol that appears nowhere else first
+            Expr::Sum { body, index, .. } | Expr::Product { body, index, .. }
+                if value.contains_symbol(index) && body.contains_symbol(var) =>
+            {
+                let taken = |s: &Symbol| s == var || body.contains_symbol(s) || value.contains_symbol(s);
+                let fresh = (1..)
+                    .map(|k| Symbol::indexed(&index.name, k))
+                    .find(|s| !taken(s))
+                    .expect("only finitely many symbols are taken");
+                Some(node.map_symbols(|s| if s == index { fresh.clone() } else { s.clone() }).subs(var, value))
+            }
+            _ => None,
+        };
+        self.transform_pruned(pre, |node| node)
+    }
+
+    /// Replaces every occurrence of the subexpression `pattern` with `replacement`, returning
+    /// a new expression.
+    ///
+    /// Matching is exact structural equality, so the grouping and order of operands matter:
+    /// `x * y * z` is stored as `(x * y) * z` and contains `x * y` but not `y * z`. Call
+    /// `canonicalize` on both the expression and the pattern first to match regardless of
+    /// operand order. The tree is searched top-down; once a subtree matches it is replaced
+    /// as a whole, and the inserted `replacement` is not searched again.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `pattern` - The subexpression to look for.
+    /// * `replacement` - The expression to put in place of each occurrence of `pattern`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let u = Expr::new_var("u");
+    /// let res = (x.clone() * y.clone()).sin() + x.clone() * y.clone();
+    /// assert_eq!(res.replace(&(x * y), &u), u.clone().sin() + u);
+    /// ```
+    pub fn replace(&self, pattern: &Expr, replacement: &Expr) -> Expr {
+        self.transform_pruned(|node| (node == pattern).then(|| replacement.clone()), |node| node)
+    }
+
+    /// Applies `f` to every symbol in the current expression, returning a new expression with
+    /// each symbol replaced by the one `f` returns.
+    ///
+    /// Constants, including `pi` and `e`, and the structure of the tree are left untouched.
+    /// This is useful for renaming variables wholesale, such as namespacing the symbols of a
+    /// generated expression or aligning the variables of two expressions before combining
+    /// them. `f` is called once per occurrence. The tree is rebuilt without recursion.
+    ///
+    /// # Arguments
+    ///
+    /// * `&self` - A reference to the current instance of `Expr`.
+    /// * `f` - The function mapping each symbol to its replacement.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = (x * y + 1.0).map_symbols(|s| Symbol::new(&format!("f_{}", s.name)));
+    /// assert_eq!(res, Expr::new_var("f_x") * Expr::new_var("f_y") + 1.0);
+    /// ```
+    pub fn map_symbols<F: Fn(&Symbol) -> Symbol>(&self, f: F) -> Expr {
+        self.transform_up(|node| match &node {
+            Expr::Symbol(s) => Expr::Symbol(f(s)),
+            // The index of a sum or product is renamed along with its occurrences in the body
+            Expr::Sum { body, index, from, to } => Expr::Sum { body: body.clone(), index: f(index), from: *from, to: *to },
+            Expr::Product { body, index, from, to } =>
+                Expr::Product { body: body.clone(), index: f(index), from: *from, to: *to },
+            _ => node,
+        })
+    }
+
+    /// Writes out every sum in the current expression as a chain of additions, returning a
+    /// new expression.
+    ///
+    /// The body of a sum is repeated once for each value of its index, from first to last,
+    /// with the index replaced by that value as a constant. An empty sum becomes `0`, and
+    /// sums nested in the body are written out first, so an inner sum may use the index of
+    /// an outer one. The result is not simplified.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let i = Expr::new_var("i");
+    /// let x = Expr::new_var("x");
+    /// let res = (x.clone() * i).summation(&Symbol::new("i"), 1, 2);
+    /// assert_eq!(res.expand_sums(), x.clone() * 1.0 + x * 2.0);
+    /// ```
+    pub fn expand_sums(&self) -> Expr {
+        self.transform_up(|node| match &node {
+            Expr::Sum { .. } => node.write_out(),
+            _ => node,
+        })
+    }
+
+    /// Writes out every product in the current expression as a chain of multiplications,
+    /// returning a new expression.
+    ///
+    /// This works like `expand_sums`, with an empty product becoming `1`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let i = Expr::new_var("i");
+    /// let x = Expr::new_var("x");
+    /// let res = Expr::product(x.clone() + i, &Symbol::new("i"), 1, 2);
+    /// assert_eq!(res.expand_products(), (x.clone() + 1.0) * (x + 2.0));
+    /// ```
+    pub fn expand_products(&self) -> Expr {
+        self.transform_up(|node| match &node {
+            Expr::Product { .. } => node.write_out(),
+            _ => node,
+        })
+    }
+
+    /// Writes out every sum and product in the current expression, as `expand_sums` and
+    /// `expand_products` do.
+    pub(crate) fn expand_indexed(&self) -> Expr {
+        self.transform_up(|node| match &node {
+            Expr::Sum { .. } | Expr::Product { .. } => node.write_out(),
+            _ => node,
+        })
+    }
+
+    /// Checks whether the current expression contains a sum or product anywhere in its tree.
+    pub(crate) fn has_indexed(&self) -> bool {
+        self.iter().any(|node| matches!(node, Expr::Sum { .. } | Expr::Product { .. }))
+    }
+
+    /// Writes out the sum or product at the root of the current expression, leaving any
+    /// other expression unchanged.
+    fn write_out(&self) -> Expr {
+        let (Expr::Sum { body, index, from, to } | Expr::Product { body, index, from, to }) = self else {
+            return self.clone();
+        };
+        let terms = (*from..=*to).map(|i| body.subs(index, &Expr::new_val(i as f64)));
+        if let Expr::Sum { .. } = self {
+            terms.reduce(|acc, term| acc + term).unwrap_or_else(Expr::zero)
+        } else {
+            terms.reduce(|acc, term| acc * term).unwrap_or_else(Expr::one)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn subs_constant() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = (x.clone() * y.clone() + x.clone()).subs(&Symbol::new("x"), &Expr::new_val(3.0));
+
+        assert_eq!(res, Expr::new_val(3.0) * y + Expr::new_val(3.0));
+    }
+
+    #[test]
+    fn subs_compound() {
+        let x = Expr::new_var("x");
+        let t = Expr::new_var("t");
+        let poly = x.clone().pow(Expr::new_val(2.0)) + 2.0 * x.clone() + 1.0;
+        let res = poly.subs(&Symbol::new("x"), &(t.clone() + 1.0));
+
+        let shifted = t.clone() + 1.0;
+        assert_eq!(res, shifted.clone().pow(Expr::new_val(2.0)) + 2.0 * shifted + 1.0);
+    }
+
+    #[test]
+    fn subs_nested_powers() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = x.clone().pow(x.clone().pow(x.clone())).subs(&Symbol::new("x"), &y);
+
+        assert_eq!(res, y.clone().pow(y.clone().pow(y)));
+    }
+
+    #[test]
+    fn subs_missing_variable() {
+        let x = Expr::new_var("x");
+        let res = (x.clone() + 1.0).sin();
+
+        assert_eq!(res.subs(&Symbol::new("z"), &Expr::new_val(5.0)), res);
+    }
+
+    #[test]
+    fn replace_leaf() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = (x.clone() + 2.0) * x.clone();
+
+        assert_eq!(res.replace(&x, &y), (y.clone() + 2.0) * y);
+        assert_eq!(res.replace(&Expr::new_val(2.0), &Expr::Pi), (x.clone() + Expr::Pi) * x);
+    }
+
+    #[test]
+    fn replace_compound() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let u = Expr::new_var("u");
+        let res = (x.clone() * y.clone()).pow(Expr::new_val(2.0)) - (x.clone() * y.clone()).exp();
+
+        assert_eq!(res.replace(&(x.clone() * y.clone()), &u), u.clone().pow(Expr::new_val(2.0)) - u.exp());
+        assert_eq!(res.replace(&(y.clone() * x.clone()), &Expr::new_var("u")), res);
+    }
+
+    #[test]
+    fn replace_top_down() {
+        let x = Expr::new_var("x");
+        let u = Expr::new_var("u");
+        // The outer sin(sin(x)) matches first, so the inner sin(x) is never inspected
+        let res = x.clone().sin().sin().sin();
+        assert_eq!(res.replace(&x.clone().sin().sin(), &u), u.clone().sin());
+
+        // The replacement contains the pattern but is not searched again
+        let res = x.clone() + 1.0;
+        assert_eq!(res.replace(&x, &(x.clone() * x.clone())), x.clone() * x.clone() + 1.0);
+    }
+
+    #[test]
+    fn replace_after_canonicalize() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let z = Expr::new_var("z");
+        let u = Expr::new_var("u");
+        let res = (z.clone() * y.clone() * x.clone()).canonicalize();
+
+        assert_eq!(res.replace(&(x.clone() * y.clone()), &u), u * z);
+    }
+
+    #[test]
+    fn map_symbols_renames_all() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = (x.clone() * 2.0 + y.clone().sin()).pow(Expr::Pi) / Expr::new_rational(1, 3);
+        let renamed = res.map_symbols(|s| Symbol::new(&s.name.to_uppercase()));
+
+        let (big_x, big_y) = (Expr::new_var("X"), Expr::new_var("Y"));
+        assert_eq!(renamed, (big_x * 2.0 + big_y.sin()).pow(Expr::Pi) / Expr::new_rational(1, 3));
+        assert!(renamed.variables().into_iter().eq([Symbol::new("X"), Symbol::new("Y")]));
+    }
+
+    #[test]
+    fn map_symbols_single_rename() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let rename = |s: &Symbol| if s.name == "x" { Symbol::new("a") } else { s.clone() };
+
+        assert_eq!((x.clone() + y.clone() * x).map_symbols(rename), Expr::new_var("a") + y * Expr::new_var("a"));
+        assert_eq!((Expr::new_val(2.0) + Expr::E).map_symbols(rename), Expr::new_val(2.0) + Expr::E);
+    }
+
+    #[test]
+    fn map_symbols_to_indexed() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = (x + y).map_symbols(|s| Symbol::indexed("v", if s.name == "x" { 1 } else { 2 }));
+
+        assert_eq!(res, Expr::Symbol(Symbol::indexed("v", 1)) + Expr::Symbol(Symbol::indexed("v", 2)));
+    }
+
+    #[test]
+    fn expand_sums_writes_out_terms() {
+        let (i, j) = (Expr::new_var("i"), Expr::new_var("j"));
+        let (index_i, index_j) = (Symbol::new("i"), Symbol::new("j"));
+        let inner = (i.clone() * j).summation(&index_j, 1, 2);
+        let res = inner.summation(&index_i, 1, 2).expand_sums();
+
+        let (one, two) = (Expr::new_val(1.0), Expr::new_val(2.0));
+        let expected = (one.clone() * one.clone() + one.clone() * two.clone()) + (two.clone() * one + two.clone() * two);
+        assert_eq!(res, expected);
+        assert_eq!(i.summation(&index_i, 1, 0).expand_sums(), Expr::zero());
+    }
+
+    #[test]
+    fn expand_products_writes_out_factors() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let res = (x.clone().pow(i.clone()).product(&index, 1, 3) + i.clone().summation(&index, 1, 2)).expand_products();
+
+        let (one, two, three) = (Expr::new_val(1.0), Expr::new_val(2.0), Expr::new_val(3.0));
+        let expected = x.clone().pow(one) * x.clone().pow(two) * x.clone().pow(three) + i.clone().summation(&index, 1, 2);
+        assert_eq!(res, expected);
+        assert_eq!(x.clone().product(&index, 5, 4).expand_products(), Expr::one());
+        assert_eq!(Expr::product(x.clone() * i.clone(), &index, 1, 2).subs(&index, &x), Expr::product(x * i, &index, 1, 2));
+    }
+
+    #[test]
+    fn sum_index_is_bound() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let res = (x.clone() * i.clone()).summation(&index, 1, 3) + i.clone();
+
+        let subbed = res.subs(&index, &Expr::new_val(5.0));
+        assert_eq!(subbed, (x.clone() * i.clone()).summation(&index, 1, 3) + 5.0);
+        assert!(subbed.variables().into_iter().eq([Symbol::new("x")]));
+
+        let renamed = res.map_symbols(|s| Symbol::new(&s.name.to_uppercase()));
+        assert_eq!(renamed, (Expr::new_var("X") * Expr::new_var("I")).summation(&Symbol::new("I"), 1, 3) + Expr::new_var("I"));
+        assert_eq!(res.eval_partial(&HashMap::from([(index, 1.0)])), (x * i).summation(&Symbol::new("i"), 1, 3) + 1.0);
+    }
+
+    #[test]
+    fn subs_avoids_capturing_index() {
+        let i = Expr::new_var("i");
+        let x = Expr::new_var("x");
+        let index = Symbol::new("i");
+        let res = (i.clone() * x.clone()).summation(&index, 1, 2);
+
+        // The outer i is kept apart from the bound index, which is renamed
+        let subbed = res.subs(&Symbol::new("x"), &i);
+        let fresh = Symbol::indexed("i", 1);
+        assert_eq!(subbed, (Expr::Symbol(fresh.clone()) * i.clone()).summation(&fresh, 1, 2));
+        let vars = HashMap::from([(index.clone(), 10.0)]);
+        assert_eq!(subbed.eval(&vars).unwrap(), 30.0);
+        assert_eq!(res.eval(&HashMap::from([(Symbol::new("x"), 10.0)])).unwrap(), 30.0);
+
+        // The fresh index avoids every symbol already in use
+        let taken = Expr::Symbol(fresh.clone());
+        let res = (i.clone() * x + taken.clone()).product(&index, 1, 2);
+        let subbed = res.subs(&Symbol::new("x"), &i);
+        let fresh = Symbol::indexed("i", 2);
+        assert_eq!(subbed, (Expr::Symbol(fresh.clone()) * i + taken).product(&fresh, 1, 2));
+    }
+}
@@ -0,0 +1,549 @@
+use std::collections::{BTreeSet, HashSet};
+use std::convert::Infallible;
+use std::mem;
+use std::rc::Rc;
+use crate::expr::Expr;
+use crate::symbol::Symbol;
+
+/// The number of operations of each kind in an expression, as returned by `Expr::op_count`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    /// Number of additions.
+    pub add: usize,
+    /// Number of subtractions.
+    pub sub: usize,
+    /// Number of multiplications.
+    pub mul: usize,
+    /// Number of divisions.
+    pub div: usize,
+    /// Number of exponentiations.
+    pub pow: usize,
+    /// Number of negations.
+    pub neg: usize,
+    /// Number of elementary function applications (sine, cosine, exponential, natural
+    /// logarithm, absolute value, sign, square root).
+    pub function: usize,
+}
+
+impl OpCounts {
+    /// Returns the total number of operations of all kinds.
+    pub fn total(&self) -> usize {
+        self.add + self.sub + self.mul + self.div + self.pow + self.neg + self.function
+    }
+}
+
+impl Expr {
+    /// Returns references to the direct sub-expressions of the current expression.
+    ///
+    /// Leaves (constants and symbols) have no children, unary operations and functions have
+    /// one, and binary operations return their left-hand side followed by their right-hand side.
+    pub(crate) fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Const(_) | Expr::Rational(_, _) | Expr::Pi | Expr::E | Expr::Symbol(_) => vec![],
+            Expr::Add(lhs, rhs)
+                | Expr::Sub(lhs, rhs)
+                | Expr::Mul(lhs, rhs)
+                | Expr::Div(lhs, rhs)
+                | Expr::Pow(lhs, rhs) => vec![lhs, rhs],
+            Expr::Neg(expr)
+                | Expr::Sin(expr)
+                | Expr::Cos(expr)
+                | Expr::Exp(expr)
+                | Expr::Ln(expr)
+                | Expr::Abs(expr)
+                | Expr::Sign(expr)
+                | Expr::Sqrt(expr) => vec![expr],
+            Expr::Sum { body, .. } | Expr::Product { body, .. } => vec![body],
+        }
+    }
+
+    /// Returns mutable references to the shared pointers holding the direct sub-expressions of
+    /// the current expression, in the same order as `children`.
+    pub(crate) fn children_mut(&mut self) -> Vec<&mut Rc<Expr>> {
+        match self {
+            Expr::Const(_) | Expr::Rational(_, _) | Expr::Pi | Expr::E | Expr::Symbol(_) => vec![],
+            Expr::Add(lhs, rhs)
+                | Expr::Sub(lhs, rhs)
+                | Expr::Mul(lhs, rhs)
+                | Expr::Div(lhs, rhs)
+                | Expr::Pow(lhs, rhs) => vec![lhs, rhs],
+            Expr::Neg(expr)
+                | Expr::Sin(expr)
+                | Expr::Cos(expr)
+                | Expr::Exp(expr)
+                | Expr::Ln(expr)
+                | Expr::Abs(expr)
+                | Expr::Sign(expr)
+                | Expr::Sqrt(expr) => vec![expr],
+            Expr::Sum { body, .. } | Expr::Product { body, .. } => vec![body],
+        }
+    }
+
+    /// Rebuilds the current expression with `f` applied to each of its direct sub-expressions.
+    ///
+    /// Leaves are returned unchanged. This is the shared building block for passes that
+    /// transform every node of a tree in the same way.
+    pub(crate) fn map_children<F: FnMut(&Expr) -> Expr>(&self, mut f: F) -> Expr {
+        match self {
+            Expr::Const(_) | Expr::Rational(_, _) | Expr::Pi | Expr::E | Expr::Symbol(_) => self.clone(),
+            Expr::Add(lhs, rhs) => Expr::Add(Rc::new(f(lhs)), Rc::new(f(rhs))),
+            Expr::Sub(lhs, rhs) => Expr::Sub(Rc::new(f(lhs)), Rc::new(f(rhs))),
+            Expr::Mul(lhs, rhs) => Expr::Mul(Rc::new(f(lhs)), Rc::new(f(rhs))),
+            Expr::Div(lhs, rhs) => Expr::Div(Rc::new(f(lhs)), Rc::new(f(rhs))),
+            Expr::Pow(lhs, rhs) => Expr::Pow(Rc::new(f(lhs)), Rc::new(f(rhs))),
+            Expr::Neg(expr) => Expr::Neg(Rc::new(f(expr))),
+            Expr::Sin(expr) => Expr::Sin(Rc::new(f(expr))),
+            Expr::Cos(expr) => Expr::Cos(Rc::new(f(expr))),
+            Expr::Exp(expr) => Expr::Exp(Rc::new(f(expr))),
+            Expr::Ln(expr) => Expr::Ln(Rc::new(f(expr))),
+            Expr::Abs(expr) => Expr::Abs(Rc::new(f(expr))),
+            Expr::Sign(expr) => Expr::Sign(Rc::new(f(expr))),
+            Expr::Sqrt(expr) => Expr::Sqrt(Rc::new(f(expr))),
+            Expr::Sum { body, index, from, to } =>
+                Expr::Sum { body: Rc::new(f(body)), index: index.clone(), from: *from, to: *to },
+            Expr::Product { body, index, from, to } =>
+                Expr::Product { body: Rc::new(f(body)), index: index.clone(), from: *from, to: *to },
+        }
+    }
+
+    /// Returns an iterator over every node of the expression in pre-order.
+    ///
+    /// Each node is yielded before its children, and children are visited from left to
+    /// right. The traversal uses an explicit stack, so it does not recurse on deep trees.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = x + Expr::new_val(1.0) / y;
+    /// assert!(res.iter().any(|node| matches!(node, Expr::Div(_, _))));
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &Expr> {
+        Iter { stack: vec![self] }
+    }
+
+    /// Returns the height of the expression tree.
+    ///
+    /// A leaf such as a constant or symbol has depth 1, and every operation adds one level
+    /// above its deepest child.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let z = Expr::new_var("z");
+    /// assert_eq!(((x + y) * z).depth(), 3);
+    /// ```
+    pub fn depth(&self) -> usize {
+        let res: Result<usize, Infallible> =
+            self.try_fold_up(|_, depths| Ok(1 + depths.into_iter().max().unwrap_or(0)));
+        match res {
+            Ok(depth) => depth,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Returns the total number of nodes in the expression tree, including leaves.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let z = Expr::new_var("z");
+    /// assert_eq!(((x + y) * z).node_count(), 5);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Tallies the operations in the expression tree by kind.
+    ///
+    /// Every occurrence is counted, including repeated subtrees, so the counts reflect the
+    /// work a plain `eval` does. Comparing the counts of two equivalent forms gives a rough
+    /// idea of which is cheaper to evaluate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let counts = (x.clone() * x.clone() + x.clone() * 2.0).op_count();
+    /// assert_eq!(counts.add, 1);
+    /// assert_eq!(counts.mul, 2);
+    /// assert_eq!(counts.total(), 3);
+    /// ```
+    pub fn op_count(&self) -> OpCounts {
+        let mut counts = OpCounts::default();
+        for node in self.iter() {
+            match node {
+                Expr::Const(_) | Expr::Rational(_, _) | Expr::Pi | Expr::E | Expr::Symbol(_) => {}
+                Expr::Add(_, _) => counts.add += 1,
+                Expr::Sub(_, _) => counts.sub += 1,
+                Expr::Mul(_, _) => counts.mul += 1,
+                Expr::Div(_, _) => counts.div += 1,
+                Expr::Pow(_, _) => counts.pow += 1,
+                Expr::Neg(_) => counts.neg += 1,
+                Expr::Sin(_)
+                    | Expr::Cos(_)
+                    | Expr::Exp(_)
+                    | Expr::Ln(_)
+                    | Expr::Abs(_)
+                    | Expr::Sign(_)
+                    | Expr::Sqrt(_) => counts.function += 1,
+                // A sum counts as one addition and a product as one multiplication, with
+                // the body counted once
+                Expr::Sum { .. } => counts.add += 1,
+                Expr::Product { .. } => counts.mul += 1,
+            }
+        }
+        counts
+    }
+
+    /// Rebuilds the current expression with its direct sub-expressions replaced, in order, by
+    /// `children`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if fewer children are supplied than the expression has.
+    pub(crate) fn with_children(&self, children: Vec<Expr>) -> Expr {
+        let mut children = children.into_iter();
+        self.map_children(|_| children.next().expect("with_children given too few children"))
+    }
+
+    /// Folds the expression bottom-up, calling `f` on every node with the results already
+    /// computed for its children (in order).
+    ///
+    /// The traversal uses an explicit heap-allocated stack rather than recursion, so it can
+    /// process arbitrarily deep trees. The first error returned by `f` stops the fold.
+    pub(crate) fn try_fold_up<T, E, F>(&self, mut f: F) -> Result<T, E>
+    where
+        F: FnMut(&Expr, Vec<T>) -> Result<T, E>,
+    {
+        enum Frame<'a> {
+            Visit(&'a Expr),
+            Combine(&'a Expr, usize),
+        }
+        let mut frames = vec![Frame::Visit(self)];
+        let mut results: Vec<T> = Vec::new();
+        while let Some(frame) = frames.pop() {
+            match frame {
+                Frame::Visit(node) => {
+                    let children = node.children();
+                    frames.push(Frame::Combine(node, children.len()));
+                    frames.extend(children.into_iter().rev().map(Frame::Visit));
+                }
+                Frame::Combine(node, arity) => {
+                    let args = results.split_off(results.len() - arity);
+                    results.push(f(node, args)?);
+                }
+            }
+        }
+        Ok(results.pop().expect("a fold always produces a result for the root"))
+    }
+
+    /// Returns every distinct node of the expression once, each after its children.
+    ///
+    /// Nodes are told apart by address, so a subtree shared through the same `Rc` is visited
+    /// once however often it occurs, and the work is proportional to the number of distinct
+    /// allocations rather than the size of the fully unshared tree. Like `try_fold_up`, this
+    /// does not recurse.
+    pub(crate) fn distinct_nodes(&self) -> Vec<&Expr> {
+        let mut seen = HashSet::new();
+        let mut order = Vec::new();
+        let mut stack = vec![(self, false)];
+        while let Some((node, ready)) = stack.pop() {
+            if ready {
+                order.push(node);
+            } else if seen.insert(node as *const Expr) {
+                stack.push((node, true));
+                stack.extend(node.children().into_iter().rev().map(|child| (child, false)));
+            }
+        }
+        order
+    }
+
+    /// Rebuilds the expression bottom-up, applying `f` to every node once its children have
+    /// been rebuilt. Like `try_fold_up`, this does not recurse on deep trees.
+    pub(crate) fn transform_up<F: FnMut(Expr) -> Expr>(&self, mut f: F) -> Expr {
+        let res: Result<Expr, Infallible> = self.try_fold_up(|node, children| Ok(f(node.with_children(children))));
+        match res {
+            Ok(expr) => expr,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Rebuilds the expression like `transform_up`, but first offers every node to `pre`. A
+    /// node for which `pre` returns a replacement is replaced as a whole without visiting its
+    /// children; any other node is rebuilt from its rewritten children and passed to `post`.
+    /// Like `try_fold_up`, this does not recurse on deep trees.
+    pub(crate) fn transform_pruned<P, F>(&self, mut pre: P, mut post: F) -> Expr
+    where
+        P: FnMut(&Expr) -> Option<Expr>,
+        F: FnMut(Expr) -> Expr,
+    {
+        enum Frame<'a> {
+            Visit(&'a Expr),
+            Combine(&'a Expr, usize),
+        }
+        let mut frames = vec![Frame::Visit(self)];
+        let mut results: Vec<Expr> = Vec::new();
+        while let Some(frame) = frames.pop() {
+            match frame {
+                Frame::Visit(node) => match pre(node) {
+                    Some(res) => results.push(res),
+                    None => {
+                        let children = node.children();
+                        frames.push(Frame::Combine(node, children.len()));
+                        frames.extend(children.into_iter().rev().map(Frame::Visit));
+                    }
+                },
+                Frame::Combine(node, arity) => {
+                    let args = results.split_off(results.len() - arity);
+                    results.push(post(node.with_children(args)));
+                }
+            }
+        }
+        results.pop().expect("a transform always produces a result for the root")
+    }
+
+    /// Rewrites the expression bottom-up in place, replacing every node by the result of `f`
+    /// once its children have been rewritten.
+    ///
+    /// Nodes are detached from their parents while they are rewritten, so a node that is not
+    /// shared with another tree is reused instead of copied. Shared nodes are copied on write
+    /// with `Rc::make_mut`, leaving the other trees unchanged. Like `transform_up`, this does
+    /// not recurse on deep trees.
+    pub(crate) fn transform_up_mut<F: FnMut(Expr) -> Expr>(&mut self, mut f: F) {
+        enum Frame {
+            Visit(Rc<Expr>),
+            Combine(Rc<Expr>, usize),
+        }
+        let hole = Rc::new(Expr::Const(0.0));
+        let mut frames = vec![Frame::Visit(Rc::new(mem::replace(self, Expr::Const(0.0))))];
+        let mut results: Vec<Rc<Expr>> = Vec::new();
+        while let Some(frame) = frames.pop() {
+            match frame {
+                Frame::Visit(mut node) => {
+                    let children: Vec<Rc<Expr>> = if node.children().is_empty() {
+                        Vec::new()
+                    } else {
+                        Rc::make_mut(&mut node)
+                            .children_mut()
+                            .into_iter()
+                            .map(|child| mem::replace(child, hole.clone()))
+                            .collect()
+                    };
+                    frames.push(Frame::Combine(node, children.len()));
+                    frames.extend(children.into_iter().rev().map(Frame::Visit));
+                }
+                Frame::Combine(mut node, arity) => {
+                    let args = results.split_off(results.len() - arity);
+                    let inner = Rc::make_mut(&mut node);
+                    for (slot, arg) in inner.children_mut().into_iter().zip(args) {
+                        *slot = arg;
+                    }
+                    let rewritten = f(mem::replace(inner, Expr::Const(0.0)));
+                    *inner = rewritten;
+                    results.push(node);
+                }
+            }
+        }
+        let root = results.pop().expect("a rewrite always produces a result for the root");
+        *self = Rc::try_unwrap(root).unwrap_or_else(|root| (*root).clone());
+    }
+
+    /// Returns every distinct symbol appearing in the current expression, in sorted order.
+    ///
+    /// The index of a sum or product is bound inside its body, so it is only included if it
+    /// also appears outside of the sum or product.
+    ///
+    /// This is useful for checking that a map of values covers an expression before calling
+    /// `eval`, and for reporting all of the missing symbols at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let vars: Vec<Symbol> = (x.clone() * y + x).variables().into_iter().collect();
+    /// assert_eq!(vars, vec![Symbol::new("x"), Symbol::new("y")]);
+    /// ```
+    pub fn variables(&self) -> BTreeSet<Symbol> {
+        let res: Result<BTreeSet<Symbol>, Infallible> = self.try_fold_up(|node, children: Vec<BTreeSet<Symbol>>| {
+            // The smaller set is merged into the larger one, so long chains stay cheap
+            let mut vars = children
+                .into_iter()
+                .reduce(|mut acc, mut set| {
+                    if acc.len() < set.len() {
+                        mem::swap(&mut acc, &mut set);
+                    }
+                    acc.extend(set);
+                    acc
+                })
+                .unwrap_or_default();
+            match node {
+                Expr::Symbol(s) => {
+                    vars.insert(s.clone());
+                }
+                Expr::Sum { index, .. } | Expr::Product { index, .. } => {
+                    vars.remove(index);
+                }
+                _ => {}
+            }
+            Ok(vars)
+        });
+        match res {
+            Ok(vars) => vars,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Checks whether the symbol `var` appears anywhere in the current expression.
+    ///
+    /// The tree is searched without recursion and the search stops at the first occurrence.
+    /// An expression that does not contain `var` is unaffected by `subs` and has a zero
+    /// derivative with respect to it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// assert!((x.clone() * y.clone()).contains_symbol(&Symbol::new("x")));
+    /// assert!(!(x * y).contains_symbol(&Symbol::new("z")));
+    /// ```
+    pub fn contains_symbol(&self, var: &Symbol) -> bool {
+        self.iter().any(|node| matches!(node, Expr::Symbol(s) if s == var))
+    }
+
+    /// Checks whether `sub` appears as a subexpression of the current expression, including
+    /// the expression itself.
+    ///
+    /// Matching is exact structural equality, as in `replace`, so the grouping and order of
+    /// operands matter. The search stops at the first occurrence.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    ///
+    /// let x = Expr::new_var("x");
+    /// let y = Expr::new_var("y");
+    /// let res = (x.clone() * y.clone()).sin();
+    /// assert!(res.contains(&x));
+    /// assert!(res.contains(&(x.clone() * y.clone())));
+    /// assert!(!res.contains(&(y * x)));
+    /// ```
+    pub fn contains(&self, sub: &Expr) -> bool {
+        self.iter().any(|node| node == sub)
+    }
+}
+
+/// Pre-order iterator over the nodes of an expression, returned by `Expr::iter`.
+struct Iter<'a> {
+    stack: Vec<&'a Expr>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Expr;
+
+    fn next(&mut self) -> Option<&'a Expr> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children().into_iter().rev());
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iter_pre_order() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let z = Expr::new_var("z");
+        let sum = x.clone() + y.clone();
+        let res = sum.clone() * (-z.clone());
+
+        let nodes: Vec<&Expr> = res.iter().collect();
+        assert_eq!(nodes, vec![&res, &sum, &x, &y, &-z.clone(), &z]);
+        assert_eq!(res.iter().count(), res.node_count());
+    }
+
+    #[test]
+    fn op_count_mixed() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = (x.clone() + y.clone()).pow(Expr::new_val(2.0)) / (x.clone() * y.clone() - 1.0)
+            + (-x.clone()).sin() * y.clone().sqrt()
+            + x.clone() * 3.0;
+
+        let counts = res.op_count();
+        assert_eq!(counts, OpCounts { add: 3, sub: 1, mul: 3, div: 1, pow: 1, neg: 1, function: 2 });
+        assert_eq!(counts.total(), 12);
+        assert_eq!(x.op_count(), OpCounts::default());
+    }
+
+    #[test]
+    fn depth_and_node_count() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let z = Expr::new_var("z");
+        let res = (x.clone() + y.clone()) * z.clone();
+
+        assert_eq!(res.depth(), 3);
+        assert_eq!(res.node_count(), 5);
+        assert_eq!(Expr::new_val(1.0).depth(), 1);
+        assert_eq!(Expr::new_val(1.0).node_count(), 1);
+        assert_eq!((-x.sin()).depth(), 3);
+    }
+
+    #[test]
+    fn variables_distinct_sorted() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = y.clone() * x.clone() + x.clone();
+
+        let expected: BTreeSet<Symbol> = [Symbol::new("x"), Symbol::new("y")].into_iter().collect();
+        assert_eq!(res.variables(), expected);
+    }
+
+    #[test]
+    fn contains_symbol_and_subexpression() {
+        let x = Expr::new_var("x");
+        let y = Expr::new_var("y");
+        let res = x.clone() * y.clone();
+
+        assert!(res.contains_symbol(&Symbol::new("x")));
+        assert!(!res.contains_symbol(&Symbol::new("z")));
+        assert!(!Expr::Pi.contains_symbol(&Symbol::new("x")));
+        assert!(res.contains(&x));
+        assert!(res.contains(&res));
+        assert!(!res.contains(&Expr::new_var("z")));
+        assert!(((x.clone() + 1.0).exp() - 2.0).contains(&(x.clone() + 1.0)));
+        assert!(!((x.clone() + 1.0).exp() - 2.0).contains(&(1.0 + x.clone())));
+    }
+
+    #[test]
+    fn variables_of_constant() {
+        let res = Expr::new_val(2.0) + Expr::new_rational(1, 2);
+        assert!(res.variables().is_empty());
+    }
+}
@@ -9,17 +9,27 @@
 //! 
 //! * `Expr`: An enum representing different kinds of mathematical expressions.
 //! * `Symbol`: A struct representing a symbolic variable.
+//! * `SymbolTable`: A struct interning symbol names as small integer `SymbolId`s.
+//! * `ExprMatrix`: A struct representing a matrix of `Expr` entries, with a symbolic determinant.
 //! 
 //! The library also provides several implementations for `Expr`:
 //!
-//! * Constructors for creating new `Expr` instances.
-//! * A `Display` implementation for converting an `Expr` to a string.
-//! * A `simplify` method for simplifying an `Expr`.
+//! * Constructors for creating new `Expr` instances, including `zero` and `one`.
+//! * A `Display` implementation for converting an `Expr` to a string, and a `display_with` method taking `DisplayOptions`.
+//! * A `simplify` method for simplifying an `Expr`, and a `rewrite` method for applying user-supplied `Rule`s.
 //! * A `expand` method for basic expanding of an `Expr`.
-//! * An `eval` method for evaluating an `Expr`.
+//! * `summation` and `product` constructors for finite sums and products over an index, and `expand_sums` and `expand_products` for writing them out.
+//! * An `eval` method for evaluating an `Expr`, plus `eval_batch` and `compile` for evaluating one `Expr` at many points.
+//! * An `eval_complex` method for evaluating an `Expr` over the complex numbers, behind the `complex` feature.
+//! * A `to_latex` method for rendering an `Expr` as LaTeX, `to_python` for SymPy syntax and `to_c` for C code.
+//! * `to_sexpr` and `from_sexpr` methods for writing and parsing an `Expr` as a prefix S-expression.
+//! * A `pretty` method for rendering an `Expr` as multi-line text with stacked fractions.
+//! * `diff`, `gradient` and `integrate` methods for symbolic calculus on an `Expr`, `diff_n` for higher derivatives, `diff_memo` for trees with heavy repetition, `limit` for limits at a point, and `solve_newton` for numeric roots.
+//! * `is_polynomial`, `poly_coeffs`, `from_poly_coeffs`, `poly_gcd`, `roots`, `factor_quadratic`, `solve_linear` and `singularities` methods for treating an `Expr` as a univariate polynomial.
 //!
 //! The library also includes operator overloads for `Expr`, located in the `operators` module, 
-//! which allow `Expr` instances to be combined using standard mathematical operators.
+//! which allow `Expr` instances to be combined using standard mathematical operators, and `From`
+//! conversions from `f64`, `i32` and `&str` (as a symbol name), plus `Sum` and `Product` for folding iterators of `Expr`.
 //!
 //! ## Examples
 //! 
@@ -40,8 +50,8 @@
 //! let x = Expr::new_var("x");
 //! let y = Expr::new_var("y");
 //! let z = Expr::new_var("z");
-//! let res = (x.clone() + x.clone() + y.clone() * y.clone()).pow(z);
-//! println!("{}", res);  // prints: "(2x + y^2)^z"
+//! let res = (&x + &x + &y * &y).pow(z);
+//! println!("{}", res);  // prints: "(x + x + y * y)^z"
 //! println!("{}", res.simplify());  // prints: "(2x + y^2)^z"
 //!
 //! let mut vars: HashMap<Symbol, f64> = HashMap::new();
@@ -55,4 +65,5 @@
 
 pub mod symbol;
 pub mod expr;
+pub mod matrix;
 
@@ -39,8 +39,8 @@ fn main() {
     let x = Expr::new_var("x");
     let y = Expr::new_var("y");
     let z = Expr::new_var("z");
-    let res = (x.clone() + x.clone() + y.clone() * y.clone()).pow(z);
-    println!("{}", res);  // prints: "(2x + y^2)^z"
+    let res = (&x + &x + &y * &y).pow(z);
+    println!("{}", res);  // prints: "(x + x + y * y)^z"
     println!("{}", res.simplify());  // prints: "(2x + y^2)^z"
 
     let mut vars: HashMap<Symbol, f64> = HashMap::new();
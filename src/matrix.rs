@@ -0,0 +1,180 @@
+use std::rc::Rc;
+use crate::expr::Expr;
+
+/// Enum representing possible errors that can occur while building or using an `ExprMatrix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The rows do not all have the same length.
+    Ragged,
+    /// The operation needs a square matrix, but the matrix has the given numbers of rows and
+    /// columns.
+    NotSquare(usize, usize),
+}
+
+/// Represents a matrix whose entries are expressions.
+///
+/// `ExprMatrix` is a thin wrapper around its rows, for small symbolic linear algebra tasks.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExprMatrix {
+    rows: Vec<Vec<Expr>>,
+}
+
+impl ExprMatrix {
+    /// Creates a new `ExprMatrix` from its rows.
+    ///
+    /// Returns `Err(MatrixError::Ragged)` if the rows do not all have the same length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::matrix::{ExprMatrix, MatrixError};
+    ///
+    /// let x = Expr::new_var("x");
+    /// let m = ExprMatrix::new(vec![vec![x.clone(), Expr::new_val(1.0)], vec![Expr::new_val(0.0), x.clone()]]);
+    /// assert!(m.is_ok());
+    ///
+    /// let ragged = ExprMatrix::new(vec![vec![x.clone()], vec![x.clone(), x]]);
+    /// assert_eq!(ragged, Err(MatrixError::Ragged));
+    /// ```
+    pub fn new(rows: Vec<Vec<Expr>>) -> Result<ExprMatrix, MatrixError> {
+        match rows.first() {
+            Some(first) if rows.iter().any(|row| row.len() != first.len()) => Err(MatrixError::Ragged),
+            _ => Ok(ExprMatrix { rows }),
+        }
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.len())
+    }
+
+    /// Returns the entry at row `i` and column `j`, or `None` if it is out of range.
+    pub fn get(&self, i: usize, j: usize) -> Option<&Expr> {
+        self.rows.get(i).and_then(|row| row.get(j))
+    }
+
+    /// Computes the determinant of the matrix by cofactor expansion along the first row.
+    ///
+    /// The result is an unsimplified tree of additions, subtractions and multiplications of
+    /// the entries; call `simplify` or `to_sum_of_products` on it to tidy it up. The number of
+    /// terms grows factorially with the size of the matrix, so this is meant for small
+    /// matrices of up to about 4x4. The determinant of the empty matrix is `1`.
+    ///
+    /// Returns `Err(MatrixError::NotSquare)` if the matrix is not square.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::expr::Expr;
+    /// use symbolic_math::matrix::ExprMatrix;
+    ///
+    /// let a = Expr::new_var("a");
+    /// let b = Expr::new_var("b");
+    /// let c = Expr::new_var("c");
+    /// let d = Expr::new_var("d");
+    /// let m = ExprMatrix::new(vec![vec![a.clone(), b.clone()], vec![c.clone(), d.clone()]]).unwrap();
+    /// assert_eq!(m.det().unwrap(), a * d - b * c);
+    /// ```
+    pub fn det(&self) -> Result<Expr, MatrixError> {
+        if self.rows() != self.cols() {
+            return Err(MatrixError::NotSquare(self.rows(), self.cols()));
+        }
+        let cols: Vec<usize> = (0..self.cols()).collect();
+        Ok(self.minor_det(0, &cols))
+    }
+
+    /// Computes the determinant of the submatrix made of the rows from `row` down and the
+    /// columns in `cols`, which has as many columns as rows.
+    fn minor_det(&self, row: usize, cols: &[usize]) -> Expr {
+        match cols {
+            [] => Expr::new_val(1.0),
+            [col] => self.rows[row][*col].clone(),
+            _ => {
+                let mut acc: Option<Expr> = None;
+                for (k, col) in cols.iter().enumerate() {
+                    let rest: Vec<usize> = cols.iter().copied().filter(|c| c != col).collect();
+                    let term = Expr::Mul(Rc::new(self.rows[row][*col].clone()), Rc::new(self.minor_det(row + 1, &rest)));
+                    acc = Some(match acc {
+                        None => term,
+                        Some(acc) if k % 2 == 1 => Expr::Sub(Rc::new(acc), Rc::new(term)),
+                        Some(acc) => Expr::Add(Rc::new(acc), Rc::new(term)),
+                    });
+                }
+                acc.expect("a minor with columns has at least one term")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::symbol::Symbol;
+
+    fn vars(names: &[&str]) -> Vec<Expr> {
+        names.iter().map(|name| Expr::new_var(name)).collect()
+    }
+
+    #[test]
+    fn det_two_by_two() {
+        let v = vars(&["a", "b", "c", "d"]);
+        let m = ExprMatrix::new(vec![vec![v[0].clone(), v[1].clone()], vec![v[2].clone(), v[3].clone()]]).unwrap();
+
+        assert_eq!(m.det().unwrap(), v[0].clone() * v[3].clone() - v[1].clone() * v[2].clone());
+    }
+
+    #[test]
+    fn det_three_by_three() {
+        let v = vars(&["a", "b", "c", "d", "e", "f", "g", "h", "i"]);
+        let m = ExprMatrix::new(v.chunks(3).map(|row| row.to_vec()).collect()).unwrap();
+        let det = m.det().unwrap();
+
+        let (a, b, c, d, e, f, g, h, i) = (&v[0], &v[1], &v[2], &v[3], &v[4], &v[5], &v[6], &v[7], &v[8]);
+        let expected = a * &(e * i - f * h) - b * &(d * i - f * g) + c * &(d * h - e * g);
+        assert_eq!(det, expected);
+    }
+
+    #[test]
+    fn det_four_by_four_numeric() {
+        let rows = [[2.0, 0.0, 1.0, 3.0], [1.0, 1.0, 0.0, 2.0], [0.0, 4.0, 1.0, 1.0], [3.0, 1.0, 2.0, 0.0]];
+        let m = ExprMatrix::new(rows.iter().map(|row| row.iter().map(|c| Expr::new_val(*c)).collect()).collect()).unwrap();
+
+        assert_eq!(m.det().unwrap().eval(&HashMap::new()).unwrap(), -28.0);
+    }
+
+    #[test]
+    fn det_symbolic_simplifies() {
+        let x = Expr::new_var("x");
+        let m = ExprMatrix::new(vec![
+            vec![x.clone(), Expr::new_val(1.0)],
+            vec![Expr::new_val(1.0), x.clone()],
+        ]).unwrap();
+        let det = m.det().unwrap();
+
+        assert_eq!(det.to_sum_of_products(), x.clone().pow(Expr::new_val(2.0)) - 1.0);
+        let mut values = HashMap::new();
+        values.insert(Symbol::new("x"), 3.0);
+        assert_eq!(det.eval(&values).unwrap(), 8.0);
+    }
+
+    #[test]
+    fn det_rejects_non_square() {
+        let v = vars(&["a", "b"]);
+        let m = ExprMatrix::new(vec![v.clone()]).unwrap();
+
+        assert_eq!(m.rows(), 1);
+        assert_eq!(m.cols(), 2);
+        assert_eq!(m.get(0, 1), Some(&v[1]));
+        assert_eq!(m.get(1, 0), None);
+        assert_eq!(m.det(), Err(MatrixError::NotSquare(1, 2)));
+        assert_eq!(ExprMatrix::new(vec![]).unwrap().det(), Ok(Expr::new_val(1.0)));
+    }
+}
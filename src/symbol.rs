@@ -1,11 +1,32 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
 /// Represents a symbolic variable in a mathematical expression.
 ///
-/// `Symbol` holds a `String` that is its name. It provides functionality to
+/// `Symbol` holds a `String` that is its name, and an optional subscript for indexed
+/// families of variables such as `x_1, x_2, ...`. It provides functionality to
 /// create a new `Symbol` from a string slice. 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Symbol {
     /// The name of the symbolic variable.
     pub name: String,
+    /// The subscript of an indexed variable, such as the `1` in `x_1`.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub index: Option<usize>,
+}
+
+/// Enum representing the reasons a name can be rejected by `Symbol::try_new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolError {
+    /// The name is the empty string.
+    Empty,
+    /// The name starts with a character that is not a letter or an underscore.
+    InvalidStart(char),
+    /// The name contains a character outside `[A-Za-z0-9_]`.
+    InvalidChar(char),
 }
 
 impl Symbol {
@@ -15,7 +36,174 @@ impl Symbol {
     ///
     /// Note: Should normally use s.get_symbol() for Expr::Symbol(s) instead.
     pub fn new(name: &str) -> Symbol {
-        Symbol { name: name.into() }
+        Symbol { name: name.into(), index: None }
+    }
+
+    /// Creates a new `Symbol` with the given name and subscript.
+    ///
+    /// Indexed symbols are distinct from each other and from the plain symbol with the same
+    /// name, and `x_1` is displayed as `x_1` and rendered as `x_{1}` in LaTeX. A symbol
+    /// created with `Symbol::new("x_1")` displays the same way but is a different symbol.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::symbol::Symbol;
+    ///
+    /// let vars: Vec<Symbol> = (1..=3).map(|i| Symbol::indexed("x", i)).collect();
+    /// assert_ne!(vars[0], vars[1]);
+    /// assert_eq!(vars[2].index, Some(3));
+    /// ```
+    pub fn indexed(name: &str, index: usize) -> Symbol {
+        Symbol { name: name.into(), index: Some(index) }
+    }
+
+    /// Creates a new `Symbol` from a string slice, checking that it is a valid identifier.
+    ///
+    /// A valid name is non-empty, starts with an ASCII letter or an underscore, and contains
+    /// only ASCII letters, digits and underscores. Unlike `new`, which accepts any string,
+    /// this guarantees the name can be written out and read back unambiguously.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::symbol::{Symbol, SymbolError};
+    ///
+    /// assert_eq!(Symbol::try_new("x_1"), Ok(Symbol::new("x_1")));
+    /// assert_eq!(Symbol::try_new("2x"), Err(SymbolError::InvalidStart('2')));
+    /// ```
+    pub fn try_new(name: &str) -> Result<Symbol, SymbolError> {
+        let mut chars = name.chars();
+        match chars.next() {
+            None => return Err(SymbolError::Empty),
+            Some(c) if !(c.is_ascii_alphabetic() || c == '_') => return Err(SymbolError::InvalidStart(c)),
+            Some(_) => {}
+        }
+        match chars.find(|c| !(c.is_ascii_alphanumeric() || *c == '_')) {
+            Some(c) => Err(SymbolError::InvalidChar(c)),
+            None => Ok(Symbol::new(name)),
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    /// Formats the symbol as its name, followed by `_` and the subscript if it is indexed, so
+    /// `Symbol::indexed("x", 1)` is written `x_1`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{}_{}", self.name, index),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Hands out a distinct tag to every `SymbolTable`, so ids from different tables never match.
+static NEXT_TABLE: AtomicU32 = AtomicU32::new(0);
+
+/// A small integer standing for a symbol name interned in a `SymbolTable`.
+///
+/// Comparing and hashing ids is a couple of integer operations, however long the names are.
+/// Each id carries a tag for the table that handed it out, and is only resolved by that table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId {
+    table: u32,
+    index: u32,
+}
+
+/// Interns symbol names, storing each distinct name once and handing out a `SymbolId` for it.
+///
+/// The table is an optional layer on top of `Symbol`: callers that handle many repeated names
+/// can key their own data by `SymbolId` and look the name up only when they need it, for
+/// example to build a `Symbol` for `eval`. Ids are assigned in the order names are first
+/// interned.
+///
+/// `Symbol` itself deliberately keeps its owned name rather than an id. Its `name` field is
+/// public and read and built directly by callers, and symbols are ordered by name, which the
+/// canonical ordering of expressions relies on; neither survives replacing the name with an
+/// index into a table.
+#[derive(Debug)]
+pub struct SymbolTable {
+    tag: u32,
+    names: Vec<Rc<str>>,
+    ids: HashMap<Rc<str>, SymbolId>,
+}
+
+impl Default for SymbolTable {
+    fn default() -> SymbolTable {
+        SymbolTable { tag: NEXT_TABLE.fetch_add(1, Ordering::Relaxed), names: Vec::new(), ids: HashMap::new() }
+    }
+}
+
+impl SymbolTable {
+    /// Creates a new, empty `SymbolTable`.
+    pub fn new() -> SymbolTable {
+        SymbolTable::default()
+    }
+
+    /// Returns the id of `name`, adding it to the table first if it is new.
+    ///
+    /// Interning the same name again returns the same id without storing the name twice.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::symbol::SymbolTable;
+    ///
+    /// let mut table = SymbolTable::new();
+    /// let x = table.intern("x");
+    /// assert_eq!(table.intern("x"), x);
+    /// assert_ne!(table.intern("y"), x);
+    /// assert_eq!(table.name(x), Some("x"));
+    /// ```
+    pub fn intern(&mut self, name: &str) -> SymbolId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let index = u32::try_from(self.names.len()).expect("a symbol table holds fewer than 2^32 names");
+        let id = SymbolId { table: self.tag, index };
+        let name: Rc<str> = Rc::from(name);
+        self.names.push(name.clone());
+        self.ids.insert(name, id);
+        id
+    }
+
+    /// Returns the id of `name` if it has been interned, without adding it.
+    pub fn get(&self, name: &str) -> Option<SymbolId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the name interned as `id`, or `None` if the id is not from this table.
+    pub fn name(&self, id: SymbolId) -> Option<&str> {
+        if id.table != self.tag {
+            return None;
+        }
+        self.names.get(id.index as usize).map(|name| &**name)
+    }
+
+    /// Returns a plain `Symbol` with the name interned as `id`, or `None` if the id is not
+    /// from this table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_math::symbol::{Symbol, SymbolTable};
+    ///
+    /// let mut table = SymbolTable::new();
+    /// let theta = table.intern("theta");
+    /// assert_eq!(table.symbol(theta), Some(Symbol::new("theta")));
+    /// ```
+    pub fn symbol(&self, id: SymbolId) -> Option<Symbol> {
+        self.name(id).map(Symbol::new)
+    }
+
+    /// Returns the number of distinct names in the table.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Checks whether no names have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
     }
 }
 
@@ -28,4 +216,92 @@ mod tests {
         let symbol = Symbol::new("x");
         assert_eq!(symbol.name, "x");
     }
+
+    #[test]
+    fn try_new_valid() {
+        for name in ["x", "X", "_", "_tmp", "theta", "x_1", "a2b3"] {
+            assert_eq!(Symbol::try_new(name), Ok(Symbol::new(name)));
+        }
+    }
+
+    #[test]
+    fn try_new_invalid() {
+        assert_eq!(Symbol::try_new(""), Err(SymbolError::Empty));
+        assert_eq!(Symbol::try_new("2x"), Err(SymbolError::InvalidStart('2')));
+        assert_eq!(Symbol::try_new(" "), Err(SymbolError::InvalidStart(' ')));
+        assert_eq!(Symbol::try_new("a+b"), Err(SymbolError::InvalidChar('+')));
+        assert_eq!(Symbol::try_new("x y"), Err(SymbolError::InvalidChar(' ')));
+        assert_eq!(Symbol::try_new("é"), Err(SymbolError::InvalidStart('é')));
+    }
+
+    #[test]
+    fn display_plain_and_indexed() {
+        assert_eq!(Symbol::new("theta").to_string(), "theta");
+        assert_eq!(Symbol::indexed("x", 1).to_string(), "x_1");
+        assert_eq!(Symbol::indexed("x", 12).to_string(), Symbol::new("x_12").to_string());
+    }
+
+    #[test]
+    fn indexed_symbols_distinct() {
+        use std::collections::HashSet;
+
+        let x1 = Symbol::indexed("x", 1);
+        let x2 = Symbol::indexed("x", 2);
+        assert_ne!(x1, x2);
+        assert_ne!(x1, Symbol::new("x"));
+        assert_ne!(x1, Symbol::new("x_1"));
+        assert_eq!(x1, Symbol::indexed("x", 1));
+        assert!(x1 < x2);
+
+        let set: HashSet<Symbol> = [x1.clone(), x2, x1, Symbol::new("x")].into_iter().collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let symbol = Symbol::new("x");
+        let json = serde_json::to_string(&symbol).unwrap();
+        assert_eq!(json, r#"{"name":"x"}"#);
+        assert_eq!(serde_json::from_str::<Symbol>(&json).unwrap(), symbol);
+
+        let symbol = Symbol::indexed("x", 2);
+        let json = serde_json::to_string(&symbol).unwrap();
+        assert_eq!(serde_json::from_str::<Symbol>(&json).unwrap(), symbol);
+    }
+
+    #[test]
+    fn interned_names_share_id() {
+        let mut table = SymbolTable::new();
+        let x = table.intern("x");
+        let y = table.intern("y");
+
+        assert_eq!(table.intern("x"), x);
+        assert_ne!(x, y);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get("x"), Some(x));
+        assert_eq!(table.get("z"), None);
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn interned_names_resolve() {
+        let mut table = SymbolTable::new();
+        assert!(table.is_empty());
+        let ids: Vec<SymbolId> = ["alpha", "beta", "alpha", "x_1"].iter().map(|name| table.intern(name)).collect();
+
+        assert_eq!(ids[0], ids[2]);
+        assert_eq!(table.name(ids[1]), Some("beta"));
+        assert_eq!(table.symbol(ids[3]), Some(Symbol::new("x_1")));
+
+        // Another table holding names at the same positions still rejects the ids
+        let mut other = SymbolTable::new();
+        for name in ["zzz", "beta"] {
+            other.intern(name);
+        }
+        assert_eq!(other.name(ids[0]), None);
+        assert_eq!(other.name(ids[1]), None);
+        assert_eq!(other.symbol(ids[0]), None);
+        assert_eq!(table.name(other.intern("beta")), None);
+    }
 }